@@ -3,10 +3,12 @@
 //! Run with: cargo bench
 
 use d2o::{
-    BashGenerator, Command, ElvishGenerator, FishGenerator, JsonGenerator, Layout,
-    NushellGenerator, Opt, OptName, OptNameType, Postprocessor, ZshGenerator,
+    BashGenerator, Command, ElvishGenerator, FishGenerator, IoHandler, JsonGenerator, Layout,
+    NushellGenerator, Opt, OptName, OptNameType, Parser, Postprocessor, TcshGenerator,
+    ZshGenerator,
 };
 use divan::AllocProfiler;
+use divan::counter::BytesCount;
 use divan::{Bencher, black_box};
 use ecow::{EcoString, EcoVec, eco_vec};
 use mimalloc_safe::MiMalloc;
@@ -88,20 +90,20 @@ fn sample_command_small() -> Command {
                     OptName::new(EcoString::from("-h"), OptNameType::ShortType),
                     OptName::new(EcoString::from("--help"), OptNameType::LongType),
                 ],
-                argument: EcoString::new(),
                 description: EcoString::from("Print help"),
+                ..Default::default()
             },
             Opt {
                 names: eco_vec![
                     OptName::new(EcoString::from("-v"), OptNameType::ShortType),
                     OptName::new(EcoString::from("--verbose"), OptNameType::LongType),
                 ],
-                argument: EcoString::new(),
                 description: EcoString::from("Verbose output"),
+                ..Default::default()
             },
         ],
-        subcommands: eco_vec![],
         version: EcoString::from("1.0.0"),
+        ..Default::default()
     }
 }
 
@@ -118,6 +120,7 @@ fn sample_command_medium() -> Command {
                 EcoString::new()
             },
             description: EcoString::from(format!("Option number {}", i)),
+            ..Default::default()
         })
         .collect();
 
@@ -125,10 +128,7 @@ fn sample_command_medium() -> Command {
         .map(|i| Command {
             name: EcoString::from(format!("sub{}", i)),
             description: EcoString::from(format!("Subcommand {}", i)),
-            usage: EcoString::new(),
-            options: eco_vec![],
-            subcommands: eco_vec![],
-            version: EcoString::new(),
+            ..Default::default()
         })
         .collect();
 
@@ -139,6 +139,7 @@ fn sample_command_medium() -> Command {
         options,
         subcommands,
         version: EcoString::from("2.0.0"),
+        ..Default::default()
     }
 }
 
@@ -164,6 +165,7 @@ fn sample_command_large() -> Command {
                 "This is the description for option number {}",
                 i
             )),
+            ..Default::default()
         })
         .collect();
 
@@ -172,8 +174,8 @@ fn sample_command_large() -> Command {
         description: EcoString::from("A large command with many options"),
         usage: EcoString::from("largecmd [OPTIONS]"),
         options,
-        subcommands: eco_vec![],
         version: EcoString::from("3.0.0"),
+        ..Default::default()
     }
 }
 
@@ -199,6 +201,51 @@ fn parse_blockwise_large(bencher: Bencher) {
     bencher.bench_local(|| Layout::parse_blockwise(black_box(&help)));
 }
 
+#[divan::bench]
+fn parse_blockwise_large_throughput(bencher: Bencher) {
+    let help = sample_help_large();
+    bencher
+        .counter(BytesCount::of_str(&help))
+        .bench_local(|| Layout::parse_blockwise(black_box(&help)));
+}
+
+/// Man page text rendered at `width` columns, wrapping descriptions the way man does.
+fn sample_manpage(width: usize) -> String {
+    let mut page = String::from("OPTIONS\n");
+    for i in 0..100 {
+        let opt = format!("       -o{i}, --option-{i}");
+        let desc = format!(
+            "Option number {i} changes how the command behaves when it processes its input files and writes results"
+        );
+        let mut line = format!("{opt:<30}");
+        for word in desc.split(' ') {
+            if line.len() + word.len() + 1 > width {
+                page.push_str(line.trim_end());
+                page.push('\n');
+                line = " ".repeat(30);
+            }
+            line.push_str(word);
+            line.push(' ');
+        }
+        page.push_str(line.trim_end());
+        page.push_str("\n\n");
+    }
+    page
+}
+
+#[divan::bench(args = [80, 200])]
+fn parse_manpage_width(bencher: Bencher, width: usize) {
+    let page = sample_manpage(width);
+    bencher.bench_local(|| Layout::parse_blockwise(black_box(&page)).len());
+}
+
+#[divan::bench]
+fn split_into_blocks_large(bencher: Bencher) {
+    let help = sample_help_large();
+    let offset = help.find('-').unwrap_or(0);
+    bencher.bench_local(|| Layout::split_into_blocks_from_offset(black_box(&help), offset));
+}
+
 #[divan::bench]
 fn parse_usage_small(bencher: Bencher) {
     let help = sample_help_small();
@@ -223,6 +270,31 @@ fn preprocess_blockwise_medium(bencher: Bencher) {
     bencher.bench_local(|| Layout::preprocess_blockwise(black_box(&help)));
 }
 
+const SINGLE_OPTION_LINE: &str = "  -v, --verbose           Enable verbose output";
+
+#[divan::bench]
+fn preprocess_single_line(bencher: Bencher) {
+    bencher.bench_local(|| Parser::preprocess_single(black_box(SINGLE_OPTION_LINE)));
+}
+
+#[divan::bench]
+fn preprocess_multi_single_line(bencher: Bencher) {
+    bencher.bench_local(|| Parser::preprocess(black_box(SINGLE_OPTION_LINE)));
+}
+
+#[divan::bench]
+fn preprocess_medium_heuristic(bencher: Bencher) {
+    let help = sample_help_medium();
+    bencher.bench_local(|| Parser::preprocess_with_column(black_box(&help), None));
+}
+
+#[divan::bench]
+fn preprocess_medium_known_column(bencher: Bencher) {
+    let help = sample_help_medium();
+    let col = Layout::get_description_offset(&help);
+    bencher.bench_local(|| Parser::preprocess_with_column(black_box(&help), col));
+}
+
 // ============================================================================
 // Generator benchmarks
 // ============================================================================
@@ -293,6 +365,12 @@ fn generate_nushell_medium(bencher: Bencher) {
     bencher.bench_local(|| NushellGenerator::generate(black_box(&cmd)));
 }
 
+#[divan::bench]
+fn generate_tcsh_medium(bencher: Bencher) {
+    let cmd = sample_command_medium();
+    bencher.bench_local(|| TcshGenerator::generate(black_box(&cmd)));
+}
+
 #[divan::bench]
 fn generate_json_small(bencher: Bencher) {
     let cmd = sample_command_small();
@@ -339,6 +417,22 @@ fn postprocess_remove_bullets(bencher: Bencher) {
     bencher.bench_local(|| Postprocessor::remove_bullets(black_box(&text)));
 }
 
+#[divan::bench]
+fn normalize_text_clean(bencher: Bencher) {
+    let help = sample_help_large()
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    bencher.bench_local(|| IoHandler::normalize_text(black_box(&help)));
+}
+
+#[divan::bench]
+fn normalize_text_dirty(bencher: Bencher) {
+    let help = sample_help_large().replace("  ", "\t");
+    bencher.bench_local(|| IoHandler::normalize_text(black_box(&help)));
+}
+
 // ============================================================================
 // JSON serialization benchmarks
 // ============================================================================
@@ -470,6 +564,7 @@ fn sample_command_massive() -> Command {
                 "This is the description for option number {} with additional context",
                 i
             )),
+            ..Default::default()
         })
         .collect();
 
@@ -478,11 +573,44 @@ fn sample_command_massive() -> Command {
         description: EcoString::from("A massive command with thousands of options"),
         usage: EcoString::from("massivecmd [OPTIONS]"),
         options,
-        subcommands: eco_vec![],
         version: EcoString::from("1.0.0"),
+        ..Default::default()
     }
 }
 
+const REPEATED_LONG_NAMES: [&str; 3] = [
+    "--ignore-whitespace-changes",
+    "--no-verify-signatures",
+    "--show-current-patch",
+];
+
+/// A tree of 500 subcommands that all repeat the same long option names.
+fn build_repeated_name_tree(make_name: impl Fn(&str) -> OptName) -> Command {
+    let mut root = Command::new(EcoString::from("treecmd"));
+    for i in 0..500 {
+        let mut sub = Command::new(EcoString::from(format!("sub{}", i)));
+        for raw in REPEATED_LONG_NAMES {
+            sub.options.push(Opt {
+                names: eco_vec![make_name(raw)],
+                ..Default::default()
+            });
+        }
+        root.subcommands.push(sub);
+    }
+    root
+}
+
+#[divan::bench]
+fn build_repeated_names_plain() -> Command {
+    build_repeated_name_tree(|raw| OptName::new(EcoString::from(raw), OptNameType::LongType))
+}
+
+#[cfg(feature = "intern")]
+#[divan::bench]
+fn build_repeated_names_interned() -> Command {
+    build_repeated_name_tree(|raw| OptName::new_interned(raw, OptNameType::LongType))
+}
+
 #[divan::bench]
 fn parse_blockwise_massive(bencher: Bencher) {
     let help = sample_help_massive();
@@ -5,6 +5,9 @@ use clap_complete_nushell::Nushell;
 use std::fs;
 use std::path::PathBuf;
 
+#[path = "src/util.rs"]
+mod util;
+
 include!("src/cli.rs");
 
 fn main() {
@@ -1,10 +1,101 @@
+use crate::util::levenshtein;
+use clap::builder::{PossibleValue, TypedValueParser};
+use clap::error::{ContextKind, ContextValue, ErrorKind};
 use clap::{Parser, ValueEnum};
 use clap_verbosity_flag::Verbosity;
 
+/// Default subcommand depth for parsed help text
+pub const DEFAULT_DEPTH: usize = 4;
+
 /// Default cache TTL in hours (24 hours)
 pub const DEFAULT_CACHE_TTL_HOURS: u64 = 24;
 
+/// Default `MANWIDTH`, wide enough that man does not wrap option descriptions
+pub const DEFAULT_MAN_WIDTH: u16 = 200;
+
+/// Default `COLUMNS` for `--help` runs, wide enough that clap-style tools do not
+/// wrap option descriptions
+pub const DEFAULT_HELP_COLUMNS: u16 = 200;
+
+/// Default number of spaces a tab in help text expands to
+pub const DEFAULT_TAB_WIDTH: u8 = 8;
+
+/// Output formats accepted by `--format`
+pub const FORMATS: &[&str] = &[
+    "bash",
+    "zsh",
+    "fish",
+    "json",
+    "yaml",
+    "native",
+    "elvish",
+    "nushell",
+    "tcsh",
+    "xonsh",
+    "carapace",
+    "powershell",
+    "markdown-table",
+];
+
+/// Value parser for `--format` that suggests the closest valid format on typos
+#[derive(Clone, Debug)]
+pub struct FormatValueParser;
+
+impl TypedValueParser for FormatValueParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        let value = value.to_string_lossy();
+        if FORMATS.contains(&value.as_ref()) {
+            return Ok(value.into_owned());
+        }
+
+        let mut err = clap::Error::new(ErrorKind::InvalidValue).with_cmd(cmd);
+        if let Some(arg) = arg {
+            err.insert(
+                ContextKind::InvalidArg,
+                ContextValue::String(arg.to_string()),
+            );
+        }
+        err.insert(
+            ContextKind::InvalidValue,
+            ContextValue::String(value.to_string()),
+        );
+        err.insert(
+            ContextKind::ValidValue,
+            ContextValue::Strings(FORMATS.iter().map(|f| f.to_string()).collect()),
+        );
+        if let Some(suggestion) = closest_format(&value) {
+            err.insert(
+                ContextKind::SuggestedValue,
+                ContextValue::String(suggestion.to_string()),
+            );
+        }
+        Err(err)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(FORMATS.iter().map(PossibleValue::new)))
+    }
+}
+
+/// Find the valid format closest to `input`, if it is within edit distance 2
+pub fn closest_format(input: &str) -> Option<&'static str> {
+    FORMATS
+        .iter()
+        .map(|format| (levenshtein(input, format), *format))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, format)| format)
+}
+
 #[derive(ValueEnum, Clone, Debug, Copy)]
+#[non_exhaustive]
 pub enum Shell {
     /// Bash shell completion
     Bash,
@@ -21,6 +112,15 @@ pub enum Shell {
     Nushell,
 }
 
+/// Command-line arguments.
+///
+/// Some defaults can be set from the environment; an explicit flag always wins:
+///
+/// - `HCL_FORMAT`: `--format`
+/// - `HCL_DEPTH`: `--depth`
+/// - `HCL_SKIP_MAN`: `--skip-man` (`1`/`true`/`yes`/`on` to enable)
+/// - `HCL_BASH_COMPAT`: `--bash-completion-compat` (same values as `HCL_SKIP_MAN`)
+/// - `HCL_TAB_WIDTH`: `--tab-width`
 #[derive(Parser, Debug)]
 #[command(
     version,
@@ -35,7 +135,7 @@ pub struct Cli {
         short = 'c',
         help = "Extract options from a command's help or man page",
         long_help = "Extract CLI options from the help texts or man pages associated with the command. Subcommand pages are also scanned automatically.",
-        conflicts_with_all = ["file", "subcommand", "loadjson"],
+        conflicts_with_all = ["file", "subcommand", "loadjson", "loadyaml"],
     )]
     pub command: Option<String>,
 
@@ -45,38 +145,108 @@ pub struct Cli {
         short = 'f',
         help = "Extract options from a help text file",
         long_help = "Extract CLI options from a text file containing help or manpage output.",
-        conflicts_with_all = ["command", "subcommand", "loadjson"],
+        conflicts_with_all = ["command", "subcommand", "loadjson", "loadyaml"],
     )]
     pub file: Option<String>,
 
-    /// Extract CLI options from a subcommand (format: command-subcommand, e.g., git-log)
+    /// Extract CLI options from a subcommand (format: command-subcommand or "command subcommand")
     #[arg(
         long,
         short = 's',
         help = "Extract options from a subcommand",
-        long_help = "Extract CLI options from a subcommand. The format is command-subcommand (for example: git-log).",
-        conflicts_with_all = ["command", "file", "loadjson"],
+        long_help = "Extract CLI options from a subcommand. The format is command-subcommand (for example: git-log) or the space-separated \"command subcommand\" (for example: \"git log\").",
+        conflicts_with_all = ["command", "file", "loadjson", "loadyaml"],
     )]
     pub subcommand: Option<String>,
 
+    /// Separator between command and subcommand in --subcommand
+    #[arg(
+        long,
+        help = "Separator used in --subcommand",
+        long_help = "Separator between the command and subcommand names in --subcommand, either '-' or ' '. By default a space is used if present, otherwise the first '-'.",
+        value_parser = clap::builder::PossibleValuesParser::new(["-", " "])
+            .map(|s| s.chars().next().unwrap_or('-')),
+        requires = "subcommand",
+    )]
+    pub subcommand_sep: Option<char>,
+
     /// Load JSON file in Command schema
     #[arg(
         long,
         short = 'l',
         help = "Load a Command JSON file",
         long_help = "Load a JSON file that uses d2o's Command schema and operate on that instead of parsing help text.",
-        conflicts_with_all = ["command", "file", "subcommand"],
+        conflicts_with_all = ["command", "file", "subcommand", "loadyaml"],
     )]
     pub loadjson: Option<String>,
 
-    /// Output format: bash, zsh, fish, json, native, elvish, nushell
+    /// Load YAML file in Command schema
+    #[arg(
+        long,
+        help = "Load a Command YAML file",
+        long_help = "Load a YAML file that uses d2o's Command schema (as produced by --format yaml) and operate on that instead of parsing help text.",
+        conflicts_with_all = ["command", "file", "subcommand", "loadjson"],
+    )]
+    pub loadyaml: Option<String>,
+
+    /// Compare against an older Command JSON and print what changed
+    #[arg(
+        long,
+        value_name = "OLD_JSON",
+        help = "Print changes relative to an older Command JSON",
+        long_help = "Load an older version of the command from a JSON file in d2o's Command schema and print the options and subcommands that were added, removed or changed instead of generating output.",
+        conflicts_with_all = ["write", "list_subcommands"],
+    )]
+    pub diff: Option<String>,
+
+    /// Write completion scripts for every shell into a directory
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Write completion scripts for all shells into DIR",
+        long_help = "Generate completion scripts for bash, zsh, fish, elvish, nushell and PowerShell in one go and write them to DIR as <cmd>.bash, <cmd>.zsh, <cmd>.fish, <cmd>.elv, <cmd>.nu and <cmd>.ps1 instead of printing to stdout.",
+        conflicts_with_all = ["write", "diff", "json", "ndjson", "list_subcommands"],
+    )]
+    pub multi_format: Option<String>,
+
+    /// Install the fish script into a completions directory
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write fish completions to PATH/<cmd>.fish",
+        long_help = "With --format fish, write the generated script to PATH/<cmd>.fish instead of stdout, e.g. /usr/share/fish/vendor_completions.d. The directory is created if needed.",
+        conflicts_with_all = ["write", "diff", "multi_format", "list_subcommands", "zsh_completion_dir"]
+    )]
+    pub fish_completion_dir: Option<String>,
+
+    /// Install the fish script into ~/.config/fish/completions
+    #[arg(
+        long,
+        help = "Same as --fish-completion-dir ~/.config/fish/completions",
+        long_help = "With --format fish, write the generated script to ~/.config/fish/completions/<cmd>.fish, where fish loads user completions from.",
+        conflicts_with_all = ["fish_completion_dir", "write", "diff", "multi_format", "list_subcommands", "zsh_completion_dir"]
+    )]
+    pub user_fish: bool,
+
+    /// Install the zsh script into a completions directory
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write zsh completions to PATH/_<cmd>",
+        long_help = "With --format zsh, write the generated script to PATH/_<cmd> instead of stdout. PATH should be a directory on $fpath. The directory is created if needed.",
+        conflicts_with_all = ["write", "diff", "multi_format", "list_subcommands"]
+    )]
+    pub zsh_completion_dir: Option<String>,
+
+    /// Output format: bash, zsh, fish, json, yaml, native, elvish, nushell, tcsh, xonsh, carapace, powershell, markdown-table
     #[arg(
         long,
         short = 'o',
         help = "Select output format",
-        long_help = "Select output format: bash, zsh, fish, json, native, elvish, or nushell.",
-        value_parser = ["bash", "zsh", "fish", "json", "native", "elvish", "nushell"],
+        long_help = "Select output format: bash, zsh, fish, json, yaml, native, elvish, nushell, tcsh, xonsh, carapace (a carapace-spec document), powershell, or markdown-table (just the options table, for pasting into existing docs).",
+        value_parser = FormatValueParser,
         default_value = "native",
+        env = "HCL_FORMAT",
     )]
     pub format: String,
 
@@ -89,22 +259,145 @@ pub struct Cli {
     )]
     pub json: bool,
 
+    /// Output newline-delimited JSON (one object per option)
+    #[arg(
+        long,
+        help = "Output newline-delimited JSON",
+        long_help = "Output newline-delimited JSON (JSON Lines): a line with the command metadata followed by one compact object per option. Useful for piping into tools like jq.",
+        conflicts_with = "json"
+    )]
+    pub ndjson: bool,
+
+    /// Wrap JSON output in a metadata envelope
+    #[arg(
+        long,
+        help = "Wrap JSON output with schema version, timestamp and source",
+        long_help = "Emit {\"schema_version\", \"generated_at\", \"source\", \"command\"} instead of the bare command, where generated_at is an RFC 3339 UTC timestamp and source is the input command or file.",
+        conflicts_with = "json_sort_keys"
+    )]
+    pub metadata: bool,
+
+    /// Sort JSON object keys and option names
+    #[arg(
+        long,
+        help = "Sort JSON keys and option names alphabetically",
+        long_help = "Emit JSON object keys and each option's names in alphabetical order, for reproducible, diff-friendly output."
+    )]
+    pub json_sort_keys: bool,
+
+    /// Print native output as `key: value` lines
+    #[arg(
+        long,
+        help = "Use a structured layout for native output",
+        long_help = "Print native output as line-oriented `key: value` fields (name, description, usage, options with names/argument/description) that are easy to parse mechanically."
+    )]
+    pub native_structured: bool,
+
+    /// Print the help command's stderr
+    #[arg(
+        long,
+        help = "Show stderr of the help command",
+        long_help = "Capture what the inspected command writes to stderr while printing its --help output and forward it to stderr. Useful for finding out why the parsed help text is empty.",
+        conflicts_with_all = ["file", "loadjson", "loadyaml"]
+    )]
+    pub capture_stderr: bool,
+
+    /// Keep options without descriptions
+    #[arg(
+        long,
+        help = "Keep options that have no description",
+        long_help = "Do not drop parsed options that lack a description (or a name). Useful for tools that document some flags without any text."
+    )]
+    pub no_filter: bool,
+
+    /// Keep duplicate options
+    #[arg(
+        long,
+        help = "Keep duplicate options",
+        long_help = "Do not merge options that share the same names and argument."
+    )]
+    pub no_dedup: bool,
+
+    /// Keep the command name as written
+    #[arg(
+        long,
+        help = "Do not normalize the command name",
+        long_help = "Keep the root command's name exactly as given instead of lowercasing it and replacing underscores and spaces with hyphens (e.g. 'My_Tool' becomes 'my-tool')."
+    )]
+    pub no_normalize_name: bool,
+
     /// Skip scanning manpage and focus on help text
     #[arg(
         long,
         short = 'm',
         help = "Skip scanning man pages",
-        long_help = "Skip scanning man pages and focus only on --help output. This does not apply if the input source is a file."
+        long_help = "Skip scanning man pages and focus only on --help output. This does not apply if the input source is a file.",
+        env = "HCL_SKIP_MAN",
+        value_parser = clap::builder::BoolishValueParser::new()
     )]
     pub skip_man: bool,
 
+    /// Column width used when rendering man pages
+    #[arg(
+        long,
+        help = "Column width used when rendering man pages",
+        long_help = "Set MANWIDTH for the man process. A wide page keeps option descriptions on one line, which helps the block splitter; man otherwise defaults to 80 columns when not attached to a terminal.",
+        default_value_t = DEFAULT_MAN_WIDTH,
+        value_parser = clap::value_parser!(u16).range(20..),
+        value_name = "COLUMNS",
+    )]
+    pub man_width: u16,
+
+    /// COLUMNS value used when running `<command> --help`
+    #[arg(
+        long,
+        help = "Terminal width reported to the help command",
+        long_help = "Set COLUMNS for the `<command> --help` process. Many tools (e.g. clap-based ones) wrap their help to COLUMNS, and unwrapped descriptions parse more reliably than continuation lines.",
+        default_value_t = DEFAULT_HELP_COLUMNS,
+        value_parser = clap::value_parser!(u16).range(20..),
+        value_name = "N",
+    )]
+    pub columns: u16,
+
+    /// Number of spaces each tab in the help text expands to
+    #[arg(
+        long,
+        help = "Spaces per tab in help text",
+        long_help = "Expand each tab in the help text or man page to this many spaces before parsing.",
+        default_value_t = DEFAULT_TAB_WIDTH,
+        value_parser = clap::value_parser!(u8).range(1..),
+        value_name = "N",
+        env = "HCL_TAB_WIDTH"
+    )]
+    pub tab_width: u8,
+
+    /// Number of attempts when running help or man commands
+    #[arg(
+        long,
+        help = "Number of attempts for running commands",
+        long_help = "Number of attempts when running the help or man command. Transient spawn failures are retried with exponential backoff; a non-zero exit status is never retried. The default of 1 disables retries.",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+        value_name = "ATTEMPTS",
+    )]
+    pub retry: u32,
+
+    /// Record the source command's version
+    #[arg(
+        long,
+        help = "Record the source command's --version output",
+        long_help = "Also run '<cmd> --version' and store its first non-empty line as the command version, even when options were read from the man page. The version is included in JSON, YAML and native output.",
+        conflicts_with_all = ["file", "loadjson", "loadyaml"]
+    )]
+    pub version_from_command: bool,
+
     /// List subcommands (debug)
     #[arg(
         long,
         short = 'L',
         help = "List discovered subcommands",
         long_help = "List subcommands discovered from the parsed help text instead of generating completions.",
-        conflicts_with = "loadjson"
+        conflicts_with_all = ["loadjson", "loadyaml"]
     )]
     pub list_subcommands: bool,
 
@@ -114,7 +407,7 @@ pub struct Cli {
         short = 'd',
         help = "Run preprocessing only",
         long_help = "Run only the preprocessing phase and print the parsed option/description pairs for debugging.",
-        conflicts_with = "loadjson"
+        conflicts_with_all = ["loadjson", "loadyaml"]
     )]
     pub debug: bool,
 
@@ -123,10 +416,10 @@ pub struct Cli {
         long,
         short = 'D',
         help = "Limit subcommand parsing depth",
-        long_help = "Set an upper bound on how deeply to scan for nested subcommands.",
-        default_value = "4"
+        long_help = "Set an upper bound on how deeply to scan for nested subcommands. Defaults to 4 when parsing help text; trees loaded with --loadjson/--loadyaml are only truncated when this is set.",
+        env = "HCL_DEPTH"
     )]
-    pub depth: usize,
+    pub depth: Option<usize>,
 
     /// Generate shell completions
     #[arg(
@@ -154,10 +447,36 @@ pub struct Cli {
         long,
         short = 'b',
         help = "Use bash-completion extended format",
-        long_help = "Use bash-completion's extended format for bash output. This encodes descriptions as name:Description and calls __ltrim_colon_completions if available."
+        long_help = "Use bash-completion's extended format for bash output. This encodes descriptions as name:Description and calls __ltrim_colon_completions if available.",
+        env = "HCL_BASH_COMPAT",
+        value_parser = clap::builder::BoolishValueParser::new()
     )]
     pub bash_completion_compat: bool,
 
+    /// Emit a comment describing each option in bash and zsh output
+    #[arg(
+        long,
+        help = "Emit option description comments",
+        long_help = "Emit a human-readable comment describing each option in bash and zsh output."
+    )]
+    pub comments: bool,
+
+    /// List zsh options under their help section headings
+    #[arg(
+        long,
+        help = "Group zsh options by help section",
+        long_help = "List options in zsh output under a `# Section: <name>` comment for each help section they appear in (e.g. `Output options:`)."
+    )]
+    pub grouped: bool,
+
+    /// Offer the top-level options in every subcommand's zsh completions
+    #[arg(
+        long,
+        help = "Complete top-level options in zsh subcommands",
+        long_help = "Treat the top-level options as global: zsh completions offer them after every subcommand that does not define an option of the same name. Fish output already offers top-level options everywhere."
+    )]
+    pub inherit_options: bool,
+
     /// Enable caching of parsed commands (default: enabled)
     #[arg(
         long,
@@ -195,27 +514,210 @@ pub struct Cli {
     )]
     pub cache_stats: bool,
 
+    /// Reject unknown fields in --loadjson input
+    #[arg(
+        long,
+        requires = "loadjson",
+        help = "Reject unknown fields in --loadjson input",
+        long_help = "Fail when the JSON passed to --loadjson contains fields that are not part of the command format (e.g. a misspelled \"optins\"), instead of silently ignoring them."
+    )]
+    pub strict: bool,
+
+    /// Enable trace-level logging
+    #[arg(
+        long,
+        help = "Enable trace-level logging",
+        long_help = "Log every traced step (input fetching, block parsing, postprocessing) at TRACE level to stderr, overriding -v/-q."
+    )]
+    pub trace: bool,
+
     /// Set the level of verbosity (-v, -vv, -q, etc.)
     #[command(flatten)]
     pub verbosity: Verbosity,
 }
 
 impl Cli {
-    /// Get the effective format, considering --json flag as legacy
+    /// Get the effective format, considering --json and --ndjson flags
     pub fn effective_format(&self) -> &str {
-        if self.json { "json" } else { &self.format }
+        if self.json {
+            "json"
+        } else if self.ndjson {
+            "ndjson"
+        } else {
+            &self.format
+        }
+    }
+
+    /// Subcommand depth for parsed help: `--depth`, or [`DEFAULT_DEPTH`]
+    pub fn effective_depth(&self) -> usize {
+        self.depth.unwrap_or(DEFAULT_DEPTH)
     }
 
-    /// Get the input file/command, prioritizing loadjson
+    /// Get the input file/command, prioritizing loadjson and loadyaml
     pub fn get_input(&self) -> Option<&str> {
         self.loadjson
             .as_deref()
+            .or(self.loadyaml.as_deref())
             .or(self.file.as_deref())
             .or(self.command.as_deref())
     }
 
+    /// Name to use for the parsed command: the command name, the `cmd-sub`
+    /// form of a subcommand, or the stem of an input file.
+    pub fn get_input_display_name(&self) -> String {
+        fn stem(path: &str) -> Option<String> {
+            Some(
+                std::path::Path::new(path)
+                    .file_stem()?
+                    .to_str()?
+                    .to_string(),
+            )
+        }
+
+        if let Some(command) = &self.command {
+            return command.clone();
+        }
+        if let Some(name) = self.file.as_deref().and_then(stem) {
+            return name;
+        }
+        if let Some(subcommand) = &self.subcommand {
+            return match self.subcommand_parts() {
+                Some((cmd, subcmd)) => format!("{}-{}", cmd, subcmd),
+                None => subcommand.clone(),
+            };
+        }
+        self.loadjson
+            .as_deref()
+            .or(self.loadyaml.as_deref())
+            .and_then(stem)
+            .unwrap_or_else(|| "command".to_string())
+    }
+
+    /// Directory to install the generated completion script into, from
+    /// `--fish-completion-dir`, `--user-fish` or `--zsh-completion-dir`.
+    pub fn completion_dir(&self) -> anyhow::Result<Option<std::path::PathBuf>> {
+        if self.user_fish {
+            let home = std::env::home_dir()
+                .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
+            return Ok(Some(home.join(".config/fish/completions")));
+        }
+
+        Ok(self
+            .fish_completion_dir
+            .as_deref()
+            .or(self.zsh_completion_dir.as_deref())
+            .map(std::path::PathBuf::from))
+    }
+
+    /// Split --subcommand into its command and subcommand parts
+    pub fn subcommand_parts(&self) -> Option<(&str, &str)> {
+        let subcommand = self.subcommand.as_deref()?.trim();
+        let sep = self
+            .subcommand_sep
+            .unwrap_or(if subcommand.contains(' ') { ' ' } else { '-' });
+        let (cmd, subcmd) = subcommand.split_once(sep)?;
+        let (cmd, subcmd) = (cmd.trim(), subcmd.trim());
+
+        if cmd.is_empty() || subcmd.is_empty() {
+            None
+        } else {
+            Some((cmd, subcmd))
+        }
+    }
+
     /// Check if preprocess only mode (renamed from debug for clarity)
     pub fn is_preprocess_only(&self) -> bool {
         self.debug
     }
+
+    /// Check if all diagnostic output should be suppressed (-q)
+    pub fn is_quiet(&self) -> bool {
+        self.verbosity.is_silent()
+    }
+
+    /// Check semantic flag combinations that clap's `conflicts_with` cannot express
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let format = self.effective_format();
+
+        if self.bash_completion_compat && format != "bash" {
+            anyhow::bail!(
+                "--bash-completion-compat requires --format bash (got '{}')",
+                format
+            );
+        }
+
+        if self.comments && !matches!(format, "bash" | "zsh") {
+            anyhow::bail!(
+                "--comments requires --format bash or zsh (got '{}')",
+                format
+            );
+        }
+
+        if self.grouped && format != "zsh" {
+            anyhow::bail!("--grouped requires --format zsh (got '{}')", format);
+        }
+
+        if self.grouped && self.comments {
+            anyhow::bail!("--grouped cannot be combined with --comments");
+        }
+
+        if self.inherit_options && format != "zsh" {
+            anyhow::bail!("--inherit-options requires --format zsh (got '{}')", format);
+        }
+
+        if self.inherit_options && (self.comments || self.grouped) {
+            anyhow::bail!("--inherit-options cannot be combined with --comments or --grouped");
+        }
+
+        if self.comments && self.bash_completion_compat {
+            anyhow::bail!("--comments cannot be combined with --bash-completion-compat");
+        }
+
+        if self.write
+            && matches!(
+                format,
+                "native" | "json" | "ndjson" | "yaml" | "markdown-table"
+            )
+        {
+            anyhow::bail!(
+                "--write requires a shell script format (bash, zsh, fish, elvish, nushell, tcsh, xonsh, powershell), got '{}'",
+                format
+            );
+        }
+
+        if self.json_sort_keys && format != "json" {
+            anyhow::bail!("--json-sort-keys requires --format json (got '{}')", format);
+        }
+
+        if self.metadata && format != "json" {
+            anyhow::bail!("--metadata requires --format json (got '{}')", format);
+        }
+
+        if self.native_structured && format != "native" {
+            anyhow::bail!(
+                "--native-structured requires --format native (got '{}')",
+                format
+            );
+        }
+
+        if (self.fish_completion_dir.is_some() || self.user_fish) && format != "fish" {
+            anyhow::bail!(
+                "--fish-completion-dir and --user-fish require --format fish (got '{}')",
+                format
+            );
+        }
+
+        if self.zsh_completion_dir.is_some() && format != "zsh" {
+            anyhow::bail!(
+                "--zsh-completion-dir requires --format zsh (got '{}')",
+                format
+            );
+        }
+
+        if self.debug && self.list_subcommands {
+            anyhow::bail!("--debug and --list-subcommands cannot be used together");
+        }
+
+        Ok(())
+    }
 }
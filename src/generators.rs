@@ -1,27 +1,103 @@
 use crate::types::{Command, Opt, OptName, OptNameType};
-use aho_corasick::AhoCorasick;
-use ecow::EcoString;
+use ecow::{EcoString, eco_format};
 use memchr::memchr;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::Write;
-use std::sync::LazyLock;
+use std::io;
 
-// Pre-compiled Aho-Corasick automaton for file/dir/path matching (SIMD-accelerated)
-static FILE_PATH_MATCHER: LazyLock<AhoCorasick> = LazyLock::new(|| {
-    AhoCorasick::builder()
-        .ascii_case_insensitive(true)
-        .build(["file", "dir", "path", "archive"])
-        .unwrap()
-});
+mod carapace;
+mod powershell;
+mod tcsh;
+mod xonsh;
+
+pub use carapace::CarapaceGenerator;
+pub use powershell::PowerShellGenerator;
+pub use tcsh::TcshGenerator;
+pub use xonsh::XonshGenerator;
+
+/// A completion script generator.
+///
+/// Implemented by every built-in shell generator, and by any
+/// `Fn(&Command) -> EcoString`, so callers can register their own shells
+/// alongside the built-in ones:
+///
+/// ```
+/// use d2o::{Command, EcoString, FishGenerator, Generator};
+///
+/// let generators: Vec<Box<dyn Generator + Send + Sync>> = vec![
+///     Box::new(FishGenerator),
+///     Box::new(|cmd: &Command| EcoString::from(format!("# {}\n", cmd.name))),
+/// ];
+/// let cmd = Command::new("mycmd".into());
+/// assert_eq!(generators[1].generate(&cmd), "# mycmd\n");
+/// ```
+pub trait Generator {
+    fn generate(&self, cmd: &Command) -> EcoString;
+
+    /// Write the generated script to `writer`.
+    fn generate_to_writer(&self, cmd: &Command, writer: &mut dyn io::Write) -> io::Result<()> {
+        writer.write_all(self.generate(cmd).as_bytes())
+    }
+}
+
+impl<F> Generator for F
+where
+    F: Fn(&Command) -> EcoString,
+{
+    fn generate(&self, cmd: &Command) -> EcoString {
+        self(cmd)
+    }
+}
+
+macro_rules! impl_generator {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Generator for $ty {
+                fn generate(&self, cmd: &Command) -> EcoString {
+                    <$ty>::generate(cmd)
+                }
+            }
+        )*
+    };
+}
+
+impl_generator!(
+    FishGenerator,
+    ZshGenerator,
+    BashGenerator,
+    ElvishGenerator,
+    NushellGenerator,
+    TcshGenerator,
+    XonshGenerator,
+    CarapaceGenerator,
+    PowerShellGenerator,
+);
 
 pub struct FishGenerator;
 
 impl FishGenerator {
     pub fn generate(cmd: &Command) -> EcoString {
+        Self::generate_inner(cmd, &[], false)
+    }
+
+    /// Generate completions where each `(a, b)` pair in `exclusions` marks
+    /// two mutually exclusive options: once either is on the command line,
+    /// the other is no longer offered.
+    pub fn generate_with_exclusions(cmd: &Command, exclusions: &[(&str, &str)]) -> EcoString {
+        Self::generate_inner(cmd, exclusions, false)
+    }
+
+    /// Like [`Self::generate`], but suppresses members of each command's
+    /// [`Command::exclusive_groups`] once another member has been typed.
+    pub fn generate_exclusive(cmd: &Command) -> EcoString {
+        Self::generate_inner(cmd, &[], true)
+    }
+
+    fn generate_inner(cmd: &Command, exclusions: &[(&str, &str)], with_groups: bool) -> EcoString {
         // Pre-calculate capacity based on options count
         let estimated_size = 64 + cmd.options.len() * 80;
         let mut buf = String::with_capacity(estimated_size);
-        Self::generate_rec(&mut buf, &[], cmd);
+        Self::generate_rec(&mut buf, &cmd.name, &[], cmd, exclusions, with_groups);
         // Remove trailing newline if present
         if buf.ends_with('\n') {
             buf.pop();
@@ -29,21 +105,156 @@ impl FishGenerator {
         EcoString::from(buf)
     }
 
-    fn generate_rec(buf: &mut String, path: &[&str], cmd: &Command) {
-        let mut current_path = path.to_vec();
-        current_path.push(&cmd.name);
-        let path_str = current_path.join("_");
+    /// Write completions for `cmd`, reached from `root` through the subcommands
+    /// in `path`. Everything is registered on `root`; a subcommand's options are
+    /// guarded by `__fish_seen_subcommand_from` for each step of its path, and
+    /// its own subcommands are offered until one of them has been typed.
+    fn generate_rec(
+        buf: &mut String,
+        root: &str,
+        path: &[&str],
+        cmd: &Command,
+        exclusions: &[(&str, &str)],
+        with_groups: bool,
+    ) {
+        let seen = path
+            .iter()
+            .map(|name| format!("__fish_seen_subcommand_from {}", name))
+            .collect::<Vec<_>>()
+            .join("; and ");
 
-        for opt in cmd.options.iter() {
+        // Coalesce options sharing a raw name: fish keeps only the last `complete`
+        // definition, so combine their descriptions into a single line instead
+        let mut entries: Vec<(&OptName, &Opt, String)> = Vec::with_capacity(cmd.options.len());
+        let mut index: HashMap<&str, usize, foldhash::fast::RandomState> = HashMap::default();
+        for opt in cmd.completion_options().iter() {
+            let desc = Self::truncate_after_period(&opt.description);
             for name in opt.names.iter() {
-                if !Self::should_skip_option(name) {
-                    Self::write_option_line(buf, &path_str, name, opt);
+                if Self::should_skip_option(name) {
+                    continue;
+                }
+                match index.get(name.raw.as_str()) {
+                    Some(&i) => {
+                        let combined = &mut entries[i].2;
+                        if !desc.is_empty() && !combined.split(" / ").any(|d| d == desc) {
+                            if !combined.is_empty() {
+                                combined.push_str(" / ");
+                            }
+                            combined.push_str(desc);
+                        }
+                    }
+                    None => {
+                        index.insert(name.raw.as_str(), entries.len());
+                        entries.push((name, opt, desc.to_string()));
+                    }
                 }
             }
         }
 
+        let mut pairs = exclusions.to_vec();
+        if with_groups {
+            for group in cmd.exclusive_groups.iter() {
+                for (i, a) in group.iter().enumerate() {
+                    for b in group.iter().skip(i + 1) {
+                        pairs.push((a.as_str(), b.as_str()));
+                    }
+                }
+            }
+        }
+
+        if !cmd.subcommands.is_empty() {
+            let condition = if path.is_empty() {
+                "__fish_use_subcommand".to_string()
+            } else {
+                let children: Vec<&str> = cmd.subcommands.iter().map(|s| s.name.as_str()).collect();
+                format!(
+                    "{}; and not __fish_seen_subcommand_from {}",
+                    seen,
+                    children.join(" ")
+                )
+            };
+            for subcmd in cmd.subcommands.iter() {
+                let desc = Self::truncate_after_period(&subcmd.description);
+                let _ = writeln!(
+                    buf,
+                    "complete -c {} -f -n '{}' -a '{}' -d '{}'",
+                    root,
+                    condition,
+                    subcmd.name.replace("'", "\\'"),
+                    desc.replace('\'', "\\'")
+                );
+            }
+        }
+
+        for (name, opt, desc) in entries.iter() {
+            let exclusion = Self::exclusion_condition(opt, cmd.completion_options(), &pairs);
+            let condition = match (seen.is_empty(), exclusion.is_empty()) {
+                (true, _) => exclusion,
+                (false, true) => seen.clone(),
+                (false, false) => format!("{}; and {}", seen, exclusion),
+            };
+            Self::write_option_line(buf, root, name, opt, desc, &condition);
+        }
+
         for subcmd in cmd.subcommands.iter() {
-            Self::generate_rec(buf, &current_path, subcmd);
+            let mut sub_path = path.to_vec();
+            sub_path.push(&subcmd.name);
+            Self::generate_rec(buf, root, &sub_path, subcmd, exclusions, with_groups);
+        }
+    }
+
+    /// `not __fish_contains_opt ...` over every option excluded by one of
+    /// `opt`'s names, including the aliases of those options, or an empty
+    /// string when nothing excludes it.
+    fn exclusion_condition<'a>(
+        opt: &Opt,
+        options: &'a [Opt],
+        pairs: &[(&'a str, &'a str)],
+    ) -> String {
+        let has_name = |raw: &str| opt.names.iter().any(|n| n.raw == raw);
+        let mut others: Vec<&str> = Vec::new();
+        for &(a, b) in pairs {
+            for (own, other) in [(a, b), (b, a)] {
+                if !has_name(own) {
+                    continue;
+                }
+                let aliases: Vec<&str> = match options
+                    .iter()
+                    .find(|o| o.names.iter().any(|n| n.raw == other))
+                {
+                    Some(excluded) => excluded.names.iter().map(|n| n.raw.as_str()).collect(),
+                    None => vec![other],
+                };
+                for raw in aliases {
+                    if !has_name(raw) && !others.contains(&raw) {
+                        others.push(raw);
+                    }
+                }
+            }
+        }
+
+        let mut condition = String::new();
+        for other in others {
+            let Some(name) = OptName::from_text(other) else {
+                continue;
+            };
+            let dashless = name.raw.trim_start_matches('-');
+            match name.opt_type {
+                OptNameType::LongType => {
+                    let _ = write!(condition, " {}", dashless);
+                }
+                OptNameType::ShortType => {
+                    let _ = write!(condition, " -s {}", dashless);
+                }
+                // `__fish_contains_opt` only understands short and long options
+                _ => {}
+            }
+        }
+
+        if condition.is_empty() {
+            condition
+        } else {
+            format!("not __fish_contains_opt{}", condition)
         }
     }
 
@@ -55,12 +266,32 @@ impl FishGenerator {
         )
     }
 
-    fn write_option_line(buf: &mut String, path_str: &str, name: &OptName, opt: &Opt) {
+    fn write_option_line(
+        buf: &mut String,
+        path_str: &str,
+        name: &OptName,
+        opt: &Opt,
+        desc: &str,
+        condition: &str,
+    ) {
         let dashless = name.raw.trim_start_matches('-');
         let flag = Self::opt_type_to_flag(name.opt_type);
-        let arg_flag = Self::opt_arg_to_flag(opt);
-        let desc = Self::truncate_after_period(&opt.description);
-
+        let mut arg_flag = match Self::opt_arg_file_flag(opt) {
+            "" => Self::opt_arg_requires_flag(opt).to_string(),
+            file_flag => format!("{} {}", Self::opt_arg_requires_flag(opt), file_flag),
+        };
+        if !opt.possible_values.is_empty() {
+            if !arg_flag.is_empty() {
+                arg_flag.push(' ');
+            }
+            let _ = write!(arg_flag, "-a '{}'", opt.possible_values.join(" "));
+        }
+        if !condition.is_empty() {
+            if !arg_flag.is_empty() {
+                arg_flag.push(' ');
+            }
+            let _ = write!(arg_flag, "-n '{}'", condition);
+        }
         let _ = writeln!(
             buf,
             "complete -c {} {} '{}' {} -d '{}'",
@@ -82,23 +313,23 @@ impl FishGenerator {
         }
     }
 
-    /// Use Aho-Corasick automaton for SIMD-accelerated multi-pattern matching
+    /// `-r` (requires an argument) for file arguments, `-x` (requires an argument,
+    /// no file completion) for other arguments.
     #[inline]
-    fn opt_arg_to_flag(opt: &Opt) -> &'static str {
+    fn opt_arg_requires_flag(opt: &Opt) -> &'static str {
         if opt.argument.is_empty() {
-            return "";
-        }
-
-        // Use pre-compiled Aho-Corasick for SIMD multi-pattern search
-        if FILE_PATH_MATCHER.is_match(opt.argument.as_str()) {
-            return "-r";
-        }
-
-        if FILE_PATH_MATCHER.is_match(opt.description.as_str()) {
-            return "-r";
+            ""
+        } else if opt.is_file_arg() {
+            "-r"
+        } else {
+            "-x"
         }
+    }
 
-        "-x"
+    /// `-F` (force file completion) for file arguments.
+    #[inline]
+    fn opt_arg_file_flag(opt: &Opt) -> &'static str {
+        if opt.is_file_arg() { "-F" } else { "" }
     }
 
     /// Truncate string after first period using SIMD-accelerated memchr
@@ -116,28 +347,271 @@ pub struct ZshGenerator;
 
 impl ZshGenerator {
     pub fn generate(cmd: &Command) -> EcoString {
+        Self::generate_with_comments(cmd, false)
+    }
+
+    /// Like [`Self::generate_exclusive`], with global options inherited from
+    /// `root` added to `cmd` and each of its nested subcommands.
+    pub fn generate_with_root(cmd: &Command, root: Option<&Command>) -> EcoString {
+        Self::generate_exclusive(&cmd.with_inherited_options(root))
+    }
+
+    /// Generate the completion function, optionally emitting a `# <description>`
+    /// comment above each option's spec lines.
+    pub fn generate_with_comments(cmd: &Command, with_comments: bool) -> EcoString {
         let estimated_size = 256 + cmd.options.len() * 64;
         let mut buf = String::with_capacity(estimated_size);
 
         let _ = writeln!(buf, "#compdef {}", cmd.name);
         let _ = writeln!(buf);
-        let _ = writeln!(buf, "_{}() {{", cmd.name);
+
+        let fn_name = format!("_{}", Self::sanitize_name(&cmd.name));
+        Self::write_function(&mut buf, &fn_name, cmd, with_comments, false, false);
+
+        let _ = write!(buf, "{} \"$@\"", fn_name);
+
+        EcoString::from(buf)
+    }
+
+    /// Generate the completion function, prefixing the specs of options in one of
+    /// `cmd.exclusive_groups` with an exclusion list such as `(--json --yaml)`, so
+    /// zsh stops offering the others once one of them is on the command line.
+    pub fn generate_exclusive(cmd: &Command) -> EcoString {
+        let estimated_size = 256 + cmd.options.len() * 80;
+        let mut buf = String::with_capacity(estimated_size);
+
+        let _ = writeln!(buf, "#compdef {}", cmd.name);
+        let _ = writeln!(buf);
+
+        let fn_name = format!("_{}", Self::sanitize_name(&cmd.name));
+        Self::write_function(&mut buf, &fn_name, cmd, false, true, false);
+
+        let _ = write!(buf, "{} \"$@\"", fn_name);
+
+        EcoString::from(buf)
+    }
+
+    /// Generate the completion function with each of `cmd.option_groups` under a
+    /// `# Section: <name>` comment. Options outside any group come first, without
+    /// a header; commands without groups get the flat [`Self::generate`] layout.
+    pub fn generate_grouped(cmd: &Command) -> EcoString {
+        let estimated_size = 256 + cmd.options.len() * 64;
+        let mut buf = String::with_capacity(estimated_size);
+
+        let _ = writeln!(buf, "#compdef {}", cmd.name);
+        let _ = writeln!(buf);
+
+        let fn_name = format!("_{}", Self::sanitize_name(&cmd.name));
+        Self::write_function(&mut buf, &fn_name, cmd, false, false, true);
+
+        let _ = write!(buf, "{} \"$@\"", fn_name);
+
+        EcoString::from(buf)
+    }
+
+    /// Make `name` usable inside a function name (`python3.12` -> `python3_12`).
+    /// The `#compdef` line keeps the original name so registration still matches.
+    pub fn sanitize_name(name: &str) -> String {
+        BashGenerator::sanitize_function_name(name)
+    }
+
+    /// Write the completion function for `cmd`. Commands with subcommands use
+    /// `_arguments -C` and dispatch to a `<fn_name>_<sub>` helper per subcommand,
+    /// emitted ahead of the function that calls it.
+    fn write_function(
+        buf: &mut String,
+        fn_name: &str,
+        cmd: &Command,
+        with_comments: bool,
+        exclusive: bool,
+        grouped: bool,
+    ) {
+        for sub in cmd.subcommands.iter() {
+            let sub_fn = format!("{}_{}", fn_name, Self::sanitize_name(&sub.name));
+            Self::write_function(buf, &sub_fn, sub, with_comments, exclusive, grouped);
+        }
+
+        let has_value_states = cmd
+            .completion_options()
+            .iter()
+            .any(|opt| !opt.possible_values.is_empty());
+
+        let _ = writeln!(buf, "{}() {{", fn_name);
+        if !cmd.subcommands.is_empty() || has_value_states {
+            let _ = writeln!(buf, "  local context state state_descr line");
+            let _ = writeln!(buf, "  typeset -A opt_args");
+        }
         let _ = writeln!(buf, "  local -a options");
         let _ = writeln!(buf);
 
-        for opt in cmd.options.iter() {
-            Self::write_opt(&mut buf, opt);
+        let write_entry = |buf: &mut String, opt: &Opt| {
+            if with_comments {
+                Self::write_comment(buf, opt);
+            }
+            let exclusion = if exclusive {
+                Self::exclusion_prefix(cmd, opt)
+            } else {
+                String::new()
+            };
+            Self::write_opt(buf, opt, &exclusion);
+        };
+
+        if grouped && cmd.accepts_options && !cmd.option_groups.is_empty() {
+            let in_group = |opt: &Opt| {
+                cmd.option_groups
+                    .iter()
+                    .any(|group| group.options.iter().any(|o| o.names == opt.names))
+            };
+            for opt in cmd.completion_options().iter().filter(|opt| !in_group(opt)) {
+                write_entry(buf, opt);
+            }
+            for group in cmd.option_groups.iter() {
+                let _ = writeln!(buf, "  # Section: {}", group.name);
+                for opt in group.options.iter() {
+                    write_entry(buf, opt);
+                }
+            }
+        } else {
+            for opt in cmd.completion_options().iter() {
+                write_entry(buf, opt);
+            }
+        }
+
+        if cmd.subcommands.is_empty() && has_value_states {
+            let _ = writeln!(buf, "  _arguments -s -S $options && return");
+            let _ = writeln!(buf);
+            let _ = writeln!(buf, "  case $state in");
+            Self::write_value_states(buf, cmd);
+            let _ = writeln!(buf, "  esac");
+        } else if cmd.subcommands.is_empty() {
+            let _ = writeln!(buf, "  _arguments -s -S $options");
+        } else {
+            Self::write_subcommand_dispatch(buf, fn_name, cmd);
         }
 
-        let _ = writeln!(buf, "  _arguments -s -S $options");
         let _ = writeln!(buf, "}}");
         let _ = writeln!(buf);
-        let _ = write!(buf, "_{} \"$@\"", cmd.name);
+    }
 
-        EcoString::from(buf)
+    fn write_subcommand_dispatch(buf: &mut String, fn_name: &str, cmd: &Command) {
+        let _ = writeln!(buf, "  _arguments -C -s -S $options \\");
+        let _ = writeln!(buf, "    '1:command:->cmds' \\");
+        let _ = writeln!(buf, "    '*:: :->args'");
+        let _ = writeln!(buf);
+        let _ = writeln!(buf, "  case $state in");
+        let _ = writeln!(buf, "    cmds)");
+        let _ = writeln!(buf, "      local -a subcommands");
+        let _ = writeln!(buf, "      subcommands=(");
+        for sub in cmd.subcommands.iter() {
+            let desc = FishGenerator::truncate_after_period(&sub.description);
+            let _ = writeln!(
+                buf,
+                "        '{}:{}'",
+                sub.name.replace(":", "\\:"),
+                desc.replace('\'', "'\\''")
+            );
+        }
+        let _ = writeln!(buf, "      )");
+        let _ = writeln!(
+            buf,
+            "      _describe -t commands '{} subcommand' subcommands",
+            cmd.name
+        );
+        let _ = writeln!(buf, "      ;;");
+        let _ = writeln!(buf, "    args)");
+        let _ = writeln!(buf, "      case $line[1] in");
+        for sub in cmd.subcommands.iter() {
+            let _ = writeln!(buf, "        {})", sub.name);
+            let _ = writeln!(
+                buf,
+                "          {}_{}",
+                fn_name,
+                Self::sanitize_name(&sub.name)
+            );
+            let _ = writeln!(buf, "          ;;");
+        }
+        let _ = writeln!(buf, "      esac");
+        let _ = writeln!(buf, "      ;;");
+        Self::write_value_states(buf, cmd);
+        let _ = writeln!(buf, "  esac");
+    }
+
+    /// `->values-<name>` state completing one of `opt.possible_values`, if it has any.
+    fn value_state(opt: &Opt) -> Option<String> {
+        if opt.possible_values.is_empty() {
+            return None;
+        }
+        let name = opt.names.first()?.raw.trim_start_matches('-');
+        let name: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        Some(format!("values-{}", name))
     }
 
-    fn write_opt(buf: &mut String, opt: &Opt) {
+    /// `case $state` arms running `_values` for each option with possible values.
+    fn write_value_states(buf: &mut String, cmd: &Command) {
+        let mut written: Vec<String> = Vec::new();
+        for opt in cmd.completion_options().iter() {
+            let Some(state) = Self::value_state(opt) else {
+                continue;
+            };
+            if written.contains(&state) {
+                continue;
+            }
+
+            let message = if opt.argument.is_empty() {
+                "value"
+            } else {
+                &opt.argument
+            };
+            let _ = write!(buf, "    {})\n      _values '{}'", state, message);
+            for value in opt.possible_values.iter() {
+                let _ = write!(buf, " '{}'", value.replace("'", "'\\''"));
+            }
+            let _ = writeln!(buf);
+            let _ = writeln!(buf, "      ;;");
+            written.push(state);
+        }
+    }
+
+    fn write_comment(buf: &mut String, opt: &Opt) {
+        const MAX_COMMENT_LEN: usize = 72;
+
+        let desc = FishGenerator::truncate_after_period(&opt.description).trim();
+        if desc.is_empty() {
+            return;
+        }
+
+        let end = desc
+            .char_indices()
+            .nth(MAX_COMMENT_LEN)
+            .map_or(desc.len(), |(idx, _)| idx);
+        let _ = writeln!(buf, "  # {}", desc[..end].trim_end());
+    }
+
+    /// `(a b c)` listing every member of the exclusive groups `opt` belongs to.
+    fn exclusion_prefix(cmd: &Command, opt: &Opt) -> String {
+        let mut excluded: Vec<&str> = Vec::new();
+        for group in cmd.exclusive_groups.iter() {
+            if !opt.names.iter().any(|name| group.contains(&name.raw)) {
+                continue;
+            }
+            for name in group.iter() {
+                if !excluded.contains(&name.as_str()) {
+                    excluded.push(name);
+                }
+            }
+        }
+
+        if excluded.is_empty() {
+            String::new()
+        } else {
+            format!("({})", excluded.join(" "))
+        }
+    }
+
+    fn write_opt(buf: &mut String, opt: &Opt, exclusion: &str) {
         let desc = FishGenerator::truncate_after_period(&opt.description);
 
         for name in opt.names.iter() {
@@ -148,13 +622,50 @@ impl ZshGenerator {
                 continue;
             }
 
-            if opt.argument.is_empty() {
-                let _ = writeln!(buf, "  options+=('{}[{}]')", name.raw, desc);
+            let value_state = Self::value_state(opt);
+
+            if opt.argument.is_empty() && value_state.is_none() {
+                let _ = writeln!(buf, "  options+=('{}{}[{}]')", exclusion, name.raw, desc);
+            } else if let Some(state) = value_state.filter(|_| !opt.argument_optional) {
+                let message = if opt.argument.is_empty() {
+                    "value"
+                } else {
+                    &opt.argument
+                };
+                let _ = writeln!(
+                    buf,
+                    "  options+=('{}{}[{}]:{}:->{}')",
+                    exclusion, name.raw, desc, message, state
+                );
+            } else if opt.argument_optional {
+                // `--name=-` only accepts the value in the same word (`--count=3`),
+                // and the `::` spec marks the value itself as optional
+                let same_word = if name.opt_type == OptNameType::LongType {
+                    "=-"
+                } else {
+                    ""
+                };
+                let action = match Self::value_state(opt) {
+                    Some(state) => format!("->{}", state),
+                    None if opt.is_file_arg() => "_files".to_string(),
+                    None => String::new(),
+                };
+                let _ = writeln!(
+                    buf,
+                    "  options+=('{}{}{}[{}]::{}:{}')",
+                    exclusion, name.raw, same_word, desc, opt.argument, action
+                );
+            } else if opt.is_file_arg() {
+                let _ = writeln!(
+                    buf,
+                    "  options+=('{}{}[{} {}]:{}:_files')",
+                    exclusion, name.raw, opt.argument, desc, opt.argument
+                );
             } else {
                 let _ = writeln!(
                     buf,
-                    "  options+=('{}[{} {}]')",
-                    name.raw, opt.argument, desc
+                    "  options+=('{}{}[{} {}]')",
+                    exclusion, name.raw, opt.argument, desc
                 );
             }
         }
@@ -169,10 +680,25 @@ impl BashGenerator {
     }
 
     pub fn generate_with_compat(cmd: &Command, bash_completion_compat: bool) -> EcoString {
+        Self::generate_impl(cmd, bash_completion_compat, false)
+    }
+
+    /// Generate the completion function with a `# <names>: <description>` comment
+    /// block describing every option above the `opts` definition.
+    pub fn generate_with_comments(cmd: &Command) -> EcoString {
+        Self::generate_impl(cmd, false, true)
+    }
+
+    fn generate_impl(
+        cmd: &Command,
+        bash_completion_compat: bool,
+        with_comments: bool,
+    ) -> EcoString {
         let estimated_size = 512 + cmd.options.len() * 32;
         let mut buf = String::with_capacity(estimated_size);
+        let fn_name = Self::sanitize_function_name(&cmd.name);
 
-        let _ = writeln!(buf, "_{}()", cmd.name);
+        let _ = writeln!(buf, "_{}()", fn_name);
         let _ = writeln!(buf, "{{");
         let _ = writeln!(buf, "  local cur prev opts");
         let _ = writeln!(buf, "  COMPREPLY=()");
@@ -180,64 +706,35 @@ impl BashGenerator {
         let _ = writeln!(buf, "  prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"");
         let _ = writeln!(buf);
 
-        // Collect all option strings into a BTreeSet for deduplication and sorting
-        let all_opts: BTreeSet<String> = if bash_completion_compat {
-            cmd.options
-                .iter()
-                .flat_map(|opt| {
-                    let base_desc = FishGenerator::truncate_after_period(&opt.description);
-                    let desc: String = base_desc
-                        .split_whitespace()
-                        .collect::<Vec<_>>()
-                        .join("_")
-                        .replace(':', "_");
-
-                    opt.names
-                        .iter()
-                        .filter_map(|name| {
-                            if matches!(
-                                name.opt_type,
-                                OptNameType::SingleDashAlone | OptNameType::DoubleDashAlone
-                            ) {
-                                None
-                            } else if desc.is_empty() {
-                                Some(name.raw.to_string())
-                            } else {
-                                let mut s = String::with_capacity(name.raw.len() + desc.len() + 1);
-                                s.push_str(&name.raw);
-                                s.push(':');
-                                s.push_str(&desc);
-                                Some(s)
-                            }
-                        })
-                        .collect::<Vec<_>>()
-                })
-                .collect()
-        } else {
-            cmd.options
-                .iter()
-                .flat_map(|opt| {
-                    opt.names
-                        .iter()
-                        .filter_map(|name| {
-                            if matches!(
-                                name.opt_type,
-                                OptNameType::SingleDashAlone | OptNameType::DoubleDashAlone
-                            ) {
-                                None
-                            } else {
-                                Some(name.raw.to_string())
-                            }
-                        })
-                        .collect::<Vec<_>>()
-                })
-                .collect()
-        };
+        if with_comments {
+            for opt in cmd.completion_options().iter() {
+                Self::write_comment(&mut buf, opt);
+            }
+        }
 
-        // Build opts string efficiently
-        let opts_joined = all_opts.into_iter().collect::<Vec<_>>().join(" ");
-        let _ = writeln!(buf, "  opts=\"{}\"", opts_joined);
+        if cmd.subcommands.is_empty() {
+            let opts = Self::option_words(cmd.completion_options(), bash_completion_compat);
+            let _ = writeln!(buf, "  opts=\"{}\"", opts.join(" "));
+        } else {
+            // Options of the subcommand in the first position, or the top-level
+            // options and subcommand names when there is none yet
+            let _ = writeln!(buf, "  case \"${{COMP_WORDS[1]}}\" in");
+            for sub in cmd.subcommands.iter() {
+                let mut opts = Self::option_words(sub.completion_options(), bash_completion_compat);
+                opts.extend(sub.subcommands.iter().map(|s| s.name.to_string()));
+                let _ = writeln!(buf, "    {})", sub.name);
+                let _ = writeln!(buf, "      opts=\"{}\"", opts.join(" "));
+                let _ = writeln!(buf, "      ;;");
+            }
+            let mut opts = Self::option_words(cmd.completion_options(), bash_completion_compat);
+            opts.extend(cmd.subcommands.iter().map(|s| s.name.to_string()));
+            let _ = writeln!(buf, "    *)");
+            let _ = writeln!(buf, "      opts=\"{}\"", opts.join(" "));
+            let _ = writeln!(buf, "      ;;");
+            let _ = writeln!(buf, "  esac");
+        }
         let _ = writeln!(buf);
+        Self::write_path_cases(&mut buf, cmd);
         let _ = writeln!(buf, "  COMPREPLY=($(compgen -W \"${{opts}}\" -- ${{cur}}))");
 
         if bash_completion_compat {
@@ -251,11 +748,178 @@ impl BashGenerator {
         let _ = write!(
             buf,
             "complete -o bashdefault -o default -o nospace -F _{} {}",
-            cmd.name, cmd.name
+            fn_name, cmd.name
         );
 
         EcoString::from(buf)
     }
+
+    /// Sorted, deduplicated option names for an `opts` word list. With
+    /// `bash_completion_compat`, each name carries its description as
+    /// `name:description`.
+    fn option_words(options: &[Opt], bash_completion_compat: bool) -> Vec<String> {
+        // Collect into a Vec, then sort and deduplicate in place; a contiguous
+        // Vec has better cache locality than a BTreeSet traversal
+        let mut words: Vec<String> = options
+            .iter()
+            .flat_map(|opt| {
+                let desc = if bash_completion_compat {
+                    Self::sanitize_bash_description(&opt.description)
+                } else {
+                    String::new()
+                };
+
+                opt.names
+                    .iter()
+                    .filter_map(|name| {
+                        if matches!(
+                            name.opt_type,
+                            OptNameType::SingleDashAlone | OptNameType::DoubleDashAlone
+                        ) {
+                            None
+                        } else if desc.is_empty() {
+                            Some(name.raw.to_string())
+                        } else {
+                            let mut s = String::with_capacity(name.raw.len() + desc.len() + 1);
+                            s.push_str(&name.raw);
+                            s.push(':');
+                            s.push_str(&desc);
+                            Some(s)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        words.sort_unstable();
+        words.dedup();
+        words
+    }
+
+    /// Replace every character that cannot appear in a portable shell
+    /// identifier (`my-tool` -> `my_tool`).
+    pub fn sanitize_function_name(name: &str) -> String {
+        name.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+
+    /// Emit a `case $prev in` block completing files (or directories, when the
+    /// argument mentions a directory) after options that take a path.
+    fn write_path_cases(buf: &mut String, cmd: &Command) {
+        let mut path_opts = cmd
+            .completion_options()
+            .iter()
+            .filter(|opt| opt.is_file_arg())
+            .peekable();
+        if path_opts.peek().is_none() {
+            return;
+        }
+
+        let _ = writeln!(buf, "  case \"${{prev}}\" in");
+        for opt in path_opts {
+            let patterns = opt
+                .names
+                .iter()
+                .filter(|name| {
+                    !matches!(
+                        name.opt_type,
+                        OptNameType::SingleDashAlone | OptNameType::DoubleDashAlone
+                    )
+                })
+                .map(|name| name.raw.as_str())
+                .collect::<Vec<_>>();
+            if patterns.is_empty() {
+                continue;
+            }
+
+            let compgen_flag = if opt.argument.to_ascii_lowercase().contains("dir") {
+                "-d"
+            } else {
+                "-f"
+            };
+            let _ = writeln!(buf, "    {})", patterns.join("|"));
+            let _ = writeln!(
+                buf,
+                "      COMPREPLY=($(compgen {} -- \"${{cur}}\"))",
+                compgen_flag
+            );
+            let _ = writeln!(buf, "      return 0");
+            let _ = writeln!(buf, "      ;;");
+        }
+        let _ = writeln!(buf, "  esac");
+        let _ = writeln!(buf);
+    }
+
+    fn write_comment(buf: &mut String, opt: &Opt) {
+        const MAX_LINE_LEN: usize = 72;
+
+        let names = opt
+            .names
+            .iter()
+            .filter(|name| {
+                !matches!(
+                    name.opt_type,
+                    OptNameType::SingleDashAlone | OptNameType::DoubleDashAlone
+                )
+            })
+            .map(|name| name.raw.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if names.is_empty() {
+            return;
+        }
+
+        let mut line = String::with_capacity(MAX_LINE_LEN);
+        line.push_str("  # ");
+        line.push_str(&names);
+        if !opt.description.trim().is_empty() {
+            line.push(':');
+        }
+
+        // Greedy word wrap, continuation lines keep the comment prefix
+        for word in opt.description.split_whitespace() {
+            if line.len() + 1 + word.len() > MAX_LINE_LEN && line.len() > "  # ".len() {
+                let _ = writeln!(buf, "{}", line);
+                line.clear();
+                line.push_str("  #");
+            }
+            line.push(' ');
+            line.push_str(word);
+        }
+        let _ = writeln!(buf, "{}", line);
+    }
+
+    /// Encode a description as a compgen-safe word for bash-completion's extended format.
+    ///
+    /// Keeps the first sentence, replaces anything but ASCII alphanumerics with `_`,
+    /// collapses repeated underscores and limits the result to 40 characters.
+    pub fn sanitize_bash_description(desc: &str) -> String {
+        const MAX_LEN: usize = 40;
+
+        let base_desc = FishGenerator::truncate_after_period(desc);
+        let mut result = String::with_capacity(base_desc.len().min(MAX_LEN));
+
+        for c in base_desc.chars() {
+            let c = if c.is_ascii_alphanumeric() { c } else { '_' };
+            if c == '_' && (result.is_empty() || result.ends_with('_')) {
+                continue;
+            }
+            if result.len() == MAX_LEN {
+                break;
+            }
+            result.push(c);
+        }
+
+        result.truncate(result.trim_end_matches('_').len());
+        result
+    }
 }
 
 pub struct ElvishGenerator;
@@ -282,17 +946,44 @@ impl ElvishGenerator {
             "        edit:complex-candidate $text &display=$text' '(spaces (- 14 (wcswidth $text)))$desc"
         );
         let _ = writeln!(buf, "    }}");
+        let _ = writeln!(buf, "    var completions = [");
+        Self::write_entries(&mut buf, &[], cmd);
+        let _ = writeln!(buf, "    ]");
+        // Only words naming a known subcommand extend the key, so positional
+        // arguments never route to a missing entry
         let _ = writeln!(buf, "    var command = '{}'", cmd.name);
         let _ = writeln!(buf, "    for word $words[1..-1] {{");
         let _ = writeln!(buf, "        if (str:has-prefix $word '-') {{");
         let _ = writeln!(buf, "            break");
         let _ = writeln!(buf, "        }}");
-        let _ = writeln!(buf, "        set command = $command';'$word");
+        let _ = writeln!(buf, "        if (has-key $completions $command';'$word) {{");
+        let _ = writeln!(buf, "            set command = $command';'$word");
+        let _ = writeln!(buf, "        }}");
         let _ = writeln!(buf, "    }}");
-        let _ = writeln!(buf, "    var completions = [");
-        let _ = writeln!(buf, "        &'{}'= {{", cmd.name);
+        let _ = writeln!(buf, "    $completions[$command]");
+        let _ = write!(buf, "}}");
+
+        EcoString::from(buf)
+    }
+
+    /// Emit one map entry per command path, keyed like the `$command` built at runtime.
+    fn write_entries(buf: &mut String, path: &[&str], cmd: &Command) {
+        let mut current_path = path.to_vec();
+        current_path.push(&cmd.name);
 
-        for opt in cmd.options.iter() {
+        let _ = writeln!(buf, "        &'{}'= {{", current_path.join(";"));
+
+        for subcmd in cmd.subcommands.iter() {
+            let desc = FishGenerator::truncate_after_period(&subcmd.description);
+            let _ = writeln!(
+                buf,
+                "            cand {} '{}'",
+                subcmd.name,
+                desc.replace('\'', "")
+            );
+        }
+
+        for opt in cmd.completion_options().iter() {
             let desc = FishGenerator::truncate_after_period(&opt.description);
             let desc_clean = desc.replace('\'', "");
             for name in opt.names.iter() {
@@ -307,11 +998,10 @@ impl ElvishGenerator {
         }
 
         let _ = writeln!(buf, "        }}");
-        let _ = writeln!(buf, "    ]");
-        let _ = writeln!(buf, "    $completions[$command]");
-        let _ = write!(buf, "}}");
 
-        EcoString::from(buf)
+        for subcmd in cmd.subcommands.iter() {
+            Self::write_entries(buf, &current_path, subcmd);
+        }
     }
 }
 
@@ -319,6 +1009,15 @@ pub struct NushellGenerator;
 
 impl NushellGenerator {
     pub fn generate(cmd: &Command) -> EcoString {
+        Self::generate_impl(cmd, false)
+    }
+
+    /// Generate completions with one `export extern "cmd sub"` block per subcommand.
+    pub fn generate_with_subcommands(cmd: &Command) -> EcoString {
+        Self::generate_impl(cmd, true)
+    }
+
+    fn generate_impl(cmd: &Command, include_subcommands: bool) -> EcoString {
         let estimated_size = 512 + cmd.options.len() * 48;
         let mut buf = String::with_capacity(estimated_size);
 
@@ -329,7 +1028,7 @@ impl NushellGenerator {
 
         // Collect options into BTreeSet for deduplication and sorting
         let all_opts: BTreeSet<&str> = cmd
-            .options
+            .completion_options()
             .iter()
             .flat_map(|opt| {
                 opt.names
@@ -363,10 +1062,98 @@ impl NushellGenerator {
         let _ = writeln!(buf, "  }}");
         let _ = writeln!(buf);
 
-        let _ = writeln!(buf, "  export extern {} [", cmd.name);
+        if include_subcommands {
+            Self::write_value_completers_rec(&mut buf, &[], cmd);
+            Self::write_extern_rec(&mut buf, &[], cmd);
+        } else {
+            Self::write_value_completers(&mut buf, &cmd.name, cmd);
+            Self::write_extern(&mut buf, &cmd.name, &cmd.name, cmd);
+        }
+
+        let _ = writeln!(buf, "}}");
+        let _ = writeln!(buf);
+        let _ = write!(buf, "export use completions *");
 
-        for opt in cmd.options.iter() {
+        EcoString::from(buf)
+    }
+
+    fn write_value_completers_rec(buf: &mut String, path: &[&str], cmd: &Command) {
+        let mut current_path = path.to_vec();
+        current_path.push(&cmd.name);
+
+        Self::write_value_completers(buf, &current_path.join(" "), cmd);
+
+        for subcmd in cmd.subcommands.iter() {
+            Self::write_value_completers_rec(buf, &current_path, subcmd);
+        }
+    }
+
+    /// Emit a `def "nu-complete <cmd> <opt>"` returning the accepted values of
+    /// every option with `possible_values`.
+    fn write_value_completers(buf: &mut String, cmd_path: &str, cmd: &Command) {
+        for opt in cmd.completion_options().iter() {
+            if opt.possible_values.is_empty() || opt.names.is_empty() {
+                continue;
+            }
+
+            let _ = writeln!(
+                buf,
+                "  def \"{}\" [] {{",
+                Self::value_completer_name(cmd_path, opt)
+            );
+            let _ = write!(buf, "    [ ");
+            for (i, value) in opt.possible_values.iter().enumerate() {
+                if i > 0 {
+                    let _ = write!(buf, " ");
+                }
+                let _ = write!(buf, "\"{}\"", value);
+            }
+            let _ = writeln!(buf, " ]");
+            let _ = writeln!(buf, "  }}");
+            let _ = writeln!(buf);
+        }
+    }
+
+    fn value_completer_name(cmd_path: &str, opt: &Opt) -> String {
+        format!(
+            "nu-complete {} {}",
+            cmd_path,
+            opt.primary_name().raw.trim_start_matches('-')
+        )
+    }
+
+    fn write_extern_rec(buf: &mut String, path: &[&str], cmd: &Command) {
+        let mut current_path = path.to_vec();
+        current_path.push(&cmd.name);
+        let cmd_path = current_path.join(" ");
+
+        if path.is_empty() {
+            Self::write_extern(buf, &cmd.name, &cmd_path, cmd);
+        } else {
+            Self::write_extern(buf, &format!("\"{}\"", cmd_path), &cmd_path, cmd);
+        }
+
+        for subcmd in cmd.subcommands.iter() {
+            Self::write_extern_rec(buf, &current_path, subcmd);
+        }
+    }
+
+    fn write_extern(buf: &mut String, extern_name: &str, cmd_path: &str, cmd: &Command) {
+        let _ = writeln!(buf, "  export extern {} [", extern_name);
+
+        for opt in cmd.completion_options().iter() {
+            // Nothing to declare, and no name to derive a value completer from
+            if opt.names.is_empty() {
+                continue;
+            }
             let desc = FishGenerator::truncate_after_period(&opt.description);
+            let arg_type = if opt.is_file_arg() {
+                EcoString::from("path")
+            } else if opt.possible_values.is_empty() {
+                EcoString::from("string")
+            } else {
+                eco_format!("string@\"{}\"", Self::value_completer_name(cmd_path, opt))
+            };
 
             for name in opt.names.iter() {
                 if matches!(
@@ -381,8 +1168,8 @@ impl NushellGenerator {
                 } else {
                     let _ = writeln!(
                         buf,
-                        "    {}: string  # {} # {}",
-                        name.raw, opt.argument, desc
+                        "    {}: {}  # {} # {}",
+                        name.raw, arg_type, opt.argument, desc
                     );
                 }
             }
@@ -390,11 +1177,6 @@ impl NushellGenerator {
 
         let _ = writeln!(buf, "  ]");
         let _ = writeln!(buf);
-        let _ = writeln!(buf, "}}");
-        let _ = writeln!(buf);
-        let _ = write!(buf, "export use completions *");
-
-        EcoString::from(buf)
     }
 }
 
@@ -410,4 +1192,429 @@ mod tests {
             "This is a description"
         );
     }
+
+    #[test]
+    fn test_nushell_skips_nameless_options_with_values() {
+        let mut cmd = Command::new(EcoString::from("tool"));
+        cmd.options.push(Opt {
+            names: ecow::EcoVec::new(),
+            argument: EcoString::from("MODE"),
+            description: EcoString::from("Mode"),
+            possible_values: ecow::eco_vec![EcoString::from("a"), EcoString::from("b")],
+            ..Default::default()
+        });
+
+        let output = NushellGenerator::generate(&cmd);
+        assert!(!output.contains("string@"));
+    }
+
+    #[test]
+    fn test_generators_handle_optional_arguments() {
+        let mut cmd = Command::new(EcoString::from("tool"));
+        cmd.options.push(Opt {
+            names: ecow::eco_vec![
+                OptName::new(EcoString::from("-c"), OptNameType::ShortType),
+                OptName::new(EcoString::from("--count"), OptNameType::LongType),
+            ],
+            argument: EcoString::from("N"),
+            description: EcoString::from("Number of repetitions"),
+            argument_optional: true,
+            ..Default::default()
+        });
+
+        let zsh = ZshGenerator::generate(&cmd);
+        assert!(zsh.contains("options+=('--count=-[Number of repetitions]::N:')"));
+        assert!(zsh.contains("options+=('-c[Number of repetitions]::N:')"));
+
+        let fish = FishGenerator::generate(&cmd);
+        assert!(fish.contains("complete -c tool -l 'count' -x -d 'Number of repetitions'"));
+
+        let bash = BashGenerator::generate(&cmd);
+        assert!(bash.contains("--count"));
+    }
+
+    #[test]
+    fn test_generators_skip_options_when_not_accepted() {
+        let mut cmd = Command::new(EcoString::from("tool"));
+        cmd.options.push(Opt {
+            names: ecow::eco_vec![OptName::new(
+                EcoString::from("--verbose"),
+                OptNameType::LongType
+            )],
+            description: EcoString::from("Be verbose"),
+            ..Default::default()
+        });
+        cmd.accepts_options = false;
+
+        assert!(!ZshGenerator::generate(&cmd).contains("--verbose"));
+        assert!(!FishGenerator::generate(&cmd).contains("verbose"));
+        assert!(!BashGenerator::generate(&cmd).contains("--verbose"));
+        assert!(!ElvishGenerator::generate(&cmd).contains("verbose"));
+        assert!(!NushellGenerator::generate(&cmd).contains("--verbose"));
+
+        cmd.accepts_options = true;
+        assert!(BashGenerator::generate(&cmd).contains("--verbose"));
+    }
+
+    #[test]
+    fn test_elvish_routes_subcommand_keys() {
+        let flag = |raw: &str, desc: &str| Opt {
+            names: ecow::eco_vec![OptName::new(EcoString::from(raw), OptNameType::LongType)],
+            description: EcoString::from(desc),
+            ..Default::default()
+        };
+
+        let mut log = Command::new(EcoString::from("log"));
+        log.description = EcoString::from("Show commits");
+        log.options.push(flag("--oneline", "One line per commit"));
+        let mut git = Command::new(EcoString::from("git"));
+        git.options.push(flag("--version", "Print version"));
+        git.subcommands.push(log);
+
+        let output = ElvishGenerator::generate(&git);
+        assert!(output.contains(
+            "        &'git'= {\n\
+             \x20           cand log 'Show commits'\n\
+             \x20           cand --version 'Print version'\n\
+             \x20       }\n"
+        ));
+        assert!(output.contains(
+            "        &'git;log'= {\n\
+             \x20           cand --oneline 'One line per commit'\n\
+             \x20       }\n"
+        ));
+        assert!(output.contains("        if (has-key $completions $command';'$word) {\n"));
+    }
+
+    #[test]
+    fn test_bash_subcommand_case_block() {
+        let flag = |raw: &str, opt_type| Opt {
+            names: ecow::eco_vec![OptName::new(EcoString::from(raw), opt_type)],
+            description: EcoString::from("Flag"),
+            ..Default::default()
+        };
+
+        let mut build = Command::new(EcoString::from("build"));
+        build.options.push(flag("--release", OptNameType::LongType));
+        build.options.push(flag("-j", OptNameType::ShortType));
+        let mut remote = Command::new(EcoString::from("remote"));
+        remote
+            .subcommands
+            .push(Command::new(EcoString::from("add")));
+        let mut cargo = Command::new(EcoString::from("cargo"));
+        cargo.options.push(flag("--version", OptNameType::LongType));
+        cargo.subcommands.push(build);
+        cargo.subcommands.push(remote);
+
+        let output = BashGenerator::generate_with_compat(&cargo, false);
+        assert!(output.contains(
+            "  case \"${COMP_WORDS[1]}\" in\n\
+             \x20   build)\n\
+             \x20     opts=\"--release -j\"\n\
+             \x20     ;;\n\
+             \x20   remote)\n\
+             \x20     opts=\"add\"\n\
+             \x20     ;;\n\
+             \x20   *)\n\
+             \x20     opts=\"--version build remote\"\n\
+             \x20     ;;\n\
+             \x20 esac\n"
+        ));
+
+        let compat = BashGenerator::generate_with_compat(&cargo, true);
+        assert!(compat.contains("      opts=\"--release:Flag -j:Flag\"\n"));
+
+        let flat = BashGenerator::generate(&Command::new(EcoString::from("tool")));
+        assert!(!flat.contains("case"));
+    }
+
+    #[test]
+    fn test_bash_sanitizes_function_name() {
+        assert_eq!(BashGenerator::sanitize_function_name("my-tool"), "my_tool");
+        assert_eq!(BashGenerator::sanitize_function_name("a.b+c_d"), "a_b_c_d");
+
+        let output = BashGenerator::generate(&Command::new(EcoString::from("my-tool")));
+        assert!(output.starts_with("_my_tool()\n"));
+        assert!(output.ends_with("-F _my_tool my-tool"));
+        assert!(!output.contains("_my-tool"));
+    }
+
+    #[test]
+    fn test_zsh_sanitizes_function_name_but_not_compdef() {
+        let mut cmd = Command::new(EcoString::from("python3.12"));
+        cmd.subcommands
+            .push(Command::new(EcoString::from("pip-install")));
+
+        let output = ZshGenerator::generate(&cmd);
+        assert!(output.starts_with("#compdef python3.12\n"));
+        assert!(output.contains("\n_python3_12() {"));
+        assert!(output.contains("\n_python3_12_pip_install() {"));
+        assert!(output.contains("        pip-install)\n          _python3_12_pip_install\n"));
+        assert!(output.ends_with("_python3_12 \"$@\""));
+    }
+
+    #[test]
+    fn test_zsh_generate_exclusive_prefixes_group_members() {
+        let mut cmd = Command::new(EcoString::from("fmt"));
+        for format in ["json", "yaml", "toml", "verbose"] {
+            cmd.options.push(Opt {
+                names: ecow::eco_vec![OptName::new(
+                    EcoString::from(format!("--{}", format)),
+                    OptNameType::LongType
+                )],
+                description: EcoString::from(format!("Use {}", format)),
+                ..Default::default()
+            });
+        }
+        cmd.exclusive_groups.push(ecow::eco_vec![
+            EcoString::from("--json"),
+            EcoString::from("--yaml"),
+            EcoString::from("--toml"),
+        ]);
+
+        let output = ZshGenerator::generate_exclusive(&cmd);
+        for format in ["json", "yaml", "toml"] {
+            assert!(output.contains(&format!(
+                "options+=('(--json --yaml --toml)--{}[Use {}]')",
+                format, format
+            )));
+        }
+        assert!(output.contains("options+=('--verbose[Use verbose]')"));
+        assert!(!ZshGenerator::generate(&cmd).contains("(--json"));
+    }
+
+    #[test]
+    fn test_zsh_generate_grouped_sections() {
+        let mut cmd = Command::new(EcoString::from("fmt"));
+        for name in ["help", "json", "yaml", "verbose"] {
+            cmd.options.push(Opt {
+                names: ecow::eco_vec![OptName::new(
+                    EcoString::from(format!("--{}", name)),
+                    OptNameType::LongType
+                )],
+                description: EcoString::from(format!("Use {}", name)),
+                ..Default::default()
+            });
+        }
+        cmd.option_groups.push(crate::types::OptionGroup {
+            name: EcoString::from("Output options"),
+            options: cmd.options[1..3].iter().cloned().collect(),
+        });
+        cmd.option_groups.push(crate::types::OptionGroup {
+            name: EcoString::from("Logging"),
+            options: cmd.options[3..].iter().cloned().collect(),
+        });
+
+        let output = ZshGenerator::generate_grouped(&cmd);
+        assert!(output.contains(
+            "  options+=('--help[Use help]')\n\
+             \x20 # Section: Output options\n\
+             \x20 options+=('--json[Use json]')\n\
+             \x20 options+=('--yaml[Use yaml]')\n\
+             \x20 # Section: Logging\n\
+             \x20 options+=('--verbose[Use verbose]')\n"
+        ));
+        assert!(!ZshGenerator::generate(&cmd).contains("# Section:"));
+
+        cmd.option_groups = ecow::EcoVec::new();
+        assert_eq!(
+            ZshGenerator::generate_grouped(&cmd),
+            ZshGenerator::generate(&cmd)
+        );
+    }
+
+    #[test]
+    fn test_fish_generate_exclusive_uses_groups() {
+        let mut cmd = Command::new(EcoString::from("fmt"));
+        for format in ["json", "yaml", "verbose"] {
+            cmd.options.push(Opt {
+                names: ecow::eco_vec![OptName::new(
+                    EcoString::from(format!("--{}", format)),
+                    OptNameType::LongType
+                )],
+                description: EcoString::from(format!("Use {}", format)),
+                ..Default::default()
+            });
+        }
+        cmd.exclusive_groups.push(ecow::eco_vec![
+            EcoString::from("--json"),
+            EcoString::from("--yaml"),
+        ]);
+
+        let output = FishGenerator::generate_exclusive(&cmd);
+        assert!(output.contains("-l 'json' -n 'not __fish_contains_opt yaml'"));
+        assert!(output.contains("-l 'yaml' -n 'not __fish_contains_opt json'"));
+        assert!(!output.contains("-l 'verbose' -n"));
+        assert!(!FishGenerator::generate(&cmd).contains("__fish_contains_opt"));
+    }
+
+    #[test]
+    fn test_zsh_includes_inherited_options() {
+        let mut root = Command::new(EcoString::from("git"));
+        root.options.push(Opt {
+            names: ecow::eco_vec![OptName::new(
+                EcoString::from("--no-pager"),
+                OptNameType::LongType
+            )],
+            description: EcoString::from("Do not pipe output into a pager"),
+            ..Default::default()
+        });
+        let mut sub = Command::new(EcoString::from("log"));
+        sub.options.push(Opt {
+            names: ecow::eco_vec![OptName::new(
+                EcoString::from("--oneline"),
+                OptNameType::LongType
+            )],
+            description: EcoString::from("One line per commit"),
+            ..Default::default()
+        });
+
+        let zsh = ZshGenerator::generate_with_root(&sub, Some(&root));
+        assert!(zsh.contains("options+=('--no-pager[Do not pipe output into a pager]')"));
+        assert!(zsh.contains("options+=('--oneline[One line per commit]')"));
+        assert!(!ZshGenerator::generate_with_root(&sub, None).contains("no-pager"));
+
+        // Nested subcommands get the root's options in their own function
+        root.subcommands.push(sub);
+        let zsh = ZshGenerator::generate_with_root(&root, Some(&root));
+        let log_fn = &zsh[zsh.find("_git_log() {").unwrap()..zsh.find("_git() {").unwrap()];
+        assert!(log_fn.contains("options+=('--no-pager[Do not pipe output into a pager]')"));
+    }
+
+    #[test]
+    fn test_fish_subcommand_conditions() {
+        let flag = |raw: &str| Opt {
+            names: ecow::eco_vec![OptName::new(EcoString::from(raw), OptNameType::LongType)],
+            description: EcoString::from("Flag"),
+            ..Default::default()
+        };
+
+        let mut add = Command::new(EcoString::from("add"));
+        add.description = EcoString::from("Add a remote. Extra text");
+        add.options.push(flag("--fetch"));
+        let mut remote = Command::new(EcoString::from("remote"));
+        remote.description = EcoString::from("Manage remotes");
+        remote.options.push(flag("--verbose"));
+        remote.subcommands.push(add);
+        let mut git = Command::new(EcoString::from("git"));
+        git.options.push(flag("--version"));
+        git.subcommands.push(remote);
+
+        let output = FishGenerator::generate(&git);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "complete -c git -f -n '__fish_use_subcommand' -a 'remote' -d 'Manage remotes'",
+                "complete -c git -l 'version'  -d 'Flag'",
+                "complete -c git -f -n '__fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add' -a 'add' -d 'Add a remote'",
+                "complete -c git -l 'verbose' -n '__fish_seen_subcommand_from remote' -d 'Flag'",
+                "complete -c git -l 'fetch' -n '__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from add' -d 'Flag'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fish_coalesces_duplicate_names() {
+        let verbose = |desc: &str| Opt {
+            names: ecow::eco_vec![OptName::new(
+                EcoString::from("--verbose"),
+                OptNameType::LongType
+            )],
+            description: EcoString::from(desc),
+            ..Default::default()
+        };
+        let mut cmd = Command::new(EcoString::from("test"));
+        cmd.options.push(verbose("Be verbose"));
+        cmd.options.push(verbose("Print more output"));
+        cmd.options.push(verbose("Be verbose"));
+
+        let output = FishGenerator::generate(&cmd);
+        assert_eq!(output.matches("-l 'verbose'").count(), 1);
+        assert!(output.contains("-d 'Be verbose / Print more output'"));
+    }
+
+    #[test]
+    fn test_fish_opt_arg_flags() {
+        let opt = |argument: &str| Opt {
+            names: ecow::EcoVec::new(),
+            argument: EcoString::from(argument),
+            ..Default::default()
+        };
+
+        assert_eq!(FishGenerator::opt_arg_requires_flag(&opt("")), "");
+        assert_eq!(FishGenerator::opt_arg_file_flag(&opt("")), "");
+        assert_eq!(FishGenerator::opt_arg_requires_flag(&opt("FILE")), "-r");
+        assert_eq!(FishGenerator::opt_arg_file_flag(&opt("FILE")), "-F");
+        assert_eq!(FishGenerator::opt_arg_requires_flag(&opt("NUM")), "-x");
+        assert_eq!(FishGenerator::opt_arg_file_flag(&opt("NUM")), "");
+    }
+
+    #[test]
+    fn test_fish_possible_values() {
+        let mut cmd = Command::new(EcoString::from("ls"));
+        cmd.options.push(Opt {
+            names: ecow::eco_vec![OptName::new(
+                EcoString::from("--color"),
+                OptNameType::LongType
+            )],
+            argument: EcoString::from("WHEN"),
+            description: EcoString::from("Use colors: always, auto, never"),
+            possible_values: ecow::eco_vec![
+                EcoString::from("always"),
+                EcoString::from("auto"),
+                EcoString::from("never")
+            ],
+            ..Default::default()
+        });
+
+        let output = FishGenerator::generate(&cmd);
+        assert!(output.contains("-l 'color' -x -a 'always auto never' -d"));
+    }
+
+    #[test]
+    fn test_generator_trait_objects() {
+        fn assert_send_sync<T: Send + Sync + ?Sized>() {}
+        assert_send_sync::<dyn Generator + Send + Sync>();
+
+        let cmd = Command::new(EcoString::from("mycmd"));
+        let generators: Vec<(Box<dyn Generator + Send + Sync>, EcoString)> = vec![
+            (Box::new(FishGenerator), FishGenerator::generate(&cmd)),
+            (Box::new(ZshGenerator), ZshGenerator::generate(&cmd)),
+            (Box::new(BashGenerator), BashGenerator::generate(&cmd)),
+            (Box::new(ElvishGenerator), ElvishGenerator::generate(&cmd)),
+            (Box::new(NushellGenerator), NushellGenerator::generate(&cmd)),
+        ];
+        for (generator, expected) in &generators {
+            assert_eq!(generator.generate(&cmd), *expected);
+
+            let mut out = Vec::new();
+            generator.generate_to_writer(&cmd, &mut out).unwrap();
+            assert_eq!(out, expected.as_bytes());
+        }
+
+        let custom = |cmd: &Command| eco_format!("complete {}\n", cmd.name);
+        assert_eq!(Generator::generate(&custom, &cmd), "complete mycmd\n");
+    }
+
+    #[test]
+    fn test_sanitize_bash_description() {
+        assert_eq!(
+            BashGenerator::sanitize_bash_description("Enable verbose mode"),
+            "Enable_verbose_mode"
+        );
+        assert_eq!(
+            BashGenerator::sanitize_bash_description("  Use `key: value` (see docs). More"),
+            "Use_key_value_see_docs"
+        );
+        assert_eq!(BashGenerator::sanitize_bash_description("..."), "");
+        assert_eq!(BashGenerator::sanitize_bash_description("--- ***"), "");
+
+        let long = BashGenerator::sanitize_bash_description(
+            "Print a very long description that goes on and on without stopping",
+        );
+        assert!(long.len() <= 40);
+        assert!(!long.ends_with('_'));
+        assert_eq!(long, "Print_a_very_long_description_that_goes");
+    }
 }
@@ -0,0 +1,184 @@
+use crate::types::{Command, Opt, OptNameType};
+use ecow::EcoString;
+use serde_json::{Map, Value, json};
+
+/// Generates a [carapace-spec](https://carapace-sh.github.io/carapace-spec/)
+/// document. Carapace reads specs as YAML, which JSON is a subset of.
+///
+/// Flags follow the spec's key syntax: `-o, --output=` is a flag with
+/// shorthand `-o` that takes a value (`?` instead of `=` when the value is
+/// optional), mapped to its description. File arguments complete with
+/// `$files` and possible values are listed under `completion.flag`.
+pub struct CarapaceGenerator;
+
+impl CarapaceGenerator {
+    pub fn generate(cmd: &Command) -> EcoString {
+        let spec = Self::command_spec(cmd);
+        EcoString::from(serde_json::to_string_pretty(&spec).unwrap_or_default())
+    }
+
+    fn command_spec(cmd: &Command) -> Value {
+        let mut spec = Map::new();
+        spec.insert("name".into(), json!(cmd.name.as_str()));
+        if !cmd.description.is_empty() {
+            spec.insert("description".into(), json!(cmd.description.as_str()));
+        }
+
+        let mut flags = Map::new();
+        let mut flag_completion = Map::new();
+        for opt in cmd.completion_options() {
+            let Some((keys, completion_name)) = Self::flag_keys(opt) else {
+                continue;
+            };
+            for key in keys {
+                flags.insert(key, json!(opt.description.as_str()));
+            }
+            if let Some(values) = Self::flag_completion(opt) {
+                flag_completion.insert(completion_name, values);
+            }
+        }
+        if !flags.is_empty() {
+            spec.insert("flags".into(), Value::Object(flags));
+        }
+        if !flag_completion.is_empty() {
+            spec.insert("completion".into(), json!({ "flag": flag_completion }));
+        }
+
+        if !cmd.subcommands.is_empty() {
+            let commands: Vec<Value> = cmd.subcommands.iter().map(Self::command_spec).collect();
+            spec.insert("commands".into(), Value::Array(commands));
+        }
+
+        Value::Object(spec)
+    }
+
+    /// Spec keys for `opt` and the name its completion is registered under.
+    ///
+    /// The first short name is paired with the first long (or old-style)
+    /// name; any further long names become flags of their own.
+    fn flag_keys(opt: &Opt) -> Option<(Vec<String>, String)> {
+        let short = opt
+            .names
+            .iter()
+            .find(|name| name.opt_type == OptNameType::ShortType);
+        let mut longs = opt
+            .names
+            .iter()
+            .filter(|name| matches!(name.opt_type, OptNameType::LongType | OptNameType::OldType));
+        let first_long = longs.next();
+
+        let suffix = if opt.argument.is_empty() {
+            ""
+        } else if opt.argument_optional {
+            "?"
+        } else {
+            "="
+        };
+
+        let (mut keys, completion_name) = match (short, first_long) {
+            (Some(s), Some(l)) => (
+                vec![format!("{}, {}{}", s.raw, l.raw, suffix)],
+                l.raw.trim_start_matches('-'),
+            ),
+            (None, Some(l)) => (
+                vec![format!("{}{}", l.raw, suffix)],
+                l.raw.trim_start_matches('-'),
+            ),
+            (Some(s), None) => (
+                vec![format!("{}{}", s.raw, suffix)],
+                s.raw.trim_start_matches('-'),
+            ),
+            (None, None) => return None,
+        };
+        keys.extend(longs.map(|l| format!("{}{}", l.raw, suffix)));
+
+        Some((keys, completion_name.to_string()))
+    }
+
+    /// `["$files"]` for file arguments, the listed values otherwise.
+    fn flag_completion(opt: &Opt) -> Option<Value> {
+        if !opt.possible_values.is_empty() {
+            Some(json!(
+                opt.possible_values
+                    .iter()
+                    .map(EcoString::as_str)
+                    .collect::<Vec<_>>()
+            ))
+        } else if opt.is_file_arg() {
+            Some(json!(["$files"]))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OptName;
+    use ecow::eco_vec;
+
+    fn opt(names: &[(&str, OptNameType)], argument: &str, values: &[&str]) -> Opt {
+        Opt {
+            names: names
+                .iter()
+                .map(|(raw, t)| OptName::new(EcoString::from(*raw), *t))
+                .collect(),
+            argument: EcoString::from(argument),
+            description: EcoString::from("desc"),
+            possible_values: values.iter().map(|v| EcoString::from(*v)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_carapace_spec() {
+        let mut cmd = Command::new(EcoString::from("mycmd"));
+        cmd.description = EcoString::from("My command");
+        cmd.options = eco_vec![
+            opt(
+                &[
+                    ("-o", OptNameType::ShortType),
+                    ("--output", OptNameType::LongType)
+                ],
+                "FILE",
+                &[],
+            ),
+            opt(
+                &[("--color", OptNameType::LongType)],
+                "WHEN",
+                &["always", "never"],
+            ),
+            opt(&[("-v", OptNameType::ShortType)], "", &[]),
+        ];
+        let mut sub = Command::new(EcoString::from("build"));
+        sub.options = eco_vec![opt(&[("--release", OptNameType::LongType)], "", &[])];
+        cmd.subcommands = eco_vec![sub];
+
+        let spec: Value = serde_json::from_str(&CarapaceGenerator::generate(&cmd)).unwrap();
+        assert_eq!(
+            spec,
+            json!({
+                "name": "mycmd",
+                "description": "My command",
+                "flags": {
+                    "-o, --output=": "desc",
+                    "--color=": "desc",
+                    "-v": "desc",
+                },
+                "completion": {
+                    "flag": {
+                        "output": ["$files"],
+                        "color": ["always", "never"],
+                    }
+                },
+                "commands": [
+                    {
+                        "name": "build",
+                        "flags": { "--release": "desc" },
+                    }
+                ],
+            })
+        );
+    }
+}
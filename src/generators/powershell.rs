@@ -0,0 +1,152 @@
+use super::FishGenerator;
+use crate::types::{Command, OptNameType};
+use ecow::EcoString;
+use std::fmt::Write;
+
+/// Generates a PowerShell (`.ps1`) argument completer.
+///
+/// The script block rebuilds the subcommand path from the bare words typed so
+/// far (`cmd;sub`), switches on it, and filters that command's
+/// `CompletionResult`s by `$wordToComplete`.
+pub struct PowerShellGenerator;
+
+impl PowerShellGenerator {
+    pub fn generate(cmd: &Command) -> EcoString {
+        let estimated_size = 1024 + cmd.options.len() * 128;
+        let mut buf = String::with_capacity(estimated_size);
+        let name = Self::quote(&cmd.name);
+
+        let _ = writeln!(
+            buf,
+            "Register-ArgumentCompleter -Native -CommandName {} -ScriptBlock {{",
+            name
+        );
+        buf.push_str("    param($wordToComplete, $commandAst, $cursorPosition)\n\n");
+        buf.push_str("    $commandElements = $commandAst.CommandElements\n");
+        buf.push_str("    $command = @(\n");
+        let _ = writeln!(buf, "        {}", name);
+        buf.push_str("        for ($i = 1; $i -lt $commandElements.Count; $i++) {\n");
+        buf.push_str("            $element = $commandElements[$i]\n");
+        buf.push_str("            if ($element -isnot [System.Management.Automation.Language.StringConstantExpressionAst] -or\n");
+        buf.push_str("                $element.StringConstantType -ne [System.Management.Automation.Language.StringConstantType]::BareWord -or\n");
+        buf.push_str("                $element.Value.StartsWith('-') -or\n");
+        buf.push_str("                $element.Value -eq $wordToComplete) {\n");
+        buf.push_str("                break\n");
+        buf.push_str("            }\n");
+        buf.push_str("            $element.Value\n");
+        buf.push_str("        }) -join ';'\n\n");
+        buf.push_str("    $completions = @(switch ($command) {\n");
+        Self::write_cases(&mut buf, &cmd.name, cmd);
+        buf.push_str("    })\n\n");
+        buf.push_str("    $completions.Where{ $_.CompletionText -like \"$wordToComplete*\" } |\n");
+        buf.push_str("        Sort-Object -Property ListItemText\n");
+        buf.push_str("}\n");
+
+        EcoString::from(buf)
+    }
+
+    /// One `switch` arm per command, keyed by its `;`-joined path.
+    fn write_cases(buf: &mut String, path: &str, cmd: &Command) {
+        let _ = writeln!(buf, "        {} {{", Self::quote(path));
+        for opt in cmd.completion_options().iter() {
+            let desc = FishGenerator::truncate_after_period(&opt.description);
+            for name in opt.names.iter() {
+                if matches!(
+                    name.opt_type,
+                    OptNameType::SingleDashAlone | OptNameType::DoubleDashAlone
+                ) {
+                    continue;
+                }
+                Self::write_result(buf, &name.raw, "ParameterName", desc);
+            }
+        }
+        for sub in cmd.subcommands.iter() {
+            let desc = FishGenerator::truncate_after_period(&sub.description);
+            Self::write_result(buf, &sub.name, "ParameterValue", desc);
+        }
+        buf.push_str("            break\n");
+        buf.push_str("        }\n");
+
+        for sub in cmd.subcommands.iter() {
+            Self::write_cases(buf, &format!("{};{}", path, sub.name), sub);
+        }
+    }
+
+    fn write_result(buf: &mut String, text: &str, result_type: &str, desc: &str) {
+        // `CompletionResult` rejects an empty tooltip
+        let tooltip = if desc.is_empty() { text } else { desc };
+        let _ = writeln!(
+            buf,
+            "            [System.Management.Automation.CompletionResult]::new({}, {}, [System.Management.Automation.CompletionResultType]::{}, {})",
+            Self::quote(text),
+            Self::quote(text),
+            result_type,
+            Self::quote(tooltip)
+        );
+    }
+
+    /// A single-quoted PowerShell string. PowerShell also treats the curly
+    /// quotes U+2018..=U+201B as single quotes, so those are doubled like `'`.
+    #[inline]
+    fn quote(s: &str) -> String {
+        let mut quoted = String::with_capacity(s.len() + 2);
+        quoted.push('\'');
+        for c in s.chars() {
+            if matches!(c, '\'' | '\u{2018}'..='\u{201B}') {
+                quoted.push(c);
+            }
+            quoted.push(c);
+        }
+        quoted.push('\'');
+        quoted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Opt, OptName};
+
+    #[test]
+    fn test_powershell_cases_per_subcommand() {
+        let mut cmd = Command::new(EcoString::from("tool"));
+        cmd.options.push(Opt {
+            names: ecow::eco_vec![
+                OptName::new(EcoString::from("-v"), OptNameType::ShortType),
+                OptName::new(EcoString::from("--verbose"), OptNameType::LongType),
+            ],
+            description: EcoString::from("Don't be quiet. More text"),
+            ..Default::default()
+        });
+        let mut sub = Command::new(EcoString::from("build"));
+        sub.description = EcoString::from("Build it");
+        cmd.subcommands.push(sub);
+
+        let output = PowerShellGenerator::generate(&cmd);
+        assert!(output.starts_with(
+            "Register-ArgumentCompleter -Native -CommandName 'tool' -ScriptBlock {\n"
+        ));
+        assert!(output.contains(
+            "        'tool' {\n            [System.Management.Automation.CompletionResult]::new('-v', '-v', [System.Management.Automation.CompletionResultType]::ParameterName, 'Don''t be quiet')\n"
+        ));
+        assert!(output.contains(
+            "::new('build', 'build', [System.Management.Automation.CompletionResultType]::ParameterValue, 'Build it')"
+        ));
+        // Each subcommand gets its own arm, keyed by its path
+        assert!(output.contains("        'tool;build' {\n            break\n        }\n"));
+        assert!(output.ends_with("        Sort-Object -Property ListItemText\n}\n"));
+    }
+
+    #[test]
+    fn test_powershell_quote_doubles_curly_quotes() {
+        assert_eq!(PowerShellGenerator::quote("it's"), "'it''s'");
+        assert_eq!(
+            PowerShellGenerator::quote("don\u{2019}t \u{2018}x\u{201B}"),
+            "'don\u{2019}\u{2019}t \u{2018}\u{2018}x\u{201B}\u{201B}'"
+        );
+        assert_eq!(
+            PowerShellGenerator::quote("\u{201C}a\u{201D}"),
+            "'\u{201C}a\u{201D}'"
+        );
+    }
+}
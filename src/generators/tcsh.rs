@@ -0,0 +1,191 @@
+use crate::types::{Command, Opt, OptNameType};
+use ecow::EcoString;
+use std::fmt::Write;
+
+/// Generates a tcsh `complete` command.
+///
+/// Options that take a file complete with `n/--opt/f/` (value in the next
+/// word) and `c/--opt=/f/` (value attached with `=`); options with
+/// [`Opt::possible_values`] use `(a b c)` in place of `f`. Subcommands are
+/// offered in the first position and every other word falls back to files.
+pub struct TcshGenerator;
+
+impl TcshGenerator {
+    pub fn generate(cmd: &Command) -> EcoString {
+        let mut rules: Vec<String> = Vec::with_capacity(cmd.options.len() + 4);
+
+        // Value rules go first: tcsh uses the first matching pattern, and
+        // `c/--/` would otherwise swallow `--opt=`.
+        for opt in cmd.completion_options() {
+            let Some(values) = Self::value_list(opt) else {
+                continue;
+            };
+            for name in &opt.names {
+                if !Self::is_safe_word(&name.raw) {
+                    continue;
+                }
+                match name.opt_type {
+                    OptNameType::LongType => {
+                        rules.push(format!("n/{}/{}/", name.raw, values));
+                        rules.push(format!("c/{}=/{}/", name.raw, values));
+                    }
+                    OptNameType::ShortType | OptNameType::OldType => {
+                        rules.push(format!("n/{}/{}/", name.raw, values));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // `c/--/` before `c/-/`, since every `--` word also starts with `-`
+        let long = Self::option_words(cmd, |t| t == OptNameType::LongType);
+        if !long.is_empty() {
+            rules.push(format!("c/--/({})/", long.join(" ")));
+        }
+        let short = Self::option_words(cmd, |t| {
+            matches!(t, OptNameType::ShortType | OptNameType::OldType)
+        });
+        if !short.is_empty() {
+            rules.push(format!("c/-/({})/", short.join(" ")));
+        }
+
+        let subcommands: Vec<&str> = cmd
+            .subcommands
+            .iter()
+            .map(|sub| sub.name.as_str())
+            .filter(|name| Self::is_safe_word(name))
+            .collect();
+        if !subcommands.is_empty() {
+            rules.push(format!("p/1/({})/", subcommands.join(" ")));
+        }
+
+        rules.push("p/*/f/".to_string());
+
+        let mut buf = String::with_capacity(64 + rules.iter().map(|r| r.len() + 8).sum::<usize>());
+        let _ = writeln!(buf, "# tcsh completion for {}", cmd.name);
+        let _ = write!(buf, "complete {}", cmd.name);
+        for rule in &rules {
+            let _ = write!(buf, " \\\n    '{}'", rule);
+        }
+        buf.push('\n');
+
+        EcoString::from(buf)
+    }
+
+    /// `(a b c)` for options with known values, `f` for file arguments.
+    fn value_list(opt: &Opt) -> Option<String> {
+        let values: Vec<&str> = opt
+            .possible_values
+            .iter()
+            .map(EcoString::as_str)
+            .filter(|value| Self::is_safe_word(value))
+            .collect();
+
+        if !values.is_empty() {
+            Some(format!("({})", values.join(" ")))
+        } else if opt.is_file_arg() {
+            Some("f".to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Dashless option names whose type matches `filter`, in declaration order.
+    fn option_words(cmd: &Command, filter: impl Fn(OptNameType) -> bool) -> Vec<&str> {
+        let mut words: Vec<&str> = cmd
+            .completion_options()
+            .iter()
+            .flat_map(|opt| opt.names.iter())
+            .filter(|name| filter(name.opt_type))
+            .map(|name| name.raw.trim_start_matches('-'))
+            .filter(|word| !word.is_empty() && Self::is_safe_word(word))
+            .collect();
+        let mut seen = std::collections::HashSet::with_capacity(words.len());
+        words.retain(|word| seen.insert(*word));
+        words
+    }
+
+    /// Words that can appear inside a single-quoted `complete` pattern
+    /// without escaping.
+    #[inline]
+    fn is_safe_word(word: &str) -> bool {
+        !word.is_empty()
+            && !word.bytes().any(|b| {
+                b.is_ascii_whitespace() || matches!(b, b'/' | b'\'' | b'(' | b')' | b'\\' | b'!')
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OptName;
+    use ecow::eco_vec;
+
+    fn opt(names: &[(&str, OptNameType)], argument: &str, values: &[&str]) -> Opt {
+        Opt {
+            names: names
+                .iter()
+                .map(|(raw, t)| OptName::new(EcoString::from(*raw), *t))
+                .collect(),
+            argument: EcoString::from(argument),
+            possible_values: values.iter().map(|v| EcoString::from(*v)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_tcsh_value_rules() {
+        let mut cmd = Command::new(EcoString::from("mycmd"));
+        cmd.options = eco_vec![
+            opt(
+                &[
+                    ("-o", OptNameType::ShortType),
+                    ("--output", OptNameType::LongType)
+                ],
+                "FILE",
+                &[],
+            ),
+            opt(
+                &[("--color", OptNameType::LongType)],
+                "WHEN",
+                &["always", "auto", "never"],
+            ),
+            opt(&[("--jobs", OptNameType::LongType)], "N", &[]),
+            opt(&[("-v", OptNameType::ShortType)], "", &[]),
+        ];
+        cmd.subcommands = eco_vec![Command::new(EcoString::from("build"))];
+
+        let output = TcshGenerator::generate(&cmd);
+        let rules: Vec<&str> = output
+            .lines()
+            .skip(2)
+            .map(|line| line.trim().trim_end_matches(" \\"))
+            .collect();
+
+        assert_eq!(
+            rules,
+            [
+                "'n/-o/f/'",
+                "'n/--output/f/'",
+                "'c/--output=/f/'",
+                "'n/--color/(always auto never)/'",
+                "'c/--color=/(always auto never)/'",
+                "'c/--/(output color jobs)/'",
+                "'c/-/(o v)/'",
+                "'p/1/(build)/'",
+                "'p/*/f/'",
+            ]
+        );
+        assert!(output.starts_with("# tcsh completion for mycmd\ncomplete mycmd \\\n"));
+    }
+
+    #[test]
+    fn test_tcsh_skips_unsafe_words() {
+        assert!(TcshGenerator::is_safe_word("always"));
+        assert!(!TcshGenerator::is_safe_word("a/b"));
+        assert!(!TcshGenerator::is_safe_word("it's"));
+        assert!(!TcshGenerator::is_safe_word("two words"));
+        assert!(!TcshGenerator::is_safe_word(""));
+    }
+}
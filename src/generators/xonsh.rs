@@ -0,0 +1,226 @@
+use super::BashGenerator;
+use crate::types::{Command, OptNameType};
+use ecow::EcoString;
+use std::fmt::Write;
+
+/// Generates a xonsh completer module (`.xsh`).
+///
+/// The command tree is emitted as a nested dict, walked by a
+/// `@contextual_completer` function registered with `add_one_completer`.
+/// Long options that take a value complete as `--opt=` with
+/// `append_space=False`; after an option with possible values, those values
+/// are offered instead.
+pub struct XonshGenerator;
+
+impl XonshGenerator {
+    pub fn generate(cmd: &Command) -> EcoString {
+        let estimated_size = 1536 + cmd.options.len() * 80;
+        let mut buf = String::with_capacity(estimated_size);
+
+        let func = BashGenerator::sanitize_function_name(&cmd.name);
+        let spec = format!("_{}_SPEC", func.to_ascii_uppercase());
+        let name = Self::py_str(&cmd.name);
+
+        let _ = writeln!(buf, "# xonsh completion for {}", cmd.name);
+        buf.push_str("from xonsh.completers.completer import add_one_completer\n");
+        buf.push_str("from xonsh.completers.tools import RichCompletion, contextual_completer\n");
+        buf.push_str("from xonsh.parsers.completion_context import CompletionContext\n\n");
+
+        // Options are (name, description, argument, possible values)
+        let _ = write!(buf, "{} = ", spec);
+        Self::write_spec(&mut buf, cmd, 0);
+        buf.push_str("\n\n\n");
+
+        let _ = writeln!(buf, "@contextual_completer");
+        let _ = writeln!(buf, "def _{}_completer(context: CompletionContext):", func);
+        let _ = writeln!(buf, "    command = context.command");
+        let _ = writeln!(buf, "    if command is None or command.arg_index == 0:");
+        let _ = writeln!(buf, "        return None");
+        let _ = writeln!(buf, "    if command.args[0].value != {}:", name);
+        let _ = writeln!(buf, "        return None");
+        let _ = writeln!(buf);
+        let _ = writeln!(buf, "    spec = {}", spec);
+        let _ = writeln!(
+            buf,
+            "    words = [arg.value for arg in command.args[1 : command.arg_index]]"
+        );
+        let _ = writeln!(buf, "    for word in words:");
+        let _ = writeln!(buf, "        spec = spec[\"subcommands\"].get(word, spec)");
+        let _ = writeln!(buf);
+        let _ = writeln!(buf, "    prefix = command.prefix");
+        let _ = writeln!(buf, "    if words:");
+        let _ = writeln!(
+            buf,
+            "        for name, description, argument, values in spec[\"options\"]:"
+        );
+        let _ = writeln!(buf, "            if name == words[-1] and argument:");
+        let _ = writeln!(buf, "                if not values:");
+        let _ = writeln!(buf, "                    return None");
+        let _ = writeln!(buf, "                return {{");
+        let _ = writeln!(
+            buf,
+            "                    RichCompletion(value, append_space=True)"
+        );
+        let _ = writeln!(buf, "                    for value in values");
+        let _ = writeln!(buf, "                    if value.startswith(prefix)");
+        let _ = writeln!(buf, "                }}");
+        let _ = writeln!(buf);
+        let _ = writeln!(buf, "    completions = set()");
+        let _ = writeln!(buf, "    if prefix.startswith(\"-\"):");
+        let _ = writeln!(
+            buf,
+            "        for name, description, argument, values in spec[\"options\"]:"
+        );
+        let _ = writeln!(buf, "            if not name.startswith(prefix):");
+        let _ = writeln!(buf, "                continue");
+        let _ = writeln!(buf, "            if argument and name.startswith(\"--\"):");
+        let _ = writeln!(buf, "                completions.add(");
+        let _ = writeln!(buf, "                    RichCompletion(");
+        let _ = writeln!(buf, "                        name + \"=\",");
+        let _ = writeln!(
+            buf,
+            "                        display=name + \"=\" + argument,"
+        );
+        let _ = writeln!(buf, "                        description=description,");
+        let _ = writeln!(buf, "                        append_space=False,");
+        let _ = writeln!(buf, "                    )");
+        let _ = writeln!(buf, "                )");
+        let _ = writeln!(buf, "            else:");
+        let _ = writeln!(buf, "                completions.add(");
+        let _ = writeln!(
+            buf,
+            "                    RichCompletion(name, description=description, append_space=True)"
+        );
+        let _ = writeln!(buf, "                )");
+        let _ = writeln!(buf, "    else:");
+        let _ = writeln!(
+            buf,
+            "        for name, subcommand in spec[\"subcommands\"].items():"
+        );
+        let _ = writeln!(buf, "            if name.startswith(prefix):");
+        let _ = writeln!(buf, "                completions.add(");
+        let _ = writeln!(buf, "                    RichCompletion(");
+        let _ = writeln!(buf, "                        name,");
+        let _ = writeln!(
+            buf,
+            "                        description=subcommand[\"description\"],"
+        );
+        let _ = writeln!(buf, "                        append_space=True,");
+        let _ = writeln!(buf, "                    )");
+        let _ = writeln!(buf, "                )");
+        let _ = writeln!(buf, "    return completions or None");
+        let _ = writeln!(buf);
+        let _ = writeln!(buf);
+        let _ = writeln!(
+            buf,
+            "add_one_completer({}, _{}_completer, \"start\")",
+            name, func
+        );
+
+        EcoString::from(buf)
+    }
+
+    /// Write `cmd` as a `{"description", "options", "subcommands"}` dict literal.
+    fn write_spec(buf: &mut String, cmd: &Command, depth: usize) {
+        let pad = "    ".repeat(depth + 1);
+        let close = "    ".repeat(depth);
+
+        buf.push_str("{\n");
+        let _ = writeln!(
+            buf,
+            "{}\"description\": {},",
+            pad,
+            Self::py_str(&cmd.description)
+        );
+
+        let _ = write!(buf, "{}\"options\": [", pad);
+        let mut any_option = false;
+        for opt in cmd.completion_options() {
+            for name in &opt.names {
+                if matches!(
+                    name.opt_type,
+                    OptNameType::SingleDashAlone | OptNameType::DoubleDashAlone
+                ) {
+                    continue;
+                }
+                any_option = true;
+                let _ = write!(
+                    buf,
+                    "\n{}    ({}, {}, {}, (",
+                    pad,
+                    Self::py_str(&name.raw),
+                    Self::py_str(&opt.description),
+                    Self::py_str(&opt.argument)
+                );
+                for value in &opt.possible_values {
+                    let _ = write!(buf, "{}, ", Self::py_str(value));
+                }
+                buf.push_str(")),");
+            }
+        }
+        if any_option {
+            let _ = write!(buf, "\n{}", pad);
+        }
+        buf.push_str("],\n");
+
+        let _ = write!(buf, "{}\"subcommands\": {{", pad);
+        for sub in &cmd.subcommands {
+            let _ = write!(buf, "\n{}    {}: ", pad, Self::py_str(&sub.name));
+            Self::write_spec(buf, sub, depth + 2);
+            buf.push(',');
+        }
+        if !cmd.subcommands.is_empty() {
+            let _ = write!(buf, "\n{}", pad);
+        }
+        buf.push_str("},\n");
+        let _ = write!(buf, "{}}}", close);
+    }
+
+    /// A double-quoted Python string literal. JSON string escapes are a
+    /// subset of Python's, so serde_json does the quoting.
+    #[inline]
+    fn py_str(s: &str) -> String {
+        serde_json::to_string(s).unwrap_or_else(|_| String::from("\"\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Opt, OptName};
+    use ecow::eco_vec;
+
+    #[test]
+    fn test_xonsh_spec_and_registration() {
+        let mut cmd = Command::new(EcoString::from("my-cmd"));
+        cmd.options = eco_vec![Opt {
+            names: eco_vec![
+                OptName::new(EcoString::from("-c"), OptNameType::ShortType),
+                OptName::new(EcoString::from("--color"), OptNameType::LongType),
+            ],
+            argument: EcoString::from("WHEN"),
+            description: EcoString::from("Use \"colors\""),
+            possible_values: eco_vec![EcoString::from("always"), EcoString::from("never")],
+            ..Default::default()
+        }];
+        let mut sub = Command::new(EcoString::from("build"));
+        sub.description = EcoString::from("Build it");
+        cmd.subcommands = eco_vec![sub];
+
+        let output = XonshGenerator::generate(&cmd);
+        assert!(output.contains("_MY_CMD_SPEC = {\n"));
+        assert!(output.contains(
+            "(\"--color\", \"Use \\\"colors\\\"\", \"WHEN\", (\"always\", \"never\", )),"
+        ));
+        assert!(
+            output.contains("        \"build\": {\n            \"description\": \"Build it\",")
+        );
+        assert!(
+            output.contains(
+                "@contextual_completer\ndef _my_cmd_completer(context: CompletionContext):"
+            )
+        );
+        assert!(output.contains("append_space=False"));
+        assert!(output.ends_with("add_one_completer(\"my-cmd\", _my_cmd_completer, \"start\")\n"));
+    }
+}
@@ -1,12 +1,27 @@
+use crate::cli::{DEFAULT_HELP_COLUMNS, DEFAULT_MAN_WIDTH, DEFAULT_TAB_WIDTH};
+use crate::postprocessor::Postprocessor;
 use anyhow::{Result, anyhow};
 use bstr::ByteSlice;
 use ecow::EcoString;
 use memchr::memchr;
+use std::borrow::Cow;
+use std::io;
+use std::path::Path;
+use std::process::Output;
+use std::time::Duration;
 use tokio::process::Command as TokioCommand;
+use tracing::{debug, instrument};
+
+/// Base delay between command retries in milliseconds
+pub const DEFAULT_RETRY_DELAY_MS: u64 = 100;
+
+/// Upper bound for the exponential backoff delay in milliseconds
+const MAX_RETRY_DELAY_MS: u64 = 5000;
 
 pub struct IoHandler;
 
 impl IoHandler {
+    #[instrument(level = "debug")]
     pub async fn read_file(path: &str) -> Result<EcoString> {
         let content = tokio::fs::read_to_string(path)
             .await
@@ -14,32 +29,249 @@ impl IoHandler {
         Ok(EcoString::from(content))
     }
 
-    pub async fn read_from_command(cmd: &str) -> Result<EcoString> {
-        let output = TokioCommand::new("sh")
-            .arg("-c")
-            .arg(cmd)
-            .output()
+    /// Write `content` to `path`, creating missing parent directories. The file
+    /// is written under a temporary name and renamed into place, so readers
+    /// never see a partially written file.
+    #[instrument(level = "debug", skip(content))]
+    pub async fn write_file(path: &Path, content: &str) -> Result<()> {
+        let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+        if let Some(dir) = dir {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .map_err(|e| anyhow!("Failed to create directory {}: {}", dir.display(), e))?;
+        }
+
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| anyhow!("Invalid output path {}", path.display()))?;
+        let mut tmp_name = std::ffi::OsString::from(".");
+        tmp_name.push(file_name);
+        tmp_name.push(".tmp");
+        let tmp_path = path.with_file_name(tmp_name);
+
+        tokio::fs::write(&tmp_path, content.as_bytes())
+            .await
+            .map_err(|e| anyhow!("Failed to write file {}: {}", tmp_path.display(), e))?;
+        tokio::fs::rename(&tmp_path, path)
             .await
-            .map_err(|e| anyhow!("Failed to execute command: {}", e))?;
+            .map_err(|e| anyhow!("Failed to write file {}: {}", path.display(), e))?;
+        Ok(())
+    }
+
+    pub async fn read_from_command(cmd: &str) -> Result<EcoString> {
+        Self::read_from_command_with_retry(cmd, 1, DEFAULT_RETRY_DELAY_MS).await
+    }
+
+    /// Run a shell command, retrying transient spawn failures with exponential backoff.
+    ///
+    /// A non-zero exit status is treated as a permanent failure and never retried.
+    pub async fn read_from_command_with_retry(
+        cmd: &str,
+        max_attempts: u32,
+        base_delay_ms: u64,
+    ) -> Result<EcoString> {
+        Self::run_command(cmd, &[], max_attempts, base_delay_ms).await
+    }
 
+    /// Run a shell command and return its `(stdout, stderr)`.
+    ///
+    /// Unlike [`IoHandler::read_from_command`], a non-zero exit status is not an
+    /// error, so the stderr of a failing command is available for diagnostics.
+    pub async fn read_from_command_with_stderr(cmd: &str) -> Result<(EcoString, EcoString)> {
+        let output = Self::command_output(cmd, &[], 1, DEFAULT_RETRY_DELAY_MS).await?;
+        if !output.status.success() {
+            debug!("command '{}' exited with {}", cmd, output.status);
+        }
+
+        Ok((Self::lossy(&output.stdout), Self::lossy(&output.stderr)))
+    }
+
+    async fn run_command(
+        cmd: &str,
+        envs: &[(&str, &str)],
+        max_attempts: u32,
+        base_delay_ms: u64,
+    ) -> Result<EcoString> {
+        let output = Self::command_output(cmd, envs, max_attempts, base_delay_ms).await?;
         if !output.status.success() {
             return Err(anyhow!("Command failed: {}", cmd));
         }
 
-        Ok(EcoString::from(
-            String::from_utf8_lossy(&output.stdout).to_string(),
-        ))
+        Ok(Self::lossy(&output.stdout))
+    }
+
+    /// Spawn `sh -c cmd`, retrying transient spawn failures.
+    async fn command_output(
+        cmd: &str,
+        envs: &[(&str, &str)],
+        max_attempts: u32,
+        base_delay_ms: u64,
+    ) -> Result<Output> {
+        Self::retry_with_backoff(max_attempts, base_delay_ms, || {
+            TokioCommand::new("sh")
+                .arg("-c")
+                .arg(cmd)
+                .envs(envs.iter().copied())
+                .output()
+        })
+        .await
+        .map_err(|e| anyhow!("Failed to execute command: {}", e))
+    }
+
+    #[inline]
+    fn lossy(bytes: &[u8]) -> EcoString {
+        EcoString::from(String::from_utf8_lossy(bytes).as_ref())
+    }
+
+    async fn retry_with_backoff<T, F, Fut>(
+        max_attempts: u32,
+        base_delay_ms: u64,
+        mut op: F,
+    ) -> io::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = io::Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match op().await {
+                Err(e) if attempt < max_attempts && Self::is_transient(&e) => {
+                    let delay = base_delay_ms
+                        .saturating_mul(1 << (attempt - 1).min(16))
+                        .min(MAX_RETRY_DELAY_MS);
+                    debug!(
+                        "Attempt {} failed ({}), retrying in {}ms",
+                        attempt, e, delay
+                    );
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    #[inline]
+    fn is_transient(e: &io::Error) -> bool {
+        matches!(
+            e.kind(),
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+        )
     }
 
     pub async fn get_command_help(cmd: &str) -> Result<EcoString> {
-        Self::read_from_command(&format!("{} --help 2>/dev/null || {}", cmd, cmd)).await
+        Self::get_command_help_with_retry(cmd, 1).await
+    }
+
+    pub async fn get_command_help_with_retry(cmd: &str, max_attempts: u32) -> Result<EcoString> {
+        Self::get_command_help_with_columns(cmd, DEFAULT_HELP_COLUMNS, max_attempts).await
+    }
+
+    /// Run `{cmd} --help` with `COLUMNS` set to `columns`, so tools that wrap
+    /// their help to the terminal width do not fall back to 80 columns.
+    #[instrument(level = "debug")]
+    pub async fn get_command_help_with_columns(
+        cmd: &str,
+        columns: u16,
+        max_attempts: u32,
+    ) -> Result<EcoString> {
+        let columns = columns.to_string();
+        Self::run_command(
+            &format!("{} --help 2>/dev/null || {}", cmd, cmd),
+            &[("COLUMNS", &columns)],
+            max_attempts,
+            DEFAULT_RETRY_DELAY_MS,
+        )
+        .await
+    }
+
+    /// Like [`IoHandler::get_command_help_with_columns`], but keeps the command's
+    /// stderr. When the command fails, its stderr is part of the error.
+    pub async fn get_command_help_with_stderr(
+        cmd: &str,
+        columns: u16,
+        max_attempts: u32,
+    ) -> Result<(EcoString, EcoString)> {
+        let columns = columns.to_string();
+        let help_cmd = format!("{} --help || {}", cmd, cmd);
+        let output = Self::command_output(
+            &help_cmd,
+            &[("COLUMNS", &columns)],
+            max_attempts,
+            DEFAULT_RETRY_DELAY_MS,
+        )
+        .await?;
+
+        let stderr = Self::lossy(&output.stderr);
+        if !output.status.success() {
+            return Err(match stderr.trim() {
+                "" => anyhow!("Command failed: {}", help_cmd),
+                stderr => anyhow!("Command failed: {}: {}", help_cmd, stderr),
+            });
+        }
+
+        Ok((Self::lossy(&output.stdout), stderr))
+    }
+
+    /// Run `{cmd} --version` and return the first non-empty line of its output.
+    pub async fn get_command_version(cmd: &str) -> Result<EcoString> {
+        let output = Self::read_from_command(&format!("{} --version 2>/dev/null", cmd)).await?;
+        Ok(Self::first_non_empty_line(&output))
+    }
+
+    fn first_non_empty_line(text: &str) -> EcoString {
+        text.lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(EcoString::from)
+            .unwrap_or_default()
     }
 
     pub async fn get_manpage(cmd: &str) -> Result<EcoString> {
-        Self::read_from_command(&format!("man {} 2>/dev/null | col -bx", cmd)).await
+        Self::get_manpage_wide(cmd).await
+    }
+
+    /// Render the man page with `MANWIDTH` set to [`DEFAULT_MAN_WIDTH`], so that
+    /// non-interactive runs do not fall back to 80 columns and wrap descriptions.
+    pub async fn get_manpage_wide(cmd: &str) -> Result<EcoString> {
+        Self::get_manpage_with_width(cmd, DEFAULT_MAN_WIDTH, 1).await
+    }
+
+    pub async fn get_manpage_with_retry(cmd: &str, max_attempts: u32) -> Result<EcoString> {
+        Self::get_manpage_with_width(cmd, DEFAULT_MAN_WIDTH, max_attempts).await
     }
 
-    pub fn normalize_text(text: &str) -> EcoString {
+    /// Render the man page with `MANWIDTH` set to `width` columns.
+    #[instrument(level = "debug")]
+    pub async fn get_manpage_with_width(
+        cmd: &str,
+        width: u16,
+        max_attempts: u32,
+    ) -> Result<EcoString> {
+        let width = width.to_string();
+        Self::run_command(
+            &format!("man {} 2>/dev/null | col -bx", cmd),
+            &[("MANWIDTH", &width)],
+            max_attempts,
+            DEFAULT_RETRY_DELAY_MS,
+        )
+        .await
+    }
+
+    /// Strip CRLF carriage returns, expand tabs and collapse double spaces,
+    /// borrowing the input when it is already clean.
+    pub fn normalize_text(text: &str) -> Cow<'_, str> {
+        Self::normalize_text_with_tab_width(text, DEFAULT_TAB_WIDTH)
+    }
+
+    /// Like [`IoHandler::normalize_text`], expanding each tab to `tab_width` spaces.
+    pub fn normalize_text_with_tab_width(text: &str, tab_width: u8) -> Cow<'_, str> {
+        if let Cow::Owned(stripped) = Postprocessor::strip_carriage_returns(text) {
+            return Cow::Owned(
+                Self::normalize_text_with_tab_width(&stripped, tab_width).into_owned(),
+            );
+        }
+
         let bytes = text.as_bytes();
 
         // SIMD fast path: check if any tabs or double spaces exist
@@ -62,9 +294,11 @@ impl IoHandler {
         };
 
         if !has_tabs && !has_double_spaces {
-            return EcoString::from(text);
+            return Cow::Borrowed(text);
         }
 
+        let tab = " ".repeat(usize::from(tab_width));
+
         // Use bstr for SIMD-accelerated line iteration
         let mut result = String::with_capacity(text.len());
         let mut first = true;
@@ -80,16 +314,41 @@ impl IoHandler {
 
             // Apply transformations only if needed
             if has_tabs && has_double_spaces {
-                let replaced = line_str.replace('\t', "        ").replace("  ", " ");
+                let replaced = line_str.replace('\t', &tab).replace("  ", " ");
                 result.push_str(&replaced);
             } else if has_tabs {
-                result.push_str(&line_str.replace('\t', "        "));
+                result.push_str(&line_str.replace('\t', &tab));
             } else {
                 result.push_str(&line_str.replace("  ", " "));
             }
         }
 
-        EcoString::from(result)
+        Cow::Owned(result)
+    }
+
+    /// Expand each tab to `tab_width` spaces, keeping runs of spaces intact so
+    /// description columns survive.
+    pub fn expand_tabs(text: &str, tab_width: u8) -> Cow<'_, str> {
+        if memchr(b'\t', text.as_bytes()).is_none() {
+            return Cow::Borrowed(text);
+        }
+        Cow::Owned(text.replace('\t', &" ".repeat(usize::from(tab_width))))
+    }
+
+    /// Reject command names that would be interpreted by the shell, since
+    /// they are interpolated into `sh -c "<cmd> --help"` and `man <cmd>`.
+    pub fn sanitize_command_name(cmd: &str) -> Result<&str> {
+        const SHELL_METACHARACTERS: &[char] =
+            &[';', '|', '&', '$', '(', ')', '<', '>', '`', '\n', '\r'];
+
+        if let Some(c) = cmd.chars().find(|c| SHELL_METACHARACTERS.contains(c)) {
+            return Err(anyhow!(
+                "Refusing to run '{}': contains shell metacharacter {:?}",
+                cmd.escape_debug(),
+                c
+            ));
+        }
+        Ok(cmd)
     }
 
     pub async fn is_man_available(cmd: &str) -> bool {
@@ -111,6 +370,47 @@ mod tests {
         let input = "hello\t\tworld";
         let output = IoHandler::normalize_text(input);
         assert!(!output.contains('\t'));
+        assert!(matches!(output, Cow::Owned(_)));
+
+        assert_eq!(IoHandler::normalize_text("--verbose\r\n"), "--verbose\n");
+
+        let clean = "hello world\nsecond line";
+        assert!(matches!(IoHandler::normalize_text(clean), Cow::Borrowed(s) if s == clean));
+
+        assert_eq!(IoHandler::normalize_text_with_tab_width("a\tb", 1), "a b");
+        assert_eq!(
+            IoHandler::normalize_text_with_tab_width("a\tb", 4),
+            "a    b"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_command_name() {
+        for safe in [
+            "ls",
+            "git log",
+            "docker-compose",
+            "python3.12",
+            "./bin/tool",
+        ] {
+            assert_eq!(IoHandler::sanitize_command_name(safe).unwrap(), safe);
+        }
+        for malicious in [
+            "ls;rm -rf /",
+            "ls | sh",
+            "ls && reboot",
+            "$(whoami)",
+            "`id`",
+            "ls > /etc/passwd",
+            "ls < input",
+            "ls\nreboot",
+        ] {
+            assert!(
+                IoHandler::sanitize_command_name(malicious).is_err(),
+                "{}",
+                malicious
+            );
+        }
     }
 
     #[tokio::test]
@@ -139,12 +439,127 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[tokio::test]
+    async fn test_retry_with_backoff_retries_transient_errors() {
+        let mut calls = 0;
+        let result = IoHandler::retry_with_backoff(3, 1, || {
+            calls += 1;
+            let attempt = calls;
+            async move {
+                if attempt == 1 {
+                    Err(io::Error::from(io::ErrorKind::WouldBlock))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 2);
+
+        // Permanent errors are returned immediately
+        let mut calls = 0;
+        let result: io::Result<()> = IoHandler::retry_with_backoff(3, 1, || {
+            calls += 1;
+            async { Err(io::Error::from(io::ErrorKind::NotFound)) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_read_from_command_with_retry_does_not_retry_exit_status() {
+        let res = IoHandler::read_from_command_with_retry("exit 1", 3, 1).await;
+        assert!(res.is_err());
+    }
+
     #[tokio::test]
     async fn test_get_command_help() {
         let help = IoHandler::get_command_help("echo").await.expect("get help");
         assert!(!help.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_read_from_command_with_stderr_captures_failure() {
+        let (stdout, stderr) =
+            IoHandler::read_from_command_with_stderr("echo out; echo 'bad flag' >&2; exit 3")
+                .await
+                .expect("spawn command");
+        assert_eq!(stdout.trim(), "out");
+        assert_eq!(stderr.trim(), "bad flag");
+    }
+
+    #[tokio::test]
+    async fn test_run_command_passes_env() {
+        let output = IoHandler::run_command("echo $MANWIDTH", &[("MANWIDTH", "200")], 1, 1)
+            .await
+            .expect("run command");
+        assert_eq!(output.trim(), "200");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_get_command_version() {
+        use std::io::Write;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().expect("create temp dir");
+        let script = dir.path().join("mycmd");
+        let mut file = std::fs::File::create(&script).expect("create script");
+        writeln!(
+            file,
+            "#!/bin/sh\necho\necho \"mycmd 1.2.3\"\necho \"extra\""
+        )
+        .unwrap();
+        drop(file);
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let version = IoHandler::get_command_version(script.to_str().unwrap())
+            .await
+            .expect("get version");
+        assert_eq!(version.as_str(), "mycmd 1.2.3");
+
+        assert_eq!(IoHandler::first_non_empty_line("\n  \n").as_str(), "");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_get_command_help_sets_columns() {
+        use std::io::Write;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().expect("create temp dir");
+        let script = dir.path().join("mycmd");
+        let mut file = std::fs::File::create(&script).expect("create script");
+        writeln!(file, "#!/bin/sh\nenv | grep '^COLUMNS='").unwrap();
+        drop(file);
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let script = script.to_str().unwrap();
+
+        let help = IoHandler::get_command_help(script).await.expect("get help");
+        assert_eq!(help.trim(), "COLUMNS=200");
+
+        let help = IoHandler::get_command_help_with_columns(script, 120, 1)
+            .await
+            .expect("get help");
+        assert_eq!(help.trim(), "COLUMNS=120");
+
+        let (stdout, _) = IoHandler::get_command_help_with_stderr(script, 90, 1)
+            .await
+            .expect("get help");
+        assert_eq!(stdout.trim(), "COLUMNS=90");
+    }
+
+    #[tokio::test]
+    async fn test_get_command_help_with_stderr_fails_on_exit_status() {
+        let err = IoHandler::get_command_help_with_stderr("echo 'no such tool' >&2; false", 80, 1)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("Command failed:"), "{}", err);
+        assert!(err.ends_with("no such tool"), "{}", err);
+    }
+
     #[tokio::test]
     async fn test_is_man_available() {
         let _man_available = IoHandler::is_man_available("echo").await;
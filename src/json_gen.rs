@@ -1,6 +1,36 @@
-use crate::types::Command;
-use ecow::EcoString;
-use serde_json::json;
+use crate::types::{Command, Opt, OptName};
+use anyhow::{Context, Result, bail};
+use ecow::{EcoString, EcoVec};
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the envelope written by [`JsonGenerator::generate_with_metadata`].
+pub const METADATA_SCHEMA_VERSION: &str = "1";
+
+const COMMAND_FIELDS: &[&str] = &[
+    "name",
+    "description",
+    "usage",
+    "options",
+    "subcommands",
+    "version",
+    "accepts_options",
+    "exclusive_groups",
+    "option_groups",
+    "usage_alternatives",
+];
+const OPTION_GROUP_FIELDS: &[&str] = &["name", "options"];
+const OPT_FIELDS: &[&str] = &[
+    "names",
+    "argument",
+    "description",
+    "possible_values",
+    "argument_optional",
+    "argument_required",
+    "env_var",
+];
+const OPT_NAME_FIELDS: &[&str] = &["raw", "type"];
 
 pub struct JsonGenerator;
 
@@ -10,20 +40,213 @@ impl JsonGenerator {
         EcoString::from(serde_json::to_string_pretty(&json).unwrap_or_default())
     }
 
+    /// Generate JSON wrapping the command in an envelope recording the schema
+    /// version, the generation time (RFC 3339, UTC) and the input `source`.
+    pub fn generate_with_metadata(cmd: &Command, source: &str) -> EcoString {
+        let json = json!({
+            "schema_version": METADATA_SCHEMA_VERSION,
+            "generated_at": Self::format_timestamp(SystemTime::now()),
+            "source": source,
+            "command": Self::command_to_json(cmd),
+        });
+        EcoString::from(serde_json::to_string_pretty(&json).unwrap_or_default())
+    }
+
+    /// Format `time` as `YYYY-MM-DDTHH:MM:SSZ`.
+    fn format_timestamp(time: SystemTime) -> String {
+        let secs = time
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let (days, rem) = (secs / 86_400, secs % 86_400);
+
+        // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+        let z = days + 719_468;
+        let era = z / 146_097;
+        let doe = z % 146_097;
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + u64::from(month <= 2);
+
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            rem / 3_600,
+            rem % 3_600 / 60,
+            rem % 60
+        )
+    }
+
+    /// Generate JSON with object keys and each option's names sorted alphabetically.
+    pub fn generate_sorted(cmd: &Command) -> EcoString {
+        let mut json = Self::command_to_json(cmd);
+        Self::sort_option_names(&mut json);
+        let json = Self::sort_keys(json);
+        EcoString::from(serde_json::to_string_pretty(&json).unwrap_or_default())
+    }
+
+    /// Sort the names of every option, both in `"options"` and in the copies
+    /// under `"option_groups"`.
+    fn sort_option_names(json: &mut Value) {
+        let sort = |opts: &mut Value| {
+            for opt in opts.as_array_mut().into_iter().flatten() {
+                if let Some(names) = opt.get_mut("names").and_then(Value::as_array_mut) {
+                    names.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+                }
+            }
+        };
+
+        if let Some(opts) = json.get_mut("options") {
+            sort(opts);
+        }
+        if let Some(groups) = json.get_mut("option_groups").and_then(Value::as_array_mut) {
+            for group in groups.iter_mut() {
+                if let Some(opts) = group.get_mut("options") {
+                    sort(opts);
+                }
+            }
+        }
+    }
+
+    /// Recursively rebuild objects through a `BTreeMap` so key order does not
+    /// depend on whether serde_json's `preserve_order` feature is enabled.
+    fn sort_keys(value: Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let sorted: BTreeMap<String, Value> = map
+                    .into_iter()
+                    .map(|(k, v)| (k, Self::sort_keys(v)))
+                    .collect();
+                Value::Object(sorted.into_iter().collect::<Map<_, _>>())
+            }
+            Value::Array(items) => Value::Array(items.into_iter().map(Self::sort_keys).collect()),
+            other => other,
+        }
+    }
+
+    /// Generate newline-delimited JSON: a metadata line followed by one line per option.
+    pub fn generate_ndjson(cmd: &Command) -> EcoString {
+        let mut meta = json!({
+            "name": cmd.name.as_str(),
+            "description": cmd.description.as_str(),
+            "usage": cmd.usage.as_str(),
+        });
+        if !cmd.version.is_empty() {
+            meta["version"] = json!(cmd.version.as_str());
+        }
+
+        let mut buf = meta.to_string();
+        for opt in cmd.options.iter() {
+            buf.push('\n');
+            buf.push_str(&Self::opt_to_json(opt).to_string());
+        }
+
+        EcoString::from(buf)
+    }
+
+    /// Parse a `Command` from JSON, ignoring unknown fields.
+    pub fn parse(s: &str) -> Result<Command> {
+        serde_json::from_str(s).context("Failed to parse Command JSON")
+    }
+
+    /// Parse a `Command` from JSON, rejecting any field `Command`, `Opt` or
+    /// `OptName` does not know about (e.g. a misspelled `"optins"`).
+    pub fn parse_strict(s: &str) -> Result<Command> {
+        let value: Value = serde_json::from_str(s).context("Failed to parse Command JSON")?;
+        Self::check_command_fields(&value, "$")?;
+        serde_json::from_value(value).context("Failed to parse Command JSON")
+    }
+
+    fn check_command_fields(value: &Value, path: &str) -> Result<()> {
+        Self::check_fields(value, COMMAND_FIELDS, path)?;
+        Self::check_options_fields(value, path)?;
+
+        if let Some(groups) = value.get("option_groups").and_then(Value::as_array) {
+            for (i, group) in groups.iter().enumerate() {
+                let group_path = format!("{}.option_groups[{}]", path, i);
+                Self::check_fields(group, OPTION_GROUP_FIELDS, &group_path)?;
+                Self::check_options_fields(group, &group_path)?;
+            }
+        }
+
+        if let Some(subs) = value.get("subcommands").and_then(Value::as_array) {
+            for (i, sub) in subs.iter().enumerate() {
+                Self::check_command_fields(sub, &format!("{}.subcommands[{}]", path, i))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_options_fields(value: &Value, path: &str) -> Result<()> {
+        if let Some(opts) = value.get("options").and_then(Value::as_array) {
+            for (i, opt) in opts.iter().enumerate() {
+                let opt_path = format!("{}.options[{}]", path, i);
+                Self::check_fields(opt, OPT_FIELDS, &opt_path)?;
+
+                if let Some(names) = opt.get("names").and_then(Value::as_array) {
+                    for (j, name) in names.iter().enumerate() {
+                        Self::check_fields(
+                            name,
+                            OPT_NAME_FIELDS,
+                            &format!("{}.names[{}]", opt_path, j),
+                        )?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_fields(value: &Value, known: &[&str], path: &str) -> Result<()> {
+        if let Some(key) = value
+            .as_object()
+            .and_then(|obj| obj.keys().find(|k| !known.contains(&k.as_str())))
+        {
+            bail!("unknown field `{}` at {}", key, path);
+        }
+        Ok(())
+    }
+
     fn command_to_json(cmd: &Command) -> serde_json::Value {
         let mut obj = json!({
             "name": cmd.name.as_str(),
             "description": cmd.description.as_str(),
             "usage": cmd.usage.as_str(),
-            "options": cmd.options.iter().map(|opt| {
-                json!({
-                    "names": opt.names.iter().map(|n| n.raw.as_str()).collect::<Vec<_>>(),
-                    "argument": opt.argument.as_str(),
-                    "description": opt.description.as_str(),
-                })
-            }).collect::<Vec<_>>(),
+            "options": Self::flat_options(cmd).map(Self::opt_to_json).collect::<Vec<_>>(),
         });
 
+        if !cmd.option_groups.is_empty() {
+            obj["option_groups"] = json!(
+                cmd.option_groups
+                    .iter()
+                    .map(|group| {
+                        json!({
+                            "name": group.name.as_str(),
+                            "options": group.options.iter().map(Self::opt_to_json).collect::<Vec<_>>(),
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        if !cmd.accepts_options {
+            obj["accepts_options"] = json!(false);
+        }
+
+        if !cmd.exclusive_groups.is_empty() {
+            obj["exclusive_groups"] = json!(
+                cmd.exclusive_groups
+                    .iter()
+                    .map(|group| group.iter().map(EcoString::as_str).collect::<Vec<_>>())
+                    .collect::<Vec<_>>()
+            );
+        }
+
         if !cmd.subcommands.is_empty() {
             obj["subcommands"] = serde_json::json!(
                 cmd.subcommands
@@ -42,6 +265,54 @@ impl JsonGenerator {
             obj["version"] = json!(cmd.version.as_str());
         }
 
+        if !cmd.usage_alternatives.is_empty() {
+            obj["usage_alternatives"] = json!(
+                cmd.usage_alternatives
+                    .iter()
+                    .map(EcoString::as_str)
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        obj
+    }
+
+    /// `cmd.options` followed by any grouped option not already among them, so
+    /// `"options"` stays the flat union when `"option_groups"` is present.
+    fn flat_options(cmd: &Command) -> impl Iterator<Item = &Opt> {
+        let mut seen: Vec<&EcoVec<OptName>> = cmd.options.iter().map(|opt| &opt.names).collect();
+        let grouped = cmd
+            .option_groups
+            .iter()
+            .flat_map(|group| group.options.iter())
+            .filter(move |opt| {
+                if seen.contains(&&opt.names) {
+                    false
+                } else {
+                    seen.push(&opt.names);
+                    true
+                }
+            });
+
+        cmd.options.iter().chain(grouped)
+    }
+
+    fn opt_to_json(opt: &Opt) -> serde_json::Value {
+        let mut obj = json!({
+            "names": opt.names.iter().map(|n| n.raw.as_str()).collect::<Vec<_>>(),
+            "argument": opt.argument.as_str(),
+            "description": opt.description.as_str(),
+            "possible_values": opt.possible_values.iter().map(EcoString::as_str).collect::<Vec<_>>(),
+        });
+        if opt.argument_optional {
+            obj["argument_optional"] = json!(true);
+        }
+        if opt.argument_required {
+            obj["argument_required"] = json!(true);
+        }
+        if !opt.env_var.is_empty() {
+            obj["env_var"] = json!(opt.env_var.as_str());
+        }
         obj
     }
 }
@@ -50,6 +321,7 @@ impl JsonGenerator {
 mod tests {
     use super::*;
     use ecow::{EcoString, EcoVec};
+    use std::time::Duration;
 
     #[test]
     fn test_json_generator_includes_fields() {
@@ -57,20 +329,17 @@ mod tests {
             name: EcoString::from("test"),
             description: EcoString::from("Test command"),
             usage: EcoString::from("test [OPTIONS]"),
-            options: EcoVec::new(),
             subcommands: {
                 let mut v = EcoVec::new();
                 v.push(Command {
                     name: EcoString::from("sub"),
                     description: EcoString::from("Subcommand"),
-                    usage: EcoString::new(),
-                    options: EcoVec::new(),
-                    subcommands: EcoVec::new(),
-                    version: EcoString::new(),
+                    ..Default::default()
                 });
                 v
             },
             version: EcoString::from("1.0.0"),
+            ..Default::default()
         };
 
         let json_str = JsonGenerator::generate(&cmd);
@@ -83,6 +352,25 @@ mod tests {
         assert_eq!(value["subcommands"][0]["description"], "Subcommand");
     }
 
+    #[test]
+    fn test_generate_with_metadata_wraps_command() {
+        let cmd = Command::new(EcoString::from("mycmd"));
+        let value: Value =
+            serde_json::from_str(&JsonGenerator::generate_with_metadata(&cmd, "mycmd")).unwrap();
+
+        assert_eq!(value["schema_version"], METADATA_SCHEMA_VERSION);
+        assert_eq!(value["source"], "mycmd");
+        assert_eq!(value["command"]["name"], "mycmd");
+        let generated_at = value["generated_at"].as_str().unwrap();
+        assert_eq!(generated_at.len(), "2023-11-14T22:13:20Z".len());
+        assert!(generated_at.ends_with('Z'));
+
+        let at = |secs| JsonGenerator::format_timestamp(UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(at(0), "1970-01-01T00:00:00Z");
+        assert_eq!(at(1_700_000_000), "2023-11-14T22:13:20Z");
+        assert_eq!(at(951_782_400), "2000-02-29T00:00:00Z");
+    }
+
     #[test]
     fn test_json_generator_includes_options() {
         let cmd = Command {
@@ -106,11 +394,11 @@ mod tests {
                     },
                     argument: EcoString::from("FILE"),
                     description: EcoString::from("Enable verbose mode"),
+                    ..Default::default()
                 });
                 v
             },
-            subcommands: EcoVec::new(),
-            version: EcoString::new(),
+            ..Default::default()
         };
 
         let json_str = JsonGenerator::generate(&cmd);
@@ -122,4 +410,178 @@ mod tests {
         assert_eq!(opt["argument"], "FILE");
         assert_eq!(opt["description"], "Enable verbose mode");
     }
+
+    #[test]
+    fn test_generate_sorted_orders_keys_and_names() {
+        let mut cmd = Command::new(EcoString::from("test"));
+        let mut names = EcoVec::new();
+        names.push(crate::types::OptName::new(
+            EcoString::from("-V"),
+            crate::types::OptNameType::ShortType,
+        ));
+        names.push(crate::types::OptName::new(
+            EcoString::from("--verbose"),
+            crate::types::OptNameType::LongType,
+        ));
+        cmd.options.push(crate::types::Opt {
+            names,
+            argument: EcoString::from("LEVEL"),
+            description: EcoString::from("Verbosity"),
+            ..Default::default()
+        });
+
+        let unsorted = JsonGenerator::generate(&cmd);
+        let sorted = JsonGenerator::generate_sorted(&cmd);
+        assert_ne!(unsorted, sorted);
+
+        let arg = sorted.find("\"argument\"").unwrap();
+        let desc = sorted.find("\"description\": \"Verbosity\"").unwrap();
+        let names = sorted.find("\"names\"").unwrap();
+        assert!(arg < desc && desc < names);
+
+        let value: Value = serde_json::from_str(&sorted).unwrap();
+        assert_eq!(value["options"][0]["names"], json!(["--verbose", "-V"]));
+
+        cmd.option_groups.push(crate::types::OptionGroup {
+            name: EcoString::from("Output"),
+            options: cmd.options.clone(),
+        });
+        let value: Value = serde_json::from_str(&JsonGenerator::generate_sorted(&cmd)).unwrap();
+        assert_eq!(
+            value["option_groups"][0]["options"][0]["names"],
+            json!(["--verbose", "-V"])
+        );
+    }
+
+    #[test]
+    fn test_parse_strict_rejects_unknown_fields() {
+        let typo = r#"{"name":"test","description":"","usage":"","options":[],"optins":[]}"#;
+        let err = JsonGenerator::parse_strict(typo).unwrap_err();
+        assert!(err.to_string().contains("unknown field `optins` at $"));
+
+        let nested = r#"{"name":"test","description":"","usage":"","options":[
+            {"names":[{"raw":"-v","type":"SHORTTYPE","kind":"x"}],"argument":"","description":""}
+        ]}"#;
+        let err = JsonGenerator::parse_strict(nested).unwrap_err();
+        assert!(err.to_string().contains("$.options[0].names[0]"));
+
+        let valid = r#"{"name":"test","description":"","usage":"","options":[
+            {"names":["-v"],"argument":"","description":"verbose"}
+        ]}"#;
+        assert_eq!(JsonGenerator::parse_strict(valid).unwrap().options.len(), 1);
+    }
+
+    #[test]
+    fn test_option_groups_roundtrip() {
+        use crate::types::{OptNameType, OptionGroup};
+
+        let opt = |raw: &str, desc: &str| Opt {
+            names: ecow::eco_vec![OptName::new(EcoString::from(raw), OptNameType::LongType)],
+            description: EcoString::from(desc),
+            ..Default::default()
+        };
+        let mut cmd = Command::new(EcoString::from("test"));
+        cmd.options.push(opt("--verbose", "Be verbose"));
+        cmd.option_groups.push(OptionGroup {
+            name: EcoString::from("Output options"),
+            options: ecow::eco_vec![opt("--json", "Output JSON"), opt("--verbose", "Be verbose")],
+        });
+
+        let json_str = JsonGenerator::generate(&cmd);
+        let value: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(value["options"].as_array().unwrap().len(), 2);
+        assert_eq!(value["option_groups"][0]["name"], "Output options");
+
+        let parsed = JsonGenerator::parse_strict(&json_str).unwrap();
+        assert_eq!(parsed.option_groups, cmd.option_groups);
+        assert_eq!(parsed.options.len(), 2);
+    }
+
+    #[test]
+    fn test_exclusive_groups_roundtrip() {
+        let mut cmd = Command::new(EcoString::from("test"));
+        cmd.exclusive_groups.push(ecow::eco_vec![
+            EcoString::from("--json"),
+            EcoString::from("-j"),
+            EcoString::from("--yaml"),
+        ]);
+
+        let json_str = JsonGenerator::generate(&cmd);
+        let value: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(
+            value["exclusive_groups"],
+            json!([["--json", "-j", "--yaml"]])
+        );
+
+        let parsed = JsonGenerator::parse_strict(&json_str).unwrap();
+        assert_eq!(parsed.exclusive_groups, cmd.exclusive_groups);
+
+        let plain = JsonGenerator::generate(&Command::new(EcoString::from("test")));
+        assert!(!plain.contains("exclusive_groups"));
+    }
+
+    #[test]
+    fn test_accepts_options_roundtrip() {
+        let mut cmd = Command::new(EcoString::from("test"));
+        cmd.accepts_options = false;
+
+        let json_str = JsonGenerator::generate(&cmd);
+        let value: Value = serde_json::from_str(&json_str).unwrap();
+        assert_eq!(value["accepts_options"], json!(false));
+        assert!(
+            !JsonGenerator::parse_strict(&json_str)
+                .unwrap()
+                .accepts_options
+        );
+
+        let plain = JsonGenerator::generate(&Command::new(EcoString::from("test")));
+        assert!(!plain.contains("accepts_options"));
+        assert!(JsonGenerator::parse_strict(&plain).unwrap().accepts_options);
+    }
+
+    #[cfg(not(feature = "strict"))]
+    #[test]
+    fn test_parse_ignores_unknown_fields() {
+        let typo = r#"{"name":"test","description":"","usage":"","options":[],"optins":[]}"#;
+        let cmd = JsonGenerator::parse(typo).unwrap();
+        assert!(cmd.options.is_empty());
+    }
+
+    #[cfg(feature = "strict")]
+    #[test]
+    fn test_parse_rejects_unknown_fields_with_strict_feature() {
+        let typo = r#"{"name":"test","description":"","usage":"","options":[],"optins":[]}"#;
+        assert!(JsonGenerator::parse(typo).is_err());
+    }
+
+    #[test]
+    fn test_generate_ndjson_emits_one_object_per_line() {
+        let mut cmd = Command::new(EcoString::from("test"));
+        cmd.version = EcoString::from("1.0.0");
+        for name in ["-a", "-b"] {
+            let mut names = EcoVec::new();
+            names.push(crate::types::OptName::new(
+                EcoString::from(name),
+                crate::types::OptNameType::ShortType,
+            ));
+            cmd.options.push(crate::types::Opt {
+                names,
+                description: EcoString::from("An option"),
+                ..Default::default()
+            });
+        }
+
+        let ndjson = JsonGenerator::generate_ndjson(&cmd);
+        let lines: Vec<serde_json::Value> = ndjson
+            .split('\n')
+            .map(|line| serde_json::from_str(line).expect("valid json line"))
+            .collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["name"], "test");
+        assert_eq!(lines[0]["version"], "1.0.0");
+        assert_eq!(lines[1]["names"], serde_json::json!(["-a"]));
+        assert_eq!(lines[2]["names"], serde_json::json!(["-b"]));
+        assert_eq!(lines[2]["description"], "An option");
+    }
 }
@@ -1,62 +1,226 @@
 use crate::parser::Parser;
-use crate::types::Opt;
+use crate::types::{Opt, OptionGroup};
 use bstr::ByteSlice;
 use ecow::{EcoString, EcoVec};
-use memchr::memchr;
+use memchr::{memchr, memrchr};
 use rayon::prelude::*;
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::LazyLock;
+use tracing::{debug, instrument, warn};
+
+/// Default upper bound, in bytes, on the size of a single option block.
+pub const MAX_BLOCK_SIZE: usize = 4096;
+
+/// Options controlling how [`Layout::parse_blockwise_with_config`] splits content.
+#[derive(Debug, Clone, Copy)]
+pub struct LayoutConfig {
+    /// Blocks larger than this many bytes are truncated at a line boundary.
+    pub max_block_size: usize,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            max_block_size: MAX_BLOCK_SIZE,
+        }
+    }
+}
+
+/// Help sections whose dash-prefixed tokens are not option definitions.
+pub const KNOWN_NON_OPTION_SECTIONS: &[&str] = &["EXAMPLES", "SEE ALSO", "FILES", "BUGS", "NOTES"];
 
 pub struct Layout;
 
 impl Layout {
     /// Parse content into options, processing blocks in parallel.
     pub fn parse_blockwise(content: &str) -> EcoVec<Opt> {
-        let blocks = Self::split_into_blocks_fast(content);
+        Self::parse_blockwise_with_config(content, &LayoutConfig::default())
+    }
 
-        // Use parallel iterator for processing multiple blocks
-        // Only parallelize if we have enough blocks to benefit
-        if blocks.len() > 4 {
-            blocks
-                .par_iter()
-                .flat_map(|block| {
-                    let opts = Parser::parse_line(block);
-                    opts.into_iter().collect::<Vec<_>>()
-                })
-                .collect::<Vec<_>>()
-                .into_iter()
-                .collect()
+    /// Parse content into options, ignoring every section whose header matches
+    /// one of `skip` (case-insensitively) up to the next section header.
+    ///
+    /// A section header is an unindented line in all caps or title case, with an
+    /// optional trailing `:` (e.g. `EXAMPLES`, `See Also:`).
+    pub fn parse_blockwise_skip_sections(content: &str, skip: &[&str]) -> EcoVec<Opt> {
+        Self::parse_blockwise(&Self::blank_sections(content, skip))
+    }
+
+    /// Like [`Layout::parse_blockwise_skip_sections`], splitting descriptions at
+    /// `desc_col` as [`Layout::parse_blockwise_with_column`] does.
+    pub fn parse_blockwise_skip_sections_with_column(
+        content: &str,
+        skip: &[&str],
+        desc_col: Option<usize>,
+    ) -> EcoVec<Opt> {
+        Self::parse_blockwise_with_column(&Self::blank_sections(content, skip), desc_col)
+    }
+
+    /// Blank out every line of the sections named in `skip`, borrowing the input
+    /// when there is nothing to skip.
+    fn blank_sections<'a>(content: &'a str, skip: &[&str]) -> Cow<'a, str> {
+        let mut kept = String::with_capacity(content.len());
+        let mut skipping = false;
+        let mut skipped_any = false;
+
+        for line in content.as_bytes().lines() {
+            let Ok(line) = std::str::from_utf8(line) else {
+                continue;
+            };
+
+            if let Some(header) = Self::section_header(line) {
+                skipping = skip.iter().any(|name| name.eq_ignore_ascii_case(header));
+            }
+
+            if skipping {
+                skipped_any = true;
+                // Keep the line count so block boundaries stay where they were
+                kept.push('\n');
+            } else {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+        }
+
+        if skipped_any {
+            Cow::Owned(kept)
         } else {
-            blocks
-                .iter()
-                .flat_map(|block| Parser::parse_line(block).into_iter())
-                .collect()
+            Cow::Borrowed(content)
         }
     }
 
+    /// Group `options` by the help section they are listed under, skipping
+    /// sections named in `skip`. Options before the first header belong to no
+    /// group.
+    ///
+    /// Besides [`Layout::section_header`] lines, any unindented line ending in
+    /// `:` starts a section, so `Output options:` is a heading too.
+    pub fn parse_option_groups(
+        content: &str,
+        options: &[Opt],
+        skip: &[&str],
+    ) -> EcoVec<OptionGroup> {
+        let mut sections: Vec<(&str, String)> = Vec::new();
+
+        for line in content.as_bytes().lines() {
+            let Ok(line) = std::str::from_utf8(line) else {
+                continue;
+            };
+
+            if let Some(header) = Self::group_header(line) {
+                sections.push((header, String::new()));
+            } else if let Some((_, body)) = sections.last_mut() {
+                body.push_str(line);
+                body.push('\n');
+            }
+        }
+
+        sections
+            .into_iter()
+            .filter(|(name, _)| !skip.iter().any(|skip| skip.eq_ignore_ascii_case(name)))
+            .filter_map(|(name, body)| {
+                let parsed = Self::parse_blockwise(&body);
+                let grouped: EcoVec<Opt> = options
+                    .iter()
+                    .filter(|opt| parsed.iter().any(|p| p.names == opt.names))
+                    .cloned()
+                    .collect();
+                (!grouped.is_empty()).then(|| OptionGroup {
+                    name: EcoString::from(name),
+                    options: grouped,
+                })
+            })
+            .collect()
+    }
+
+    /// A [`Layout::section_header`], or an unindented `Name:` line.
+    fn group_header(line: &str) -> Option<&str> {
+        if let Some(header) = Self::section_header(line) {
+            return Some(header);
+        }
+        if line.starts_with(char::is_whitespace) || line.starts_with('-') {
+            return None;
+        }
+
+        let header = line.trim_end().strip_suffix(':')?;
+        let plain = header
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '/' | '_'));
+        (plain && header.starts_with(char::is_alphabetic)).then_some(header)
+    }
+
+    /// The header name of a section header line, without a trailing `:`.
+    fn section_header(line: &str) -> Option<&str> {
+        if line.starts_with(char::is_whitespace) {
+            return None;
+        }
+
+        let header = line.trim_end();
+        let header = header.strip_suffix(':').unwrap_or(header);
+        let mut words = header.split(' ').peekable();
+        words.peek()?;
+
+        let all_caps =
+            header.chars().any(char::is_alphabetic) && !header.chars().any(char::is_lowercase);
+        let title_case = words.all(|word| word.chars().next().is_some_and(char::is_uppercase));
+        let plain = header
+            .chars()
+            .all(|c| c.is_alphabetic() || matches!(c, ' ' | '-' | '/'));
+
+        (plain && (all_caps || title_case)).then_some(header)
+    }
+
+    /// Parse content into options using the limits in `config`.
+    #[instrument(level = "debug", skip_all, fields(len = content.len()))]
+    pub fn parse_blockwise_with_config(content: &str, config: &LayoutConfig) -> EcoVec<Opt> {
+        let blocks = Self::split_into_blocks_bounded(content, config.max_block_size);
+        debug!("parsing {} blocks", blocks.len());
+
+        Self::flat_map_blocks(&blocks, Parser::parse_line)
+    }
+
+    /// Parse content into options, splitting descriptions at a fixed column.
+    ///
+    /// When `desc_col` is `None` the column is detected from the content, falling
+    /// back to the heuristic split used by [`Layout::parse_blockwise`].
+    pub fn parse_blockwise_with_column(content: &str, desc_col: Option<usize>) -> EcoVec<Opt> {
+        let desc_col = desc_col.or_else(|| Self::get_description_offset(content));
+        Self::flat_map_blocks(&Self::split_into_blocks_fast(content), |block| {
+            Parser::parse_line_with_column(block, desc_col)
+        })
+    }
+
     /// Preprocess content into option/description pairs, processing blocks in parallel.
     pub fn preprocess_blockwise(content: &str) -> EcoVec<(EcoString, EcoString)> {
-        let blocks = Self::split_into_blocks_fast(content);
+        Self::flat_map_blocks(&Self::split_into_blocks_fast(content), Parser::preprocess)
+    }
 
-        // Only parallelize if we have enough blocks
-        if blocks.len() > 4 {
+    /// Apply `f` to every block and concatenate the results in block order,
+    /// spreading the work across the Rayon pool when there are enough blocks.
+    fn flat_map_blocks<T, F>(blocks: &[EcoString], f: F) -> EcoVec<T>
+    where
+        T: Clone + Send,
+        F: Fn(&str) -> EcoVec<T> + Sync,
+    {
+        // Order guarantee: `par_iter().flat_map().collect::<Vec<_>>()` is an indexed
+        // collect, so results come out in block order exactly as in the sequential
+        // branch. Do not replace it with an unordered reduction (e.g. `fold`/`reduce`
+        // into sets) - generators and dedup rely on help-text order.
+        if blocks.len() > Self::parallel_threshold() {
             blocks
                 .par_iter()
-                .flat_map(|block| {
-                    let pairs = Parser::preprocess(block);
-                    pairs.into_iter().collect::<Vec<_>>()
-                })
+                .flat_map(|block| f(block).into_iter().collect::<Vec<_>>())
                 .collect::<Vec<_>>()
                 .into_iter()
                 .collect()
         } else {
-            blocks
-                .iter()
-                .flat_map(|block| Parser::preprocess(block).into_iter())
-                .collect()
+            blocks.iter().flat_map(|block| f(block)).collect()
         }
     }
 
     pub fn parse_usage(content: &str) -> EcoString {
-        let keywords = ["usage", "synopsis"];
+        const KEYWORDS: [&str; 2] = ["usage", "synopsis"];
         let bytes = content.as_bytes();
 
         // SIMD fast scan for 'u' or 's' (first chars of keywords)
@@ -67,24 +231,6 @@ impl Layout {
             }
         }
 
-        // Fast scan for keywords first
-        let lower = content.to_lowercase();
-        let mut keyword_pos = None;
-        for keyword in &keywords {
-            if let Some(pos) = lower.find(keyword) {
-                // Check if followed by ':'
-                let rest = &lower[pos..];
-                if rest.contains(':') {
-                    keyword_pos = Some(pos);
-                    break;
-                }
-            }
-        }
-
-        if keyword_pos.is_none() {
-            return EcoString::new();
-        }
-
         // Use bstr for SIMD-accelerated line iteration
         let lines: Vec<&str> = bytes
             .lines()
@@ -92,20 +238,22 @@ impl Layout {
             .collect();
 
         for (i, line) in lines.iter().enumerate() {
-            let lower = line.to_lowercase();
-            if keywords.iter().any(|k| lower.contains(k)) && lower.contains(':') {
+            // The header is everything up to the first ':' ("Usage: foo [OPTIONS]"),
+            // or the whole line for a bare "SYNOPSIS" heading
+            let header = memchr(b':', line.as_bytes()).map_or(*line, |pos| &line[..=pos]);
+            if Parser::parse_usage_header(&KEYWORDS, header).is_some() {
                 let mut usage_result = String::with_capacity(256);
-                let mut first = true;
+                usage_result.push_str(line);
 
-                for l in lines[i..].iter() {
-                    if (l.is_empty() || (!l.starts_with(' ') && !l.contains(':'))) && !first {
+                // Continuation lines (alternative forms, wrapped arguments) must be
+                // indented deeper than the usage header itself
+                let min_indent = Self::indent_width(line) + 1;
+                for l in lines[i + 1..].iter() {
+                    if l.trim().is_empty() || Self::indent_width(l) < min_indent {
                         break;
                     }
-                    if !first {
-                        usage_result.push('\n');
-                    }
+                    usage_result.push('\n');
                     usage_result.push_str(l);
-                    first = false;
                 }
 
                 if !usage_result.is_empty() {
@@ -117,19 +265,135 @@ impl Layout {
         EcoString::new()
     }
 
+    /// Split a usage string from [`Self::parse_usage`] listing alternative
+    /// invocations separated by `|`, such as `Usage: mycmd -a FILE | mycmd -b DIR`,
+    /// into one string per form.
+    ///
+    /// Empty unless there are at least two forms and each starts with the
+    /// program name, so `mycmd [-a|-b]` and `mycmd FILE | less` are left alone.
+    pub fn parse_usage_alternatives(usage: &str) -> EcoVec<EcoString> {
+        let mut lines = usage.lines();
+        let first = lines
+            .next()
+            .and_then(|line| line.split_once(':'))
+            .map_or("", |(_, rest)| rest);
+        let body = std::iter::once(first)
+            .chain(lines)
+            .flat_map(str::split_whitespace)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        // Split on `|` outside of brackets
+        let mut forms = Vec::new();
+        let mut depth = 0usize;
+        let mut start = 0;
+        for (i, b) in body.bytes().enumerate() {
+            match b {
+                b'[' | b'(' | b'{' | b'<' => depth += 1,
+                b']' | b')' | b'}' | b'>' => depth = depth.saturating_sub(1),
+                b'|' if depth == 0 => {
+                    forms.push(body[start..i].trim());
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        forms.push(body[start..].trim());
+
+        let program = forms[0].split_whitespace().next();
+        if forms.len() < 2
+            || program.is_none()
+            || forms
+                .iter()
+                .any(|form| form.split_whitespace().next() != program)
+        {
+            return EcoVec::new();
+        }
+
+        forms.into_iter().map(EcoString::from).collect()
+    }
+
+    /// Extract a version number such as `1.2.3`, `v2.0` or `version 4.1-rc1`
+    /// from help or description text.
+    pub fn parse_version(content: &str) -> Option<EcoString> {
+        static VERSION_RE: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"(?i)(?:^|[\s(])(?:version\s+|v)?(\d+\.\d+(?:\.\d+)*(?:-[0-9A-Za-z.]+)?)\b")
+                .unwrap()
+        });
+
+        // SIMD fast scan: every version contains a '.'
+        memchr(b'.', content.as_bytes())?;
+
+        VERSION_RE
+            .captures(content)
+            .map(|caps| EcoString::from(&caps[1]))
+    }
+
+    #[inline]
+    fn indent_width(line: &str) -> usize {
+        line.len() - line.trim_start().len()
+    }
+
+    /// Minimum number of blocks before parsing is spread across the Rayon pool.
+    ///
+    /// Scales with the number of available cores (twice the core count, capped
+    /// at 32); on a single-core machine parallelism is never used.
+    #[cfg(not(test))]
+    fn parallel_threshold() -> usize {
+        static THRESHOLD: LazyLock<usize> =
+            LazyLock::new(
+                || match std::thread::available_parallelism().map_or(1, |n| n.get()) {
+                    1 => usize::MAX,
+                    cpus => (cpus * 2).min(32),
+                },
+            );
+        *THRESHOLD
+    }
+
+    /// Pinned threshold so tests exercise the same code paths on every machine.
+    #[cfg(test)]
+    fn parallel_threshold() -> usize {
+        4
+    }
+
     /// Optimized block splitting that minimizes allocations
     /// Uses bstr for SIMD-accelerated line iteration
     fn split_into_blocks_fast(content: &str) -> EcoVec<EcoString> {
-        let bytes = content.as_bytes();
+        Self::split_into_blocks_bounded(content, MAX_BLOCK_SIZE)
+    }
 
-        // SIMD fast path: check if '-' exists at all
-        if memchr(b'-', bytes).is_none() {
-            return EcoVec::new();
+    fn split_into_blocks_bounded(content: &str, max_block_size: usize) -> EcoVec<EcoString> {
+        // SIMD fast path: check if '-' exists at all, and skip the lines before it
+        match memchr(b'-', content.as_bytes()) {
+            Some(pos) => Self::split_blocks(content, pos, max_block_size),
+            None => EcoVec::new(),
         }
+    }
+
+    /// Split `content` into option blocks, starting at the line containing `start_offset`.
+    ///
+    /// Lines before that line are skipped, so the caller must ensure they
+    /// cannot start a block (i.e. contain no `-`).
+    pub fn split_into_blocks_from_offset(content: &str, start_offset: usize) -> EcoVec<EcoString> {
+        Self::split_blocks(content, start_offset, MAX_BLOCK_SIZE)
+    }
+
+    fn split_blocks(
+        content: &str,
+        start_offset: usize,
+        max_block_size: usize,
+    ) -> EcoVec<EcoString> {
+        let bytes = content.as_bytes();
+        let start_offset = start_offset.min(bytes.len());
+        let line_start = memrchr(b'\n', &bytes[..start_offset]).map_or(0, |p| p + 1);
+        let bytes = &bytes[line_start..];
 
         let mut blocks = EcoVec::new();
         let mut current_block = String::with_capacity(256);
         let mut in_block = false;
+        let mut truncated = false;
+        // Inside the description of a bare `--` end-of-options marker
+        let mut in_marker = false;
 
         // Use bstr for SIMD-accelerated line iteration
         for line in bytes.lines() {
@@ -137,28 +401,94 @@ impl Layout {
             let line_str = unsafe { std::str::from_utf8_unchecked(line) };
             let trimmed = line_str.trim_start();
 
-            if trimmed.is_empty() {
+            if Self::is_end_of_options_marker(trimmed) {
+                // Fast path: `--` ends option parsing, it is not an option itself
+                if !current_block.is_empty() {
+                    Self::warn_if_truncated(truncated, &current_block, max_block_size);
+                    blocks.push(EcoString::from(current_block.as_str()));
+                    current_block.clear();
+                }
+                in_block = false;
+                truncated = false;
+                in_marker = true;
+            } else if in_marker && !trimmed.is_empty() && !trimmed.starts_with('-') {
+                // Skip the marker's description
+            } else if trimmed.is_empty() {
+                in_marker = false;
                 if in_block && !current_block.is_empty() {
+                    Self::warn_if_truncated(truncated, &current_block, max_block_size);
                     blocks.push(EcoString::from(current_block.as_str()));
                     current_block.clear();
                     in_block = false;
+                    truncated = false;
                 }
             } else if trimmed.starts_with('-') || in_block {
-                if !current_block.is_empty() {
+                in_marker = false;
+                in_block = true;
+
+                // The cap bounds a single option's entry: a new option line that
+                // would overflow the block starts the next block instead
+                if trimmed.starts_with('-')
+                    && !current_block.is_empty()
+                    && (truncated || current_block.len() + 1 + line_str.len() > max_block_size)
+                {
+                    Self::warn_if_truncated(truncated, &current_block, max_block_size);
+                    blocks.push(EcoString::from(current_block.as_str()));
+                    current_block.clear();
+                    truncated = false;
+                }
+                if truncated {
+                    continue;
+                }
+
+                let sep = usize::from(!current_block.is_empty());
+                if current_block.len() + sep + line_str.len() > max_block_size {
+                    truncated = true;
+                    if current_block.is_empty() {
+                        // A single oversized line: keep as much of it as fits
+                        let mut end = max_block_size.min(line_str.len());
+                        while !line_str.is_char_boundary(end) {
+                            end -= 1;
+                        }
+                        current_block.push_str(&line_str[..end]);
+                    }
+                    continue;
+                }
+
+                if sep == 1 {
                     current_block.push('\n');
                 }
                 current_block.push_str(line_str);
-                in_block = true;
             }
         }
 
         if !current_block.is_empty() {
+            Self::warn_if_truncated(truncated, &current_block, max_block_size);
             blocks.push(EcoString::from(current_block));
         }
 
         blocks
     }
 
+    /// A line whose first token is a bare `--`, alone (`--`) or followed by
+    /// its description on the same line (`-- end of flags`).
+    #[inline]
+    fn is_end_of_options_marker(trimmed: &str) -> bool {
+        trimmed
+            .strip_prefix("--")
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+    }
+
+    fn warn_if_truncated(truncated: bool, block: &str, max_block_size: usize) {
+        if truncated {
+            let first_line = block.lines().next().unwrap_or_default().trim();
+            warn!(
+                "Option block starting with '{}' exceeds {} bytes, truncating",
+                first_line, max_block_size
+            );
+        }
+    }
+
     pub fn get_option_offsets(s: &str) -> EcoVec<usize> {
         let short_offset = Self::get_short_option_offset(s);
         let long_offset = Self::get_long_option_offset(s);
@@ -180,6 +510,41 @@ impl Layout {
         result
     }
 
+    /// Detect the most common column at which option descriptions start.
+    ///
+    /// Only lines starting at one of the [`Layout::get_option_offsets`] are
+    /// considered, and the description must be separated by at least two spaces.
+    pub fn get_description_offset(s: &str) -> Option<usize> {
+        let offsets = Self::get_option_offsets(s);
+        if offsets.is_empty() {
+            return None;
+        }
+
+        let locations: EcoVec<(usize, usize)> = s
+            .as_bytes()
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let line_str = std::str::from_utf8(line).ok()?;
+                let trimmed = line_str.trim_start();
+                let offset = line_str.len() - trimmed.len();
+                if !trimmed.starts_with('-') || !offsets.contains(&offset) {
+                    return None;
+                }
+
+                let gap = trimmed.find("  ")?;
+                let rest = &trimmed[gap..];
+                let desc = rest.trim_start();
+                if desc.is_empty() {
+                    return None;
+                }
+                Some((i, offset + gap + rest.len() - desc.len()))
+            })
+            .collect();
+
+        Self::get_most_frequent_offset(&locations)
+    }
+
     fn get_option_locations(s: &str, predicate: fn(&str) -> bool) -> EcoVec<(usize, usize)> {
         let bytes = s.as_bytes();
 
@@ -211,6 +576,8 @@ impl Layout {
         Self::get_most_frequent_offset(&locations)
     }
 
+    /// The offset shared by the most locations. Ties go to the smallest
+    /// offset, since left-aligned options are almost always the real ones.
     fn get_most_frequent_offset(locations: &[(usize, usize)]) -> Option<usize> {
         if locations.is_empty() {
             return None;
@@ -224,7 +591,7 @@ impl Layout {
 
         freq_map
             .into_iter()
-            .max_by_key(|(_, count)| *count)
+            .max_by_key(|&(offset, count)| (count, std::cmp::Reverse(offset)))
             .map(|(offset, _)| offset)
     }
 }
@@ -232,6 +599,252 @@ impl Layout {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+
+    /// Records the `message` field of every event it sees.
+    #[derive(Clone, Default)]
+    struct MessageRecorder(Arc<Mutex<Vec<String>>>);
+
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for MessageRecorder {
+        fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+            struct Visitor<'a>(&'a mut Vec<String>);
+            impl tracing::field::Visit for Visitor<'_> {
+                fn record_debug(
+                    &mut self,
+                    field: &tracing::field::Field,
+                    value: &dyn std::fmt::Debug,
+                ) {
+                    if field.name() == "message" {
+                        self.0.push(format!("{value:?}"));
+                    }
+                }
+            }
+            event.record(&mut Visitor(&mut self.0.lock().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_parse_blockwise_emits_block_count_event() {
+        let recorder = MessageRecorder::default();
+        let subscriber = tracing_subscriber::registry().with(recorder.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            Layout::parse_blockwise("  -a  all\n\n  -b  bee\n");
+        });
+
+        let messages = recorder.0.lock().unwrap();
+        assert!(
+            messages.iter().any(|m| m == "parsing 2 blocks"),
+            "{messages:?}"
+        );
+    }
+
+    #[test]
+    fn test_end_of_options_marker_with_inline_description() {
+        let content =
+            "  -v, --verbose   be verbose\n  -- end of flags\n  -q, --quiet     be quiet\n";
+        let blocks = Layout::split_into_blocks_fast(content);
+        assert!(!blocks.iter().any(|b| b.contains("end of flags")));
+
+        let opts = Layout::parse_blockwise(content);
+        assert!(
+            !opts
+                .iter()
+                .any(|opt| opt.names.iter().any(|n| n.raw == "--"))
+        );
+        assert!(
+            opts.iter()
+                .any(|opt| opt.names.iter().any(|n| n.raw == "--quiet"))
+        );
+        assert!(Layout::is_end_of_options_marker("--\tend"));
+        assert!(!Layout::is_end_of_options_marker("--verbose"));
+    }
+
+    #[test]
+    fn test_end_of_options_marker_is_not_an_option() {
+        let content = "\
+  -v, --verbose
+      be verbose
+  --
+      end of options
+  -q, --quiet
+      be quiet
+";
+        let blocks = Layout::split_into_blocks_fast(content);
+        assert!(!blocks.iter().any(|b| b.contains("end of options")));
+
+        let opts = Layout::parse_blockwise(content);
+        assert!(opts.iter().all(|opt| !opt.names.is_empty()));
+        assert!(
+            !opts
+                .iter()
+                .any(|opt| opt.names.iter().any(|n| n.raw == "--"))
+        );
+        let verbose = opts
+            .iter()
+            .find(|opt| opt.names.iter().any(|n| n.raw == "--verbose"))
+            .unwrap();
+        assert_eq!(verbose.description, "be verbose");
+        assert!(
+            opts.iter()
+                .any(|opt| opt.names.iter().any(|n| n.raw == "--quiet"))
+        );
+    }
+
+    #[test]
+    fn test_split_into_blocks_from_offset_skips_leading_lines() {
+        let content = "Usage: foo [OPTIONS]\n\nOptions:\n  -a  all\n  -b  bee\n\n  -c  see\n";
+        let offset = content.find('-').unwrap();
+        let from_offset = Layout::split_into_blocks_from_offset(content, offset);
+        assert_eq!(from_offset, Layout::split_into_blocks_fast(content));
+        assert_eq!(from_offset.len(), 2);
+        assert_eq!(from_offset[0], "  -a  all\n  -b  bee");
+
+        // An offset in the middle of a line starts from that line
+        let mid = content.find("b  bee").unwrap();
+        let blocks = Layout::split_into_blocks_from_offset(content, mid);
+        assert_eq!(blocks[0], "  -b  bee");
+    }
+
+    #[test]
+    fn test_parse_blockwise_truncates_oversized_blocks() {
+        let mut content = String::from("  -a  first option\n");
+        for i in 0..400 {
+            content.push_str(&format!("      continuation line number {}\n", i));
+        }
+        content.push_str("\n  -b  second option\n");
+
+        let blocks = Layout::split_into_blocks_fast(&content);
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks[0].len() <= MAX_BLOCK_SIZE);
+        assert!(blocks[0].starts_with("  -a  first option"));
+        assert_eq!(blocks[1], "  -b  second option");
+
+        let opts = Layout::parse_blockwise(&content);
+        assert!(opts.iter().any(|o| o.names[0].raw == "-a"));
+        assert!(opts.iter().any(|o| o.names[0].raw == "-b"));
+
+        let config = LayoutConfig { max_block_size: 16 };
+        let opts =
+            Layout::parse_blockwise_with_config("  -c  a description that is long\n", &config);
+        assert_eq!(opts.len(), 1);
+        assert_eq!(opts[0].names[0].raw, "-c");
+    }
+
+    #[test]
+    fn test_parse_blockwise_keeps_dense_options_past_block_cap() {
+        let content: String = (0..300)
+            .map(|i| format!("  --option-{:<4}  description of option number {}\n", i, i))
+            .collect();
+        assert!(content.len() > MAX_BLOCK_SIZE);
+
+        let blocks = Layout::split_into_blocks_fast(&content);
+        assert!(blocks.len() > 1);
+        assert!(blocks.iter().all(|b| b.len() <= MAX_BLOCK_SIZE));
+        assert!(
+            blocks
+                .iter()
+                .all(|b| b.trim_start().starts_with("--option-"))
+        );
+
+        let opts = Layout::parse_blockwise(&content);
+        assert_eq!(opts.len(), 300);
+        assert_eq!(opts[299].names[0].raw, "--option-299");
+    }
+
+    fn ten_block_help() -> String {
+        (0..10)
+            .map(|i| {
+                format!(
+                    "  -{}, --option-{}  description {}\n",
+                    (b'a' + i) as char,
+                    i,
+                    i
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_preprocess_blockwise_parallel_preserves_order() {
+        let content = ten_block_help();
+        let blocks = Layout::split_into_blocks_fast(&content);
+        assert!(blocks.len() > Layout::parallel_threshold());
+
+        let sequential: EcoVec<(EcoString, EcoString)> = blocks
+            .iter()
+            .flat_map(|block| Parser::preprocess(block).into_iter())
+            .collect();
+        let parallel = Layout::preprocess_blockwise(&content);
+        assert_eq!(parallel, sequential);
+        assert_eq!(parallel.len(), 10);
+    }
+
+    #[test]
+    fn test_parse_blockwise_parallel_preserves_order() {
+        let content = ten_block_help();
+        let blocks = Layout::split_into_blocks_fast(&content);
+
+        let sequential: EcoVec<Opt> = blocks
+            .iter()
+            .flat_map(|block| Parser::parse_line(block).into_iter())
+            .collect();
+        for _ in 0..8 {
+            assert_eq!(Layout::parse_blockwise(&content), sequential);
+        }
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            Layout::parse_version("ripgrep 14.1.0").as_deref(),
+            Some("14.1.0")
+        );
+        assert_eq!(
+            Layout::parse_version("A tool (version 2.0-rc1)").as_deref(),
+            Some("2.0-rc1")
+        );
+        assert_eq!(Layout::parse_version("mytool v3.4").as_deref(), Some("3.4"));
+        assert_eq!(Layout::parse_version("No version here."), None);
+    }
+
+    #[test]
+    fn test_parse_blockwise_skip_sections() {
+        let content = "OPTIONS:\n  -v, --verbose\n      Be verbose\n\nEXAMPLES\n  -x, --example\n      Not an option\n\nSee Also:\n  -s, --see\n      Nor this\n\nMore Options\n  -q, --quiet\n      Be quiet\n";
+
+        let all = Layout::parse_blockwise(content);
+        assert_eq!(all.len(), 4);
+
+        let opts = Layout::parse_blockwise_skip_sections(content, KNOWN_NON_OPTION_SECTIONS);
+        let names: Vec<&str> = opts.iter().map(|o| o.names[0].raw.as_str()).collect();
+        assert_eq!(names, ["--verbose", "--quiet"]);
+    }
+
+    #[test]
+    fn test_parse_option_groups() {
+        let content = "usage: tool [OPTIONS]\n\n  -h, --help\n      Show help\n\nOutput options:\n  -o, --output <FILE>\n      Write to FILE\n  -q, --quiet\n      Be quiet\n\nNETWORK\n  --proxy <URL>\n      Use a proxy\n\nEXAMPLES\n  --proxy <URL>\n      Not a group\n";
+        let options = Layout::parse_blockwise_skip_sections(content, KNOWN_NON_OPTION_SECTIONS);
+        assert_eq!(options.len(), 4);
+
+        let groups = Layout::parse_option_groups(content, &options, KNOWN_NON_OPTION_SECTIONS);
+        let summary: Vec<(&str, usize)> = groups
+            .iter()
+            .map(|group| (group.name.as_str(), group.options.len()))
+            .collect();
+        assert_eq!(summary, [("Output options", 2), ("NETWORK", 1)]);
+        assert_eq!(groups[1].options[0], options[3]);
+    }
+
+    #[test]
+    fn test_section_header() {
+        assert_eq!(Layout::section_header("SEE ALSO"), Some("SEE ALSO"));
+        assert_eq!(Layout::section_header("Examples:"), Some("Examples"));
+        assert_eq!(Layout::section_header("  EXAMPLES"), None);
+        assert_eq!(Layout::section_header("Run this command"), None);
+        assert_eq!(Layout::section_header(""), None);
+    }
 
     #[test]
     fn test_parse_usage() {
@@ -240,6 +853,74 @@ mod tests {
         assert!(!usage.is_empty());
     }
 
+    #[test]
+    fn test_parse_usage_requires_header() {
+        // Mentioning "usage" inside a description is not a usage header
+        let line = "  -h, --help  print usage: see manual";
+        assert!(Parser::parse_usage_header(&["usage", "synopsis"], line).is_none());
+        assert!(Layout::parse_usage(line).is_empty());
+
+        let synopsis = "SYNOPSIS\n       ls [OPTION]... [FILE]...\n\nDESCRIPTION";
+        assert_eq!(
+            Layout::parse_usage(synopsis),
+            "SYNOPSIS\n       ls [OPTION]... [FILE]..."
+        );
+    }
+
+    #[test]
+    fn test_parse_usage_multiple_forms() {
+        let content = "  Usage:\n    tool [OPTION]... SOURCE DEST\n    tool [OPTION]... SOURCE... DIRECTORY\n    tool [OPTION]... -t DIRECTORY SOURCE...\n  Options:\n    -v  verbose";
+        let usage = Layout::parse_usage(content);
+        assert_eq!(usage.lines().count(), 4);
+        assert!(usage.contains("SOURCE DEST"));
+        assert!(usage.contains("SOURCE... DIRECTORY"));
+        assert!(usage.contains("-t DIRECTORY SOURCE..."));
+        assert!(!usage.contains("Options"));
+
+        let cp = "Usage: cp [OPTION]... [-T] SOURCE DEST\n  or:  cp [OPTION]... SOURCE... DIRECTORY\n  or:  cp [OPTION]... -t DIRECTORY SOURCE...\nCopy SOURCE to DEST.";
+        let usage = Layout::parse_usage(cp);
+        assert_eq!(usage.lines().count(), 3);
+        assert!(!usage.contains("Copy"));
+    }
+
+    #[test]
+    fn test_parse_usage_large_input_without_header() {
+        let content = "  --some-option   does something useful\n".repeat(20_000);
+        let start = std::time::Instant::now();
+        assert!(Layout::parse_usage(&content).is_empty());
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_parse_usage_alternatives() {
+        let content = "Usage: mycmd -a FILE | mycmd -b DIR\n\nOptions:";
+        assert_eq!(
+            Layout::parse_usage_alternatives(&Layout::parse_usage(content)).as_slice(),
+            ["mycmd -a FILE", "mycmd -b DIR"]
+        );
+
+        // Wrapped onto a continuation line
+        let wrapped = "Usage: mycmd -a FILE |\n         mycmd -b DIR\n";
+        assert_eq!(
+            Layout::parse_usage_alternatives(&Layout::parse_usage(wrapped)).len(),
+            2
+        );
+
+        // Choices inside brackets and pipelines are not alternatives
+        for content in [
+            "Usage: mycmd [-a|-b] FILE",
+            "Usage: mycmd FILE | less",
+            "Usage: mycmd [OPTIONS]",
+            "no usage here",
+        ] {
+            assert!(
+                Layout::parse_usage_alternatives(&Layout::parse_usage(content)).is_empty(),
+                "{}",
+                content
+            );
+        }
+    }
+
     #[test]
     fn test_parse_and_preprocess_blockwise() {
         let content = "\
@@ -255,6 +936,25 @@ mod tests {
         assert!(pairs.iter().any(|(opt, _)| opt.contains("--verbose")));
     }
 
+    #[test]
+    fn test_parse_blockwise_with_column_detects_description_column() {
+        let content = "\
+  -i, --input FILE       Input FASTA/FASTQ file\n\
+  -o, --output FILE      Output BAM file\n\
+  --min-mapq INT         Minimum mapping quality\n";
+
+        assert_eq!(Layout::get_description_offset(content), Some(23));
+
+        let opts = Layout::parse_blockwise_with_column(content, None);
+        assert_eq!(opts.len(), 3);
+        let output = opts
+            .iter()
+            .find(|o| o.names.iter().any(|n| n.raw == "--output"))
+            .unwrap();
+        assert_eq!(output.argument.as_str(), "FILE");
+        assert_eq!(output.description.as_str(), "Output BAM file");
+    }
+
     #[test]
     fn test_get_option_offsets() {
         let content = "\
@@ -266,4 +966,18 @@ mod tests {
         // both short and long options are aligned, so we should get a single offset
         assert_eq!(offsets.len(), 1);
     }
+
+    #[test]
+    fn test_get_option_offsets_prefers_smaller_offset_on_ties() {
+        // Each offset appears twice; repeat to rule out hash-order luck
+        let content = "      --wide-a     nested
+  --all        show all
+      --wide-b     nested
+  --verbose    be verbose
+";
+
+        for _ in 0..16 {
+            assert_eq!(Layout::get_option_offsets(content).as_slice(), [2]);
+        }
+    }
 }
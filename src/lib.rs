@@ -1,26 +1,53 @@
-pub mod cache;
+mod cache;
 pub mod cli;
-pub mod generators;
-pub mod io_handler;
-pub mod json_gen;
-pub mod layout;
-pub mod parser;
-pub mod postprocessor;
-pub mod subcommand_parser;
+mod generators;
+mod io_handler;
+mod json_gen;
+mod layout;
+mod markdown_gen;
+mod parser;
+mod postprocessor;
+mod subcommand_parser;
 pub mod types;
+mod util;
+mod yaml_gen;
 
 pub use cache::{Cache, CacheEntry, CacheStats, DEFAULT_TTL_SECS};
 pub use cli::{Cli, Shell};
 pub use generators::{
-    BashGenerator, ElvishGenerator, FishGenerator, NushellGenerator, ZshGenerator,
+    BashGenerator, CarapaceGenerator, ElvishGenerator, FishGenerator, Generator, NushellGenerator,
+    PowerShellGenerator, TcshGenerator, XonshGenerator, ZshGenerator,
 };
 pub use io_handler::IoHandler;
-pub use json_gen::JsonGenerator;
-pub use layout::Layout;
+pub use json_gen::{JsonGenerator, METADATA_SCHEMA_VERSION};
+pub use layout::{KNOWN_NON_OPTION_SECTIONS, Layout, LayoutConfig};
+pub use markdown_gen::MarkdownGenerator;
 pub use parser::Parser;
-pub use postprocessor::Postprocessor;
-pub use subcommand_parser::SubcommandParser;
+pub use postprocessor::{Postprocessor, PostprocessorConfig};
+pub use subcommand_parser::{SubcommandParser, SubcommandParserConfig};
 pub use types::*;
+pub use yaml_gen::YamlGenerator;
+
+/// String and vector types used by the public structs, so callers can build
+/// a [`Command`] without depending on `ecow` themselves. Not covered by the
+/// stability policy in `STABILITY.md`.
+#[doc(hidden)]
+pub use ecow::{EcoString, EcoVec, eco_vec};
+
+/// The types most programs need: parse help text into a [`Command`] and
+/// render it for a shell.
+///
+/// ```
+/// use d2o::prelude::*;
+/// ```
+pub mod prelude {
+    pub use crate::{
+        BashGenerator, CarapaceGenerator, Command, ElvishGenerator, FishGenerator, Generator,
+        JsonGenerator, Layout, NushellGenerator, Opt, OptName, OptNameType, Parser, Postprocessor,
+        PowerShellGenerator, Shell, Subcommand, SubcommandParser, TcshGenerator, XonshGenerator,
+        ZshGenerator,
+    };
+}
 
 use shadow_rs::shadow;
 shadow!(build);
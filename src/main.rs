@@ -3,11 +3,15 @@ use clap_complete::generate;
 use clap_complete::shells::{Bash, Elvish, Fish, PowerShell, Zsh};
 use clap_complete_nushell::Nushell;
 use d2o::{
-    BashGenerator, Cache, Cli, Command, ElvishGenerator, FishGenerator, IoHandler, JsonGenerator,
-    Layout, NushellGenerator, Postprocessor, Shell, SubcommandParser, ZshGenerator,
+    BashGenerator, Cache, CarapaceGenerator, Cli, Command, ElvishGenerator, FishGenerator,
+    Generator, IoHandler, JsonGenerator, KNOWN_NON_OPTION_SECTIONS, Layout, MarkdownGenerator,
+    NushellGenerator, Parser, Postprocessor, PostprocessorConfig, PowerShellGenerator, Shell,
+    SubcommandParser, TcshGenerator, XonshGenerator, YamlGenerator, ZshGenerator,
     command_with_version,
 };
 use ecow::EcoString;
+use std::collections::HashMap;
+use std::fmt::Write;
 use std::io;
 use std::path::Path;
 use std::time::Duration;
@@ -21,7 +25,13 @@ fn init_tracing(cli: &Cli) {
     use tracing_subscriber::fmt;
     use tracing_subscriber::prelude::*;
 
-    if let Some(level) = cli.verbosity.tracing_level() {
+    let level = if cli.trace {
+        Some(tracing::Level::TRACE)
+    } else {
+        cli.verbosity.tracing_level()
+    };
+
+    if let Some(level) = level {
         tracing_subscriber::registry()
             .with(fmt::layer())
             .with(tracing_subscriber::filter::LevelFilter::from_level(level))
@@ -39,6 +49,7 @@ async fn main() -> anyhow::Result<()> {
     let matches = command_with_version().get_matches_from(expanded_args);
     let cli = Cli::from_arg_matches(&matches)?;
     init_tracing(&cli);
+    cli.validate()?;
 
     let mut command = command_with_version();
     let name = crate_name!();
@@ -53,6 +64,7 @@ async fn main() -> anyhow::Result<()> {
             Shell::PowerShell => generate(PowerShell, &mut command, name, &mut stdout),
             Shell::Elvish => generate(Elvish, &mut command, name, &mut stdout),
             Shell::Nushell => generate(Nushell, &mut command, name, &mut stdout),
+            other => anyhow::bail!("completions for {:?} are not supported", other),
         }
         return Ok(());
     }
@@ -80,7 +92,7 @@ async fn main() -> anyhow::Result<()> {
     // Handle preprocess only (debug mode)
     if cli.is_preprocess_only() {
         let content = get_input_content(&cli).await?;
-        let pairs = Layout::preprocess_blockwise(&content);
+        let pairs = Layout::preprocess_blockwise(&IoHandler::normalize_text(&content));
         for (opt_part, desc) in pairs.iter() {
             println!("{}\n{}", opt_part, desc);
         }
@@ -100,23 +112,51 @@ async fn main() -> anyhow::Result<()> {
     // Normal processing with optional caching
     let cmd = if cli.loadjson.is_some() {
         load_command_from_json(&cli).await?
+    } else if cli.loadyaml.is_some() {
+        load_command_from_yaml(&cli).await?
     } else {
         let content = get_input_content(&cli).await?;
         build_command_with_cache(&cli, &content).await?
     };
 
-    let output = match format.as_str() {
-        "fish" => FishGenerator::generate(&cmd),
-        "zsh" => ZshGenerator::generate(&cmd),
-        "bash" => BashGenerator::generate_with_compat(&cmd, cli.bash_completion_compat),
-        "elvish" => ElvishGenerator::generate(&cmd),
-        "nushell" => NushellGenerator::generate(&cmd),
-        "json" => JsonGenerator::generate(&cmd),
-        "native" => format_native(&cmd),
-        _ => anyhow::bail!("Unknown output option"),
-    };
+    if let Some(old_file) = &cli.diff {
+        let content = IoHandler::read_file(old_file).await?;
+        let old = JsonGenerator::parse(&content)?;
+        println!("{}", Command::diff(&old, &cmd));
+        return Ok(());
+    }
+
+    if cmd.is_empty() {
+        if cli.is_quiet() {
+            return Ok(());
+        }
+        eprintln!(
+            "warning: no options or subcommands found for '{}'",
+            cmd.name
+        );
+    }
+
+    if let Some(dir) = &cli.multi_format {
+        for path in write_multi_format(&cli, &cmd, Path::new(dir)).await? {
+            println!("{}", path.display());
+        }
+        return Ok(());
+    }
 
-    if cli.write {
+    let output = generate_output(&cli, &cmd, &format)?;
+
+    if let Some(dir) = cli.completion_dir()? {
+        // Fish loads `<cmd>.fish`, zsh's compinit loads `_<cmd>` from $fpath
+        let base = script_base_name(&cmd.name)?;
+        let file_name = if format == "zsh" {
+            format!("_{}", base)
+        } else {
+            format!("{}.fish", base)
+        };
+        let path = dir.join(file_name);
+        IoHandler::write_file(&path, &output).await?;
+        println!("{}", path.display());
+    } else if cli.write {
         let path = write_output_to_cache(&cmd, &format, &output).await?;
         println!("{}", path.display());
     } else {
@@ -126,60 +166,207 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Shell formats written by `--multi-format`, with their file extensions
+const MULTI_FORMATS: &[(&str, &str)] = &[
+    ("bash", "bash"),
+    ("zsh", "zsh"),
+    ("fish", "fish"),
+    ("elvish", "elv"),
+    ("nushell", "nu"),
+    ("powershell", "ps1"),
+];
+
+type BoxedGenerator = Box<dyn Generator + Send + Sync>;
+
+/// Shell generators for `--format`, configured from the CLI flags
+fn shell_generators(cli: &Cli) -> HashMap<&'static str, BoxedGenerator> {
+    let comments = cli.comments;
+    let grouped = cli.grouped;
+    let inherit = cli.inherit_options;
+    let bash_compat = cli.bash_completion_compat;
+
+    let mut generators: HashMap<&'static str, BoxedGenerator> = HashMap::with_capacity(9);
+    generators.insert(
+        "fish",
+        Box::new(|cmd: &Command| FishGenerator::generate_exclusive(cmd)),
+    );
+    generators.insert(
+        "zsh",
+        Box::new(move |cmd: &Command| {
+            if comments {
+                ZshGenerator::generate_with_comments(cmd, true)
+            } else if grouped {
+                ZshGenerator::generate_grouped(cmd)
+            } else if inherit {
+                ZshGenerator::generate_with_root(cmd, Some(cmd))
+            } else {
+                ZshGenerator::generate_exclusive(cmd)
+            }
+        }),
+    );
+    generators.insert(
+        "bash",
+        Box::new(move |cmd: &Command| {
+            if comments {
+                BashGenerator::generate_with_comments(cmd)
+            } else {
+                BashGenerator::generate_with_compat(cmd, bash_compat)
+            }
+        }),
+    );
+    generators.insert("elvish", Box::new(ElvishGenerator));
+    generators.insert("nushell", Box::new(NushellGenerator));
+    generators.insert("tcsh", Box::new(TcshGenerator));
+    generators.insert("xonsh", Box::new(XonshGenerator));
+    generators.insert("carapace", Box::new(CarapaceGenerator));
+    generators.insert("powershell", Box::new(PowerShellGenerator));
+    generators
+}
+
+fn generate_output(cli: &Cli, cmd: &Command, format: &str) -> anyhow::Result<EcoString> {
+    if let Some(generator) = shell_generators(cli).get(format) {
+        return Ok(generator.generate(cmd));
+    }
+
+    Ok(match format {
+        "json" if cli.json_sort_keys => JsonGenerator::generate_sorted(cmd),
+        "json" if cli.metadata => {
+            let source = cli.get_input().or(cli.subcommand.as_deref());
+            JsonGenerator::generate_with_metadata(cmd, source.unwrap_or_default())
+        }
+        "json" => JsonGenerator::generate(cmd),
+        "ndjson" => JsonGenerator::generate_ndjson(cmd),
+        "yaml" => YamlGenerator::generate(cmd),
+        "markdown-table" => MarkdownGenerator::generate_table(cmd),
+        "native" if cli.native_structured => format_native_structured(cmd),
+        "native" => format_native(cmd),
+        _ => anyhow::bail!("Unknown output option"),
+    })
+}
+
+/// Write one completion script per [`MULTI_FORMATS`] entry into `dir` as
+/// `<cmd>.<ext>`. Each file is written to a temporary name and renamed into
+/// place, so readers never see a partially written script.
+async fn write_multi_format(
+    cli: &Cli,
+    cmd: &Command,
+    dir: &Path,
+) -> anyhow::Result<Vec<std::path::PathBuf>> {
+    let base = script_base_name(&cmd.name)?;
+    let mut paths = Vec::with_capacity(MULTI_FORMATS.len());
+    for (format, ext) in MULTI_FORMATS {
+        let output = generate_output(cli, cmd, format)?;
+        let path = dir.join(format!("{}.{}", base, ext));
+        IoHandler::write_file(&path, &output).await?;
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+/// The last path component of `name`, used to name files written into an
+/// output directory. `--command /usr/bin/tool` installs `tool.fish`, never a
+/// path outside the directory.
+fn script_base_name(name: &str) -> anyhow::Result<&str> {
+    Path::new(name)
+        .file_name()
+        .and_then(|base| base.to_str())
+        .filter(|base| !base.is_empty() && *base != "..")
+        .ok_or_else(|| anyhow::anyhow!("cannot derive a file name from command '{}'", name))
+}
+
 async fn get_input_content(cli: &Cli) -> anyhow::Result<EcoString> {
     let content = if let Some(json_file) = &cli.loadjson {
         IoHandler::read_file(json_file).await?
+    } else if let Some(yaml_file) = &cli.loadyaml {
+        IoHandler::read_file(yaml_file).await?
     } else if let Some(file) = &cli.file {
         IoHandler::read_file(file).await?
     } else if let Some(cmd_name) = &cli.command {
+        let cmd_name = IoHandler::sanitize_command_name(cmd_name)?;
         if cli.skip_man || !IoHandler::is_man_available(cmd_name).await {
-            IoHandler::get_command_help(cmd_name).await?
+            debug!("falling back to --help for '{}'", cmd_name);
+            get_command_help(cli, cmd_name).await?
         } else {
-            IoHandler::get_manpage(cmd_name).await?
+            debug!("using man page for '{}'", cmd_name);
+            IoHandler::get_manpage_with_width(cmd_name, cli.man_width, cli.retry).await?
         }
-    } else if let Some(subcommand) = &cli.subcommand {
-        let (cmd, subcmd) = subcommand.split_once('-').ok_or_else(|| {
-            anyhow::anyhow!("Subcommand format should be command-subcommand (e.g., git-log)")
+    } else if cli.subcommand.is_some() {
+        let (cmd, subcmd) = cli.subcommand_parts().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Subcommand format should be command-subcommand (e.g., git-log) or \"command subcommand\""
+            )
         })?;
+        let cmd = IoHandler::sanitize_command_name(cmd)?;
+        let subcmd = IoHandler::sanitize_command_name(subcmd)?;
 
         if cli.skip_man || !IoHandler::is_man_available(cmd).await {
-            IoHandler::get_command_help(&format!("{} {}", cmd, subcmd)).await?
+            debug!("falling back to --help for '{} {}'", cmd, subcmd);
+            get_command_help(cli, &format!("{} {}", cmd, subcmd)).await?
         } else {
-            IoHandler::get_manpage(&format!("{}-{}", cmd, subcmd)).await?
+            debug!("using man page for '{}-{}'", cmd, subcmd);
+            IoHandler::get_manpage_with_width(
+                &format!("{}-{}", cmd, subcmd),
+                cli.man_width,
+                cli.retry,
+            )
+            .await?
         }
     } else {
         return Err(anyhow::anyhow!(
-            "No input source specified. Use --command, --file, --subcommand, or --loadjson"
+            "No input source specified. Use --command, --file, --subcommand, --loadjson, or --loadyaml"
         ));
     };
 
-    Ok(Postprocessor::unicode_spaces_to_ascii(
-        &Postprocessor::remove_bullets(&IoHandler::normalize_text(&content)),
-    ))
+    let without_cr = Postprocessor::strip_carriage_returns(&content);
+    let without_overstrike = Postprocessor::fix_overstrike(&without_cr);
+    // Runs of spaces are kept so `build_command` can find the description column
+    let expanded = IoHandler::expand_tabs(&without_overstrike, cli.tab_width);
+    let without_bullets = Postprocessor::remove_bullets(&expanded);
+    Ok(EcoString::from(Postprocessor::unicode_spaces_to_ascii(
+        &without_bullets,
+    )))
+}
+
+/// Run `{cmd} --help`, forwarding its stderr with `--capture-stderr`.
+async fn get_command_help(cli: &Cli, cmd: &str) -> anyhow::Result<EcoString> {
+    if !cli.capture_stderr {
+        return IoHandler::get_command_help_with_columns(cmd, cli.columns, cli.retry).await;
+    }
+
+    let (stdout, stderr) =
+        IoHandler::get_command_help_with_stderr(cmd, cli.columns, cli.retry).await?;
+    for line in stderr.lines() {
+        eprintln!("{}: {}", cmd, line);
+    }
+    Ok(stdout)
 }
 
 fn build_command(cli: &Cli, content: &str) -> anyhow::Result<Command> {
-    let name = if let Some(cmd_name) = &cli.command {
-        EcoString::from(cmd_name.as_str())
-    } else if let Some(file) = &cli.file {
-        EcoString::from(
-            Path::new(file)
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("command"),
-        )
-    } else if let Some(subcommand) = &cli.subcommand {
-        EcoString::from(subcommand.as_str())
-    } else {
-        EcoString::from("command")
+    let mut cmd = Command::new(EcoString::from(cli.get_input_display_name()));
+    // The description column has to be measured before double spaces are collapsed,
+    // since a two-space gap is often all that separates an option from its description
+    let layout = IoHandler::expand_tabs(content, cli.tab_width);
+    let content = &*IoHandler::normalize_text(&layout);
+    cmd.options = match Layout::get_description_offset(&layout) {
+        Some(col) => Layout::parse_blockwise_skip_sections_with_column(
+            &layout,
+            KNOWN_NON_OPTION_SECTIONS,
+            Some(col),
+        ),
+        None => Layout::parse_blockwise_skip_sections(content, KNOWN_NON_OPTION_SECTIONS),
     };
-
-    let mut cmd = Command::new(name.clone());
-    cmd.options = Layout::parse_blockwise(content);
     cmd.usage = Layout::parse_usage(content);
+    cmd.usage_alternatives = Layout::parse_usage_alternatives(&cmd.usage);
+    cmd.accepts_options = Command::usage_accepts_options(&cmd.usage);
+    cmd.exclusive_groups = Parser::parse_exclusive_groups(&cmd.options);
+    if cli.grouped {
+        cmd.option_groups =
+            Layout::parse_option_groups(content, &cmd.options, KNOWN_NON_OPTION_SECTIONS);
+    }
 
     let subcommand_candidates = SubcommandParser::parse(content);
-    if cli.depth > 0 && !subcommand_candidates.is_empty() {
+    if cli.effective_depth() > 0 && !subcommand_candidates.is_empty() {
         for subcmd in subcommand_candidates.iter() {
             let sub = Command {
                 name: subcmd.cmd.clone(),
@@ -188,6 +375,10 @@ fn build_command(cli: &Cli, content: &str) -> anyhow::Result<Command> {
                 options: ecow::EcoVec::new(),
                 subcommands: ecow::EcoVec::new(),
                 version: EcoString::new(),
+                accepts_options: true,
+                exclusive_groups: ecow::EcoVec::new(),
+                option_groups: ecow::EcoVec::new(),
+                usage_alternatives: ecow::EcoVec::new(),
             };
             cmd.subcommands.push(sub);
         }
@@ -196,19 +387,19 @@ fn build_command(cli: &Cli, content: &str) -> anyhow::Result<Command> {
     Ok(cmd)
 }
 
+fn postprocessor_config(cli: &Cli) -> PostprocessorConfig {
+    PostprocessorConfig {
+        filter_invalid: !cli.no_filter,
+        dedup: !cli.no_dedup,
+        normalize_name: !cli.no_normalize_name,
+        ..PostprocessorConfig::default()
+    }
+}
+
 /// Build a command with caching support.
 async fn build_command_with_cache(cli: &Cli, content: &str) -> anyhow::Result<Command> {
     // Determine command name for cache key
-    let name = cli
-        .command
-        .as_deref()
-        .or(cli.subcommand.as_deref())
-        .or_else(|| {
-            cli.file
-                .as_ref()
-                .and_then(|f| Path::new(f).file_name()?.to_str())
-        })
-        .unwrap_or("command");
+    let name = cli.get_input_display_name();
 
     // Determine source identifier for cache key
     let source = if cli.command.is_some() || cli.subcommand.is_some() {
@@ -221,6 +412,33 @@ async fn build_command_with_cache(cli: &Cli, content: &str) -> anyhow::Result<Co
         cli.file.as_deref()
     };
 
+    // Postprocessing, grouping and version flags change the cached result, so they are part of the source key
+    let mut source = source.map(EcoString::from);
+    if cli.no_filter
+        || cli.no_dedup
+        || cli.no_normalize_name
+        || cli.grouped
+        || cli.version_from_command
+    {
+        let key = source.get_or_insert_with(EcoString::new);
+        if cli.no_filter {
+            key.push_str("+no-filter");
+        }
+        if cli.no_dedup {
+            key.push_str("+no-dedup");
+        }
+        if cli.no_normalize_name {
+            key.push_str("+no-normalize-name");
+        }
+        if cli.grouped {
+            key.push_str("+grouped");
+        }
+        if cli.version_from_command {
+            key.push_str("+version-from-command");
+        }
+    }
+    let source = source.as_deref();
+
     let content_hash = Cache::hash_content(content);
 
     // Try cache if enabled
@@ -228,18 +446,19 @@ async fn build_command_with_cache(cli: &Cli, content: &str) -> anyhow::Result<Co
         let ttl = Duration::from_secs(cli.cache_ttl * 3600);
         if let Ok(cache) = Cache::with_ttl(ttl) {
             // Try to get from cache
-            if let Some(cached_cmd) = cache.get(name, source, content_hash).await {
+            if let Some(cached_cmd) = cache.get(&name, source, content_hash).await {
                 debug!("Cache hit for command: {}", name);
                 return Ok(cached_cmd);
             }
 
             // Parse and cache the result
             debug!("Cache miss for command: {}, parsing...", name);
-            let cmd = build_command(cli, content)?;
-            let cmd = Postprocessor::fix_command(cmd);
+            let mut cmd = build_command(cli, content)?;
+            cmd.version = fetch_command_version(cli).await;
+            let cmd = Postprocessor::fix_command_with_config(cmd, &postprocessor_config(cli));
 
             // Store in cache (ignore errors, caching is best-effort)
-            if let Err(e) = cache.set(name, source, content_hash, &cmd).await {
+            if let Err(e) = cache.set(&name, source, content_hash, &cmd).await {
                 debug!("Failed to cache command: {}", e);
             }
 
@@ -248,8 +467,33 @@ async fn build_command_with_cache(cli: &Cli, content: &str) -> anyhow::Result<Co
     }
 
     // Caching disabled or failed to initialize
-    let cmd = build_command(cli, content)?;
-    Ok(Postprocessor::fix_command(cmd))
+    let mut cmd = build_command(cli, content)?;
+    cmd.version = fetch_command_version(cli).await;
+    Ok(Postprocessor::fix_command_with_config(
+        cmd,
+        &postprocessor_config(cli),
+    ))
+}
+
+/// Run `{cmd} --version` when the options came from the command's --help output,
+/// or always with `--version-from-command`.
+async fn fetch_command_version(cli: &Cli) -> EcoString {
+    let cmd_name = match (&cli.command, cli.subcommand_parts()) {
+        (Some(cmd_name), _) => cmd_name.as_str(),
+        (None, Some((cmd_name, _))) if cli.version_from_command => cmd_name,
+        _ => return EcoString::new(),
+    };
+
+    if !cli.version_from_command && !cli.skip_man && IoHandler::is_man_available(cmd_name).await {
+        return EcoString::new();
+    }
+
+    IoHandler::get_command_version(cmd_name)
+        .await
+        .unwrap_or_else(|e| {
+            debug!("Failed to get version for {}: {}", cmd_name, e);
+            EcoString::new()
+        })
 }
 
 async fn load_command_from_json(cli: &Cli) -> anyhow::Result<Command> {
@@ -258,27 +502,121 @@ async fn load_command_from_json(cli: &Cli) -> anyhow::Result<Command> {
         .as_ref()
         .ok_or_else(|| anyhow::anyhow!("No JSON file specified"))?;
     let content = IoHandler::read_file(json_file).await?;
-    let mut cmd: Command = serde_json::from_str(&content)?;
-    cmd = Postprocessor::fix_command(cmd);
+    let mut cmd = if cli.strict {
+        JsonGenerator::parse_strict(&content)?
+    } else {
+        JsonGenerator::parse(&content)?
+    };
+    cmd = limit_subcommand_depth(cmd, cli.depth);
+    cmd = Postprocessor::fix_command_with_config(cmd, &postprocessor_config(cli));
+    Ok(cmd)
+}
+
+async fn load_command_from_yaml(cli: &Cli) -> anyhow::Result<Command> {
+    let yaml_file = cli
+        .loadyaml
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("No YAML file specified"))?;
+    let content = IoHandler::read_file(yaml_file).await?;
+    let mut cmd = YamlGenerator::parse(&content)?;
+    cmd = limit_subcommand_depth(cmd, cli.depth);
+    cmd = Postprocessor::fix_command_with_config(cmd, &postprocessor_config(cli));
     Ok(cmd)
 }
 
+/// Truncate the subcommand tree below `depth` levels, or not at all for `None`.
+///
+/// A `DEPTH=N` annotation in a subcommand's description overrides the
+/// remaining depth for that branch and is removed from the description.
+fn limit_subcommand_depth(mut cmd: Command, depth: Option<usize>) -> Command {
+    if depth == Some(0) {
+        cmd.subcommands.clear();
+        return cmd;
+    }
+
+    cmd.subcommands = cmd
+        .subcommands
+        .into_iter()
+        .map(|mut sub| {
+            let sub_depth = sub.take_depth_override().or(depth.map(|depth| depth - 1));
+            limit_subcommand_depth(sub, sub_depth)
+        })
+        .collect();
+    cmd
+}
+
+/// Native output with one `key: value` field per line, options as a list of entries.
+fn format_native_structured(cmd: &Command) -> EcoString {
+    let mut buf = String::with_capacity(256 + cmd.options.len() * 96);
+
+    let _ = writeln!(buf, "name: {}", cmd.name);
+    if !cmd.version.is_empty() {
+        let _ = writeln!(buf, "version: {}", cmd.version);
+    }
+    let _ = writeln!(buf, "description: {}", cmd.description);
+    let _ = writeln!(buf, "usage: {}", cmd.usage.replace("\n", " "));
+
+    let _ = writeln!(buf, "options:");
+    for opt in cmd.options.iter() {
+        let names = opt
+            .names
+            .iter()
+            .map(|n| n.raw.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let argument = if opt.argument.is_empty() {
+            "(none)"
+        } else {
+            opt.argument.as_str()
+        };
+        let _ = writeln!(buf, "  - names: [{}]", names);
+        let _ = writeln!(buf, "    argument: {}", argument);
+        let _ = writeln!(buf, "    description: {}", opt.description);
+    }
+
+    if !cmd.subcommands.is_empty() {
+        let _ = writeln!(buf, "subcommands:");
+        for subcmd in cmd.subcommands.iter() {
+            let _ = writeln!(buf, "  - name: {}", subcmd.name);
+            let _ = writeln!(buf, "    description: {}", subcmd.description);
+        }
+    }
+
+    EcoString::from(buf.trim_end())
+}
+
 fn format_native(cmd: &Command) -> EcoString {
     let mut output = Vec::new();
 
     output.push(format!("Name:  {}", cmd.name));
+    if !cmd.version.is_empty() {
+        output.push(format!("Version:  {}", cmd.version));
+    }
     output.push(format!("Desc:  {}", cmd.description));
-    output.push(format!("Usage:\n{}", cmd.usage));
+    if cmd.usage_alternatives.is_empty() {
+        output.push(format!("Usage:\n{}", cmd.usage));
+    } else {
+        let mut usage = String::from("Usage:");
+        for (i, form) in cmd.usage_alternatives.iter().enumerate() {
+            let _ = write!(usage, "\n  {}. {}", i + 1, form);
+        }
+        output.push(usage);
+    }
 
     for opt in cmd.options.iter() {
         output.push(format!(
-            "  {} ({})",
+            "  {} ({})  [{}]",
             opt.names
                 .iter()
                 .map(|n| n.raw.to_string())
                 .collect::<Vec<_>>()
                 .join(", "),
-            opt.argument
+            opt.argument,
+            opt.names
+                .iter()
+                .map(|n| n.opt_type.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
         ));
     }
 
@@ -322,16 +660,41 @@ mod tests {
             command: None,
             file: None,
             subcommand: None,
+            subcommand_sep: None,
             loadjson: None,
+            loadyaml: None,
             format: "native".to_string(),
             json: false,
+            ndjson: false,
             skip_man: false,
+            retry: 1,
             list_subcommands: false,
             debug: false,
-            depth: 4,
+            depth: None,
             completions: None,
             write: false,
             bash_completion_compat: false,
+            comments: false,
+            grouped: false,
+            inherit_options: false,
+            trace: false,
+            strict: false,
+            capture_stderr: false,
+            no_filter: false,
+            no_dedup: false,
+            no_normalize_name: false,
+            json_sort_keys: false,
+            native_structured: false,
+            diff: None,
+            multi_format: None,
+            metadata: false,
+            fish_completion_dir: None,
+            user_fish: false,
+            zsh_completion_dir: None,
+            version_from_command: false,
+            man_width: d2o::cli::DEFAULT_MAN_WIDTH,
+            columns: d2o::cli::DEFAULT_HELP_COLUMNS,
+            tab_width: d2o::cli::DEFAULT_TAB_WIDTH,
             cache: false, // Disable cache in tests by default
             cache_ttl: DEFAULT_CACHE_TTL_HOURS,
             cache_clear: false,
@@ -369,6 +732,22 @@ mod tests {
         assert!(msg.contains("No input source specified"));
     }
 
+    #[tokio::test]
+    async fn test_get_input_content_rejects_shell_metacharacters() {
+        let cli = Cli {
+            command: Some("ls;rm -rf /".to_string()),
+            ..test_cli()
+        };
+        let err = get_input_content(&cli).await.unwrap_err();
+        assert!(err.to_string().contains("shell metacharacter"));
+
+        let cli = Cli {
+            subcommand: Some("git $(id)".to_string()),
+            ..test_cli()
+        };
+        assert!(get_input_content(&cli).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_load_command_from_json_roundtrip() {
         use std::io::Write;
@@ -388,13 +767,12 @@ mod tests {
                         ));
                         names
                     },
-                    argument: EcoString::new(),
                     description: EcoString::from("Verbose"),
+                    ..Default::default()
                 });
                 v
             },
-            subcommands: EcoVec::new(),
-            version: EcoString::new(),
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&cmd).unwrap();
@@ -415,6 +793,16 @@ mod tests {
         assert_eq!(loaded.options[0].description.as_str(), "Verbose");
     }
 
+    #[test]
+    fn test_script_base_name() {
+        assert_eq!(script_base_name("tool").unwrap(), "tool");
+        assert_eq!(script_base_name("/tmp/pt/bin/tool").unwrap(), "tool");
+        assert_eq!(script_base_name("../x").unwrap(), "x");
+        assert!(script_base_name("..").is_err());
+        assert!(script_base_name("").is_err());
+        assert!(script_base_name("/").is_err());
+    }
+
     #[test]
     fn test_build_command_uses_command_name_and_parses_options() {
         let cli = Cli {
@@ -427,6 +815,7 @@ mod tests {
 
         assert_eq!(cmd.name.as_str(), "mycmd");
         assert!(cmd.usage.contains("mycmd"));
+        assert!(cmd.accepts_options);
         assert_eq!(cmd.options.len(), 1);
         let opt = &cmd.options[0];
         let names: Vec<String> = opt.names.iter().map(|n| n.raw.to_string()).collect();
@@ -434,11 +823,46 @@ mod tests {
         assert!(names.contains(&"--verbose".to_string()));
     }
 
+    #[test]
+    fn test_get_input_display_name() {
+        let name = |cli: Cli| cli.get_input_display_name();
+
+        assert_eq!(
+            name(Cli {
+                command: Some("ls".to_string()),
+                ..test_cli()
+            }),
+            "ls"
+        );
+        assert_eq!(
+            name(Cli {
+                file: Some("/tmp/help.txt".to_string()),
+                ..test_cli()
+            }),
+            "help"
+        );
+        assert_eq!(
+            name(Cli {
+                subcommand: Some("git log".to_string()),
+                ..test_cli()
+            }),
+            "git-log"
+        );
+        assert_eq!(
+            name(Cli {
+                loadjson: Some("out/tool.json".to_string()),
+                ..test_cli()
+            }),
+            "tool"
+        );
+        assert_eq!(name(test_cli()), "command");
+    }
+
     #[test]
     fn test_build_command_name_from_file_and_subcommands() {
         let cli = Cli {
             file: Some("/tmp/mycmd-help.txt".to_string()),
-            depth: 1,
+            depth: Some(1),
             ..test_cli()
         };
 
@@ -446,12 +870,56 @@ mod tests {
             "USAGE: mycmd [COMMAND]\n\nSUBCOMMANDS:\n  run   Run things\n  build Build things";
         let cmd = build_command(&cli, help).expect("build command");
 
-        assert_eq!(cmd.name.as_str(), "mycmd-help.txt");
+        assert_eq!(cmd.name.as_str(), "mycmd-help");
+        assert!(!cmd.accepts_options);
         let names: Vec<String> = cmd.subcommands.iter().map(|s| s.name.to_string()).collect();
         assert!(names.contains(&"run".to_string()));
         assert!(names.contains(&"build".to_string()));
     }
 
+    #[test]
+    fn test_limit_subcommand_depth_honors_depth_annotation() {
+        let leaf = |name: &str| Command::new(EcoString::from(name));
+        let branch = |name: &str, desc: &str, sub: Command| {
+            let mut cmd = Command::new(EcoString::from(name));
+            cmd.description = EcoString::from(desc);
+            cmd.subcommands.push(sub);
+            cmd
+        };
+
+        let mut root = Command::new(EcoString::from("docker"));
+        root.subcommands.push(branch(
+            "container",
+            "Manage containers DEPTH=2",
+            branch("run", "Run a container", leaf("detached")),
+        ));
+        root.subcommands.push(branch(
+            "image",
+            "Manage images",
+            branch("ls", "List", leaf("all")),
+        ));
+
+        let limited = limit_subcommand_depth(root.clone(), Some(2));
+
+        // Annotated branch keeps two more levels, without the annotation
+        let container = &limited.subcommands[0];
+        assert_eq!(container.subcommands[0].subcommands.len(), 1);
+        assert_eq!(container.description.as_str(), "Manage containers");
+
+        // Unannotated branch is cut off at the global depth
+        let image = &limited.subcommands[1];
+        assert_eq!(image.subcommands.len(), 1);
+        assert!(image.subcommands[0].subcommands.is_empty());
+
+        // Without an explicit depth only annotated branches are limited
+        let unlimited = limit_subcommand_depth(root, None);
+        assert_eq!(unlimited.subcommands[1].subcommands[0].subcommands.len(), 1);
+        assert_eq!(
+            unlimited.subcommands[0].description.as_str(),
+            "Manage containers"
+        );
+    }
+
     #[test]
     fn test_format_native_includes_fields() {
         let mut cmd = Command::new(EcoString::from("test"));
@@ -473,15 +941,12 @@ mod tests {
             },
             argument: EcoString::from("FILE"),
             description: EcoString::from("Enable verbose mode"),
+            ..Default::default()
         });
 
         cmd.subcommands.push(Command {
             name: EcoString::from("sub"),
-            description: EcoString::new(),
-            usage: EcoString::new(),
-            options: EcoVec::new(),
-            subcommands: EcoVec::new(),
-            version: EcoString::new(),
+            ..Default::default()
         });
 
         let out = format_native(&cmd);
@@ -489,7 +954,21 @@ mod tests {
         assert!(out.contains("Desc:  Test command"));
         assert!(out.contains("Usage:\ntest [OPTIONS]"));
         assert!(out.contains("-v, --verbose"));
+        assert!(out.contains("[short (-), long (--)]"));
         assert!(out.contains("Subcommand: sub"));
+
+        let structured = format_native_structured(&cmd);
+        assert!(structured.starts_with("name: test\ndescription: Test command\n"));
+        assert!(structured.contains("  - names: [-v, --verbose]\n    argument: FILE\n"));
+        assert!(structured.contains("    description: Enable verbose mode"));
+        assert!(structured.contains("subcommands:\n  - name: sub"));
+
+        cmd.usage_alternatives = ecow::eco_vec![
+            EcoString::from("test -a FILE"),
+            EcoString::from("test -b DIR"),
+        ];
+        let out = format_native(&cmd);
+        assert!(out.contains("Usage:\n  1. test -a FILE\n  2. test -b DIR\n\n"));
     }
 
     #[tokio::test]
@@ -0,0 +1,69 @@
+use crate::types::Command;
+use ecow::EcoString;
+use std::fmt::Write;
+
+pub struct MarkdownGenerator;
+
+impl MarkdownGenerator {
+    /// Generate just the `| Option | Argument | Description |` table, ready to
+    /// paste into an existing Markdown document.
+    pub fn generate_table(cmd: &Command) -> EcoString {
+        let mut buf = String::with_capacity(64 + cmd.options.len() * 80);
+
+        let _ = writeln!(buf, "| Option | Argument | Description |");
+        let _ = write!(buf, "| --- | --- | --- |");
+
+        for opt in cmd.options.iter() {
+            let names = opt
+                .names
+                .iter()
+                .map(|name| format!("`{}`", Self::escape_cell(&name.raw)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let argument = if opt.argument.is_empty() {
+                String::new()
+            } else {
+                format!("`{}`", Self::escape_cell(&opt.argument))
+            };
+
+            let _ = write!(
+                buf,
+                "\n| {} | {} | {} |",
+                names,
+                argument,
+                Self::escape_cell(&opt.description)
+            );
+        }
+
+        EcoString::from(buf)
+    }
+
+    /// Escape `|` and turn line breaks into `<br>` so a value stays in its cell.
+    fn escape_cell(text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+
+        if let Some(first) = lines.next() {
+            result.push_str(first);
+        }
+        for line in lines {
+            result.push_str("<br>");
+            result.push_str(line);
+        }
+
+        result.replace('|', "\\|")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_cell() {
+        assert_eq!(
+            MarkdownGenerator::escape_cell("Either a|b\n  or c"),
+            "Either a\\|b<br>or c"
+        );
+    }
+}
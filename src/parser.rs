@@ -1,27 +1,81 @@
-use crate::types::{Opt, OptName};
+use crate::types::{Opt, OptName, OptNameType};
 use bstr::ByteSlice;
-use ecow::{EcoString, EcoVec};
+use ecow::{EcoString, EcoVec, eco_format};
 use memchr::memchr;
 use regex::Regex;
 use std::collections::HashSet;
+use std::sync::LazyLock;
 
 pub struct Parser;
 
+/// Most help blocks hold only a handful of options; up to this many, a
+/// linear scan beats hashing every option.
+const SMALL_DEDUP_MAX: usize = 4;
+
+/// Order-preserving deduplicating collection that only builds a hash set
+/// once the expected size exceeds [`SMALL_DEDUP_MAX`].
+struct SmallDedup<T> {
+    items: EcoVec<T>,
+    seen: Option<HashSet<T, foldhash::fast::RandomState>>,
+}
+
+impl<T: Clone + Eq + std::hash::Hash> SmallDedup<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        let seen = (capacity > SMALL_DEDUP_MAX).then(|| {
+            HashSet::with_capacity_and_hasher(capacity, foldhash::fast::RandomState::default())
+        });
+        Self {
+            items: EcoVec::with_capacity(capacity),
+            seen,
+        }
+    }
+
+    /// Push `item` unless an equal one is already present.
+    fn insert(&mut self, item: &T) -> bool {
+        let is_new = match &mut self.seen {
+            Some(seen) => seen.insert(item.clone()),
+            None => !self.items.contains(item),
+        };
+        if is_new {
+            self.items.push(item.clone());
+        }
+        is_new
+    }
+
+    fn into_items(self) -> EcoVec<T> {
+        self.items
+    }
+}
+
 impl Parser {
     pub fn parse_line(s: &str) -> EcoVec<Opt> {
-        let pairs = Self::preprocess(s);
-        let mut opts = EcoVec::new();
-        let mut seen: HashSet<Opt, foldhash::fast::RandomState> =
-            HashSet::with_capacity_and_hasher(pairs.len(), foldhash::fast::RandomState::default());
+        Self::parse_line_with_column(s, None)
+    }
 
-        for (opt_str, desc_str) in pairs.iter() {
-            for opt in Self::parse_with_opt_part(opt_str, desc_str).iter() {
-                if seen.insert(opt.clone()) {
+    /// Parse options, splitting option and description at `desc_col` when given.
+    pub fn parse_line_with_column(s: &str, desc_col: Option<usize>) -> EcoVec<Opt> {
+        if desc_col.is_none() && memchr(b'\n', s.as_bytes()).is_none() {
+            let Some((opt_str, desc_str)) = Self::preprocess_single(s) else {
+                return EcoVec::new();
+            };
+            let mut opts: EcoVec<Opt> = EcoVec::new();
+            for opt in Self::parse_with_opt_part(&opt_str, &desc_str).iter() {
+                if !opts.contains(opt) {
                     opts.push(opt.clone());
                 }
             }
+            return opts;
+        }
+
+        let pairs = Self::preprocess_with_column(s, desc_col);
+        let mut opts = SmallDedup::with_capacity(pairs.len());
+
+        for (opt_str, desc_str) in pairs.iter() {
+            for opt in Self::parse_with_opt_part(opt_str, desc_str).iter() {
+                opts.insert(opt);
+            }
         }
-        opts
+        opts.into_items()
     }
 
     pub fn preprocess(s: &str) -> EcoVec<(EcoString, EcoString)> {
@@ -45,44 +99,10 @@ impl Parser {
                 continue;
             }
 
-            // Try to split option and description from the same line first
-            // Most help text has format: "  -v, --verbose         description text"
-            // Count parts and find opt_end without allocating Vec
-            let mut opt_end = 0;
-            let mut part_count = 0;
-            for (idx, part) in trimmed.split_whitespace().enumerate() {
-                part_count += 1;
-                let part_bytes = part.as_bytes();
-                if part_bytes.first() == Some(&b'-') || idx == 0 {
-                    opt_end = idx + 1;
-                } else if memchr(b'=', part_bytes).is_some() || part_bytes.first() != Some(&b'-') {
-                    // Could be an argument marker
-                    opt_end = idx + 1;
-                } else {
-                    break;
-                }
-            }
-
-            if opt_end > 0 && opt_end < part_count {
-                // Description is on the same line - build strings without intermediate Vec
-                let mut opt_str = EcoString::new();
-                let mut desc_str = EcoString::new();
-                for (idx, part) in trimmed.split_whitespace().enumerate() {
-                    if idx < opt_end {
-                        if !opt_str.is_empty() {
-                            opt_str.push(' ');
-                        }
-                        opt_str.push_str(part);
-                    } else {
-                        if !desc_str.is_empty() {
-                            desc_str.push(' ');
-                        }
-                        desc_str.push_str(part);
-                    }
-                }
+            if let Some((opt_str, desc_str)) = Self::split_same_line(trimmed) {
                 result.push((opt_str, desc_str));
                 i += 1;
-            } else if opt_end > 0 {
+            } else {
                 // No description on this line, try next line
                 let opt_str = EcoString::from(trimmed);
                 let desc_str = if i + 1 < lines.len() {
@@ -104,17 +124,176 @@ impl Parser {
                     result.push((opt_str, EcoString::new()));
                     i += 1;
                 }
+            }
+        }
+
+        result
+    }
+
+    /// Preprocess a single option line without allocating a result vector.
+    ///
+    /// Returns `None` if the line does not start with `-`. A line without a
+    /// description yields an empty description, matching [`Parser::preprocess`].
+    pub fn preprocess_single(s: &str) -> Option<(EcoString, EcoString)> {
+        let trimmed = s.trim_start();
+        if trimmed.as_bytes().first() != Some(&b'-') {
+            return None;
+        }
+        Some(
+            Self::split_same_line(trimmed)
+                .unwrap_or_else(|| (EcoString::from(trimmed), EcoString::new())),
+        )
+    }
+
+    /// Split a trimmed option line into option and description parts when
+    /// both are present on the same line.
+    fn split_same_line(trimmed: &str) -> Option<(EcoString, EcoString)> {
+        // Most help text has format: "-v, --verbose         description text"
+        // Count parts and find opt_end without allocating Vec
+        let mut opt_end = 0;
+        let mut part_count = 0;
+        for (idx, part) in trimmed.split_whitespace().enumerate() {
+            part_count += 1;
+            let part_bytes = part.as_bytes();
+            if part_bytes.first() == Some(&b'-') || idx == 0 {
+                opt_end = idx + 1;
+            } else if memchr(b'=', part_bytes).is_some() || part_bytes.first() != Some(&b'-') {
+                // Could be an argument marker
+                opt_end = idx + 1;
+            } else {
+                break;
+            }
+        }
+
+        if opt_end == 0 || opt_end >= part_count {
+            return None;
+        }
+
+        // Build strings without intermediate Vec
+        let mut opt_str = EcoString::new();
+        let mut desc_str = EcoString::new();
+        for (idx, part) in trimmed.split_whitespace().enumerate() {
+            if idx < opt_end {
+                if !opt_str.is_empty() {
+                    opt_str.push(' ');
+                }
+                opt_str.push_str(part);
             } else {
+                if !desc_str.is_empty() {
+                    desc_str.push(' ');
+                }
+                desc_str.push_str(part);
+            }
+        }
+        Some((opt_str, desc_str))
+    }
+
+    /// Preprocess help text, splitting each option line at a fixed description column.
+    ///
+    /// With `desc_col` set to `None` this is equivalent to [`Parser::preprocess`].
+    /// Lines whose option part runs past the column fall back to splitting at the
+    /// first run of two spaces, or take their description from the next line.
+    /// Following lines indented deeper than the option line continue its
+    /// description.
+    pub fn preprocess_with_column(
+        s: &str,
+        desc_col: Option<usize>,
+    ) -> EcoVec<(EcoString, EcoString)> {
+        let Some(col) = desc_col else {
+            return Self::preprocess(s);
+        };
+
+        let bytes = s.as_bytes();
+        let lines: Vec<&str> = bytes
+            .lines()
+            .filter_map(|line| std::str::from_utf8(line).ok())
+            .collect();
+        let mut result = EcoVec::new();
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = lines[i];
+            let trimmed = line.trim_start();
+
+            if trimmed.as_bytes().first() != Some(&b'-') {
+                i += 1;
+                continue;
+            }
+
+            let indent = line.len() - trimmed.len();
+            let (opt_str, mut desc_str) =
+                if let Some((opt_part, desc_part)) = Self::split_at_column_or_gap(line, col) {
+                    (
+                        EcoString::from(opt_part.trim()),
+                        EcoString::from(desc_part.trim()),
+                    )
+                } else {
+                    // Option part fills the whole line, description starts on the next one
+                    (EcoString::from(trimmed.trim_end()), EcoString::new())
+                };
+            i += 1;
+
+            while let Some(next) = lines.get(i) {
+                let next_trimmed = next.trim();
+                if next_trimmed.is_empty()
+                    || next_trimmed.starts_with('-')
+                    || next.len() - next.trim_start().len() <= indent
+                {
+                    break;
+                }
+                if !desc_str.is_empty() {
+                    desc_str.push(' ');
+                }
+                desc_str.push_str(next_trimmed);
                 i += 1;
             }
+
+            result.push((opt_str, desc_str));
         }
 
         result
     }
 
+    /// Split `line` into the text before byte column `col` and the description
+    /// starting at the first non-space byte at or after `col`.
+    ///
+    /// Returns `(line, "")` when `col` is past the end of the line or not on a
+    /// character boundary. Leading blanks of the description are skipped with a
+    /// single byte scan instead of iterating over words.
+    pub fn split_at_column(line: &str, col: usize) -> (&str, &str) {
+        let Some(rest) = line.get(col..) else {
+            return (line, "");
+        };
+
+        let start = rest
+            .as_bytes()
+            .iter()
+            .position(|&b| b != b' ' && b != b'\t')
+            .unwrap_or(rest.len());
+
+        (&line[..col], &rest[start..])
+    }
+
+    fn split_at_column_or_gap(line: &str, col: usize) -> Option<(&str, &str)> {
+        let (opt_part, desc_part) = Self::split_at_column(line, col);
+        if opt_part.len() == col
+            && opt_part.ends_with(char::is_whitespace)
+            && !opt_part.trim().is_empty()
+            && !desc_part.trim().is_empty()
+        {
+            return Some((opt_part, desc_part));
+        }
+
+        // Option part overflows the column: split at the first double space instead
+        let trimmed = line.trim();
+        let gap = trimmed.find("  ")?;
+        Some((&trimmed[..gap], &trimmed[gap..]))
+    }
+
     pub fn parse_with_opt_part(opt_str: &str, desc_str: &str) -> EcoVec<Opt> {
         let names = Self::parse_opt_names(opt_str);
-        let arg = Self::parse_opt_arg(opt_str);
+        let (arg, argument_optional) = Self::strip_optional_brackets(Self::parse_opt_arg(opt_str));
+        let argument_required = Self::has_trailing_equals(opt_str);
 
         if names.is_empty() {
             return EcoVec::new();
@@ -125,38 +304,73 @@ impl Parser {
             names,
             argument: arg,
             description: EcoString::from(desc_str),
+            possible_values: Self::parse_possible_values(desc_str),
+            argument_optional,
+            argument_required,
+            env_var: EcoString::new(),
         });
         result
     }
 
+    /// Whether any option name is written with `=` (`--flag=`, `--flag=VALUE`).
+    fn has_trailing_equals(opt_str: &str) -> bool {
+        opt_str
+            .split([',', '/', '|'])
+            .flat_map(str::split_whitespace)
+            .filter(|word| word.starts_with('-'))
+            .any(|word| {
+                matches!(OptName::split_attached_argument(word), (_, Some(arg)) if !arg.starts_with('['))
+            })
+    }
+
     fn parse_opt_names(s: &str) -> EcoVec<OptName> {
         let mut names = EcoVec::new();
         let mut seen: HashSet<EcoString, foldhash::fast::RandomState> =
             HashSet::with_hasher(foldhash::fast::RandomState::default());
 
-        for part in s.split([',', '/', '|']) {
-            let trimmed = part.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
+        let words = s
+            .split([',', '|'])
+            .flat_map(str::split_whitespace)
+            .flat_map(|word| {
+                // `--verbose/v`: a single character after a long name is its short form
+                let compressed = word.starts_with("--");
+                word.split('/')
+                    .enumerate()
+                    .map(move |(i, token)| (token, compressed && i > 0))
+            });
+
+        for (token, after_long) in words {
+            let name = if after_long && Self::is_compressed_short(token) {
+                Some(OptName::new(
+                    eco_format!("-{}", token),
+                    OptNameType::ShortType,
+                ))
+            } else if token.starts_with('-') {
+                OptName::from_text(token)
+            } else {
+                None
+            };
 
-            for word in trimmed.split_whitespace() {
-                if word.starts_with('-')
-                    && let Some(name) = OptName::from_text(word)
-                {
-                    // Only add if not already seen (deduplicate)
-                    if seen.insert(name.raw.clone()) {
-                        // Insert in sorted order (insertion sort - fast for small N)
-                        let pos = names.iter().position(|n| n > &name).unwrap_or(names.len());
-                        names.insert(pos, name);
-                    }
-                }
+            if let Some(name) = name
+                // Only add if not already seen (deduplicate)
+                && seen.insert(name.raw.clone())
+            {
+                // Insert in sorted order (insertion sort - fast for small N)
+                let pos = names.iter().position(|n| n > &name).unwrap_or(names.len());
+                names.insert(pos, name);
             }
         }
 
         names
     }
 
+    /// The `v` in `--verbose/v`.
+    #[inline]
+    fn is_compressed_short(token: &str) -> bool {
+        let mut chars = token.chars();
+        matches!((chars.next(), chars.next()), (Some(c), None) if c.is_ascii_alphanumeric())
+    }
+
     fn parse_opt_arg(s: &str) -> EcoString {
         for part in s.split([',', '/', '|']) {
             let trimmed = part.trim();
@@ -169,13 +383,34 @@ impl Parser {
         EcoString::new()
     }
 
+    /// Unwrap an optional argument written as `[N]` or `[=N]`, returning whether
+    /// the brackets were present.
+    fn strip_optional_brackets(arg: EcoString) -> (EcoString, bool) {
+        match arg
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            .map(|inner| inner.trim_start_matches('='))
+        {
+            Some(inner) if !inner.is_empty() && !inner.contains(['[', ']']) => {
+                (EcoString::from(inner), true)
+            }
+            _ => (arg, false),
+        }
+    }
+
     fn extract_arg_from_part(s: &str) -> Option<EcoString> {
         let mut words = s.split_whitespace();
-        // Skip first word (the option name)
-        words.next()?;
-
-        // Build arg from remaining words
-        let mut arg = EcoString::new();
+        // The first word is the option name, possibly with an attached
+        // argument (`--output=FILE`, `-o=FILE`, `--color[=WHEN]`)
+        let first = words.next()?;
+        let attached = if first.starts_with('-') {
+            OptName::split_attached_argument(first).1
+        } else {
+            None
+        };
+
+        // Build arg from the attached argument and the remaining words
+        let mut arg = EcoString::from(attached.unwrap_or_default());
         for word in words {
             if !arg.is_empty() {
                 arg.push(' ');
@@ -190,22 +425,132 @@ impl Parser {
         Some(arg)
     }
 
-    pub fn parse_usage_header(keywords: &[&str], block: &str) -> Option<EcoString> {
-        if keywords.is_empty() || block.is_empty() {
-            return None;
+    /// Values listed in a description, such as `Use colors: always, auto, never`,
+    /// `one of: json|yaml|toml` or clap's `[possible values: a, b]`.
+    ///
+    /// Each `:` starts a candidate list running to the next `.`, `;`, `)` or
+    /// `]`. A list needs at least two single-word values separated by commas
+    /// or pipes; a trailing `or`/`and` before the last value is ignored.
+    pub fn parse_possible_values(desc: &str) -> EcoVec<EcoString> {
+        // Fast path: every recognized form has a colon
+        if memchr(b':', desc.as_bytes()).is_none() {
+            return EcoVec::new();
         }
 
-        let header_line = block.lines().next()?.to_lowercase();
-        for keyword in keywords {
-            let pattern = format!(r"^\s*{}\s*:?\s*$", regex::escape(keyword));
-            if let Ok(re) = Regex::new(&pattern)
-                && re.is_match(&header_line)
+        for (pos, _) in desc.match_indices(':') {
+            let rest = &desc[pos + 1..];
+            let end = rest.find(['.', ';', ')', ']', '\n']).unwrap_or(rest.len());
+            let list = &rest[..end];
+            let sep = if list.contains('|') { '|' } else { ',' };
+
+            let values: Option<EcoVec<EcoString>> = list
+                .split(sep)
+                .map(|item| {
+                    let item = item.trim();
+                    let item = item
+                        .strip_prefix("or ")
+                        .or_else(|| item.strip_prefix("and "))
+                        .unwrap_or(item)
+                        .trim_matches(['\'', '"', '`']);
+                    Self::is_possible_value(item).then(|| EcoString::from(item))
+                })
+                .collect();
+
+            if let Some(values) = values
+                && values.len() >= 2
             {
-                return Some(EcoString::from(header_line));
+                return values;
+            }
+        }
+
+        EcoVec::new()
+    }
+
+    #[inline]
+    fn is_possible_value(item: &str) -> bool {
+        item.bytes()
+            .next()
+            .is_some_and(|b| b.is_ascii_alphanumeric())
+            && item
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'+'))
+    }
+
+    /// Collect groups of mutually exclusive options from descriptions such as
+    /// "mutually exclusive with --yaml and --toml".
+    ///
+    /// Each group holds every name of the describing option plus every name of
+    /// the options mentioned after the phrase (names that match no option are
+    /// kept as written); groups sharing a name are merged.
+    pub fn parse_exclusive_groups(options: &[Opt]) -> EcoVec<EcoVec<EcoString>> {
+        static EXCLUSIVE_RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"(?i)mutually[ -]exclusive with\s+(.*)").unwrap());
+        // The dash must start a word, so `non-interactive` is not `-interactive`
+        static NAME_RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"(?:^|[\s(,])(--?[A-Za-z0-9][A-Za-z0-9_-]*)").unwrap());
+
+        let mut groups: Vec<Vec<EcoString>> = Vec::new();
+
+        for opt in options.iter().filter(|opt| !opt.names.is_empty()) {
+            let Some(caps) = EXCLUSIVE_RE.captures(&opt.description) else {
+                continue;
+            };
+
+            let mut group: Vec<EcoString> = opt.names.iter().map(|n| n.raw.clone()).collect();
+            let own_names = group.len();
+            for m in NAME_RE.captures_iter(&caps[1]) {
+                let mentioned = &m[1];
+                let names: Vec<EcoString> = options
+                    .iter()
+                    .find(|other| other.names.iter().any(|n| n.raw == mentioned))
+                    .map_or_else(
+                        || vec![EcoString::from(mentioned)],
+                        |other| other.names.iter().map(|n| n.raw.clone()).collect(),
+                    );
+                for name in names {
+                    if !group.contains(&name) {
+                        group.push(name);
+                    }
+                }
+            }
+            if group.len() == own_names {
+                continue;
             }
+
+            // Merge every existing group that shares a name with this one
+            let mut merged = group;
+            groups.retain(|existing| {
+                if existing.iter().any(|name| merged.contains(name)) {
+                    for name in existing {
+                        if !merged.contains(name) {
+                            merged.push(name.clone());
+                        }
+                    }
+                    false
+                } else {
+                    true
+                }
+            });
+            groups.push(merged);
+        }
+
+        groups.into_iter().map(EcoVec::from).collect()
+    }
+
+    pub fn parse_usage_header(keywords: &[&str], block: &str) -> Option<EcoString> {
+        if keywords.is_empty() || block.is_empty() {
+            return None;
         }
 
-        None
+        // A keyword alone on the line, optionally followed by a colon. Layout
+        // calls this for every line of the help text, so no regex here.
+        let header_line = block.lines().next()?.to_lowercase();
+        let trimmed = header_line.trim();
+        let word = trimmed.strip_suffix(':').unwrap_or(trimmed).trim_end();
+        keywords
+            .iter()
+            .any(|keyword| word.eq_ignore_ascii_case(keyword))
+            .then(|| EcoString::from(header_line))
     }
 }
 
@@ -213,6 +558,281 @@ impl Parser {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_exclusive_groups() {
+        let opt = |raw: &str, desc: &str| Opt {
+            names: ecow::eco_vec![OptName::from_text(raw).unwrap()],
+            description: EcoString::from(desc),
+            ..Default::default()
+        };
+        let options = [
+            opt("--json", "Output JSON. Mutually exclusive with --yaml"),
+            opt("--yaml", "Output YAML"),
+            opt(
+                "--toml",
+                "Output TOML; mutually exclusive with --json and --yaml",
+            ),
+            opt("--verbose", "Be verbose"),
+        ];
+
+        let groups = Parser::parse_exclusive_groups(&options);
+        assert_eq!(groups.len(), 1);
+        let mut group: Vec<&str> = groups[0].iter().map(EcoString::as_str).collect();
+        group.sort_unstable();
+        assert_eq!(group, ["--json", "--toml", "--yaml"]);
+    }
+
+    #[test]
+    fn test_parse_exclusive_groups_uses_every_name() {
+        let opt = |raws: &[&str], desc: &str| Opt {
+            names: raws
+                .iter()
+                .map(|raw| OptName::from_text(raw).unwrap())
+                .collect(),
+            description: EcoString::from(desc),
+            ..Default::default()
+        };
+        let options = [
+            opt(
+                &["-j", "--json"],
+                "Output JSON. Mutually exclusive with --yaml",
+            ),
+            opt(&["-y", "--yaml"], "Output YAML"),
+            opt(&["--batch"], "Mutually exclusive with non-interactive mode"),
+            opt(&["-interactive"], "Prompt"),
+        ];
+
+        let groups = Parser::parse_exclusive_groups(&options);
+        assert_eq!(groups.len(), 1);
+        let mut group: Vec<&str> = groups[0].iter().map(EcoString::as_str).collect();
+        group.sort_unstable();
+        assert_eq!(group, ["--json", "--yaml", "-j", "-y"]);
+    }
+
+    #[test]
+    fn test_optional_bracketed_argument() {
+        for (line, arg) in [
+            ("  --count [N]        Number of repetitions", "N"),
+            ("  --format [FORMAT]  Output format", "FORMAT"),
+        ] {
+            let opts = Parser::parse_line_with_column(line, Some(21));
+            assert_eq!(opts.len(), 1, "{}", line);
+            assert_eq!(opts[0].argument, arg);
+            assert!(opts[0].argument_optional);
+        }
+
+        let required =
+            Parser::parse_line_with_column("  --format FORMAT    Output format", Some(21));
+        assert_eq!(required[0].argument, "FORMAT");
+        assert!(!required[0].argument_optional);
+
+        // Multiple bracket groups are left verbatim
+        let (arg, optional) = Parser::strip_optional_brackets(EcoString::from("[A] [B]"));
+        assert_eq!(arg, "[A] [B]");
+        assert!(!optional);
+    }
+
+    #[test]
+    fn test_small_dedup_keeps_first_occurrence_order() {
+        for capacity in [0, SMALL_DEDUP_MAX, SMALL_DEDUP_MAX + 1] {
+            let mut dedup = SmallDedup::with_capacity(capacity);
+            for n in [3, 1, 3, 2, 1, 4, 5, 6] {
+                dedup.insert(&n);
+            }
+            assert_eq!(dedup.into_items().as_slice(), [3, 1, 2, 4, 5, 6]);
+        }
+    }
+
+    #[test]
+    fn test_parse_possible_values() {
+        let values = |desc: &str| -> Vec<String> {
+            Parser::parse_possible_values(desc)
+                .iter()
+                .map(|v| v.to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            values("Use colors: always, auto, never"),
+            ["always", "auto", "never"]
+        );
+        assert_eq!(
+            values("Output format, one of: json|yaml|toml"),
+            ["json", "yaml", "toml"]
+        );
+        assert_eq!(values("Sort order. Choices: asc, desc."), ["asc", "desc"]);
+        assert_eq!(
+            values("Log level (valid values: debug, info, warn, or error)"),
+            ["debug", "info", "warn", "error"]
+        );
+        assert_eq!(
+            values("Coloring [default: auto] [possible values: always, never, auto]"),
+            ["always", "never", "auto"]
+        );
+        assert_eq!(
+            values("Quote style: 'single', 'double'"),
+            ["single", "double"]
+        );
+
+        assert!(values("No list here").is_empty());
+        assert!(values("Output format: json").is_empty());
+        assert!(values("Note: this flag is deprecated, use --other instead").is_empty());
+
+        let opts = Parser::parse_line_with_column(
+            "  --color <when>          Use colors: always, auto, never",
+            Some(26),
+        );
+        assert_eq!(opts[0].possible_values.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_equals_attached_argument() {
+        let parse = |line: &str| {
+            let opts = Parser::parse_line_with_column(line, Some(25));
+            assert_eq!(opts.len(), 1, "{}", line);
+            let names: Vec<_> = opts[0].names.iter().map(|n| n.raw.to_string()).collect();
+            (
+                names,
+                opts[0].argument.to_string(),
+                opts[0].argument_optional,
+            )
+        };
+
+        assert_eq!(
+            parse("  --output=FILE          Write to FILE"),
+            (vec!["--output".into()], "FILE".into(), false)
+        );
+        assert_eq!(
+            parse("  -o=FILE                Write to FILE"),
+            (vec!["-o".into()], "FILE".into(), false)
+        );
+        assert_eq!(
+            parse("  -o, --output=FILE      Write to FILE"),
+            (vec!["--output".into(), "-o".into()], "FILE".into(), false)
+        );
+        // Empty value and only the first `=` splits
+        assert_eq!(
+            parse("  --opt=                 Set opt"),
+            (vec!["--opt".into()], String::new(), false)
+        );
+        assert_eq!(
+            parse("  --opt=a=b              Set opt"),
+            (vec!["--opt".into()], "a=b".into(), false)
+        );
+        // Attached optional argument
+        assert_eq!(
+            parse("  --color[=WHEN]         Colorize output"),
+            (vec!["--color".into()], "WHEN".into(), true)
+        );
+    }
+
+    #[test]
+    fn test_parse_ripgrep_style_help() {
+        let help = "\
+OPTIONS:
+    -A NUM, --after-context=NUM
+        Show NUM lines after each match.
+
+    --color=WHEN
+        Controls when to use color.
+
+    -e PATTERN, --regexp=PATTERN
+        A pattern to search for.
+
+    --sort=SORTBY
+        Sort results in ascending order.
+";
+        let opts = crate::layout::Layout::parse_blockwise(help);
+        let find = |name: &str| {
+            opts.iter()
+                .find(|opt| opt.names.iter().any(|n| n.raw == name))
+                .unwrap_or_else(|| panic!("{} not parsed: {:?}", name, opts))
+        };
+
+        let after = find("--after-context");
+        assert_eq!(after.names.len(), 2);
+        assert_eq!(after.names[1].raw, "-A");
+        assert_eq!(after.argument, "NUM");
+        assert_eq!(find("--color").argument, "WHEN");
+        assert_eq!(find("-e").argument, "PATTERN");
+        assert_eq!(find("--sort").argument, "SORTBY");
+        assert!(
+            opts.iter()
+                .flat_map(|opt| opt.names.iter())
+                .all(|n| !n.raw.contains('='))
+        );
+    }
+
+    #[test]
+    fn test_parse_compressed_short_form() {
+        let opts = Parser::parse_line_with_column("  --verbose/v        Be verbose", Some(21));
+        assert_eq!(opts.len(), 1);
+        let names: Vec<_> = opts[0]
+            .names
+            .iter()
+            .map(|n| (n.raw.as_str(), n.opt_type))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("--verbose", OptNameType::LongType),
+                ("-v", OptNameType::ShortType)
+            ]
+        );
+
+        let opts = Parser::parse_line_with_column("  --output/o FILE    Write to FILE", Some(21));
+        assert_eq!(opts[0].names.len(), 2);
+        assert_eq!(opts[0].names[1].raw, "-o");
+        assert_eq!(opts[0].argument, "FILE");
+
+        // Only a single character after a long name is a short form
+        let opts = Parser::parse_line_with_column("  --color/colour     Use color", Some(21));
+        assert_eq!(opts[0].names.len(), 1);
+    }
+
+    #[test]
+    fn test_trailing_equals_marks_argument_required() {
+        let opts = Parser::parse_line_with_column("  --flag=           Set the flag", Some(21));
+        assert_eq!(opts[0].names[0].raw, "--flag");
+        assert!(opts[0].argument_required);
+
+        let opts = Parser::parse_line_with_column("  --flag=VALUE      Set the flag", Some(21));
+        assert_eq!(opts[0].names[0].raw, "--flag");
+        assert!(opts[0].argument_required);
+
+        let opts = Parser::parse_line_with_column("  --flag            Set the flag", Some(21));
+        assert_eq!(opts[0].names[0].raw, "--flag");
+        assert!(!opts[0].argument_required);
+    }
+
+    #[test]
+    fn test_split_at_column() {
+        let line = "  -v, --verbose         Enable verbose output";
+        assert_eq!(
+            Parser::split_at_column(line, 24),
+            ("  -v, --verbose         ", "Enable verbose output")
+        );
+        assert_eq!(Parser::split_at_column(line, 200), (line, ""));
+        assert_eq!(Parser::split_at_column("  -q  ", 4), ("  -q", ""));
+    }
+
+    #[test]
+    fn test_preprocess_single_matches_preprocess() {
+        for line in [
+            "  -a, --all  show all",
+            "-v, --verbose         be chatty",
+            "  --output=FILE  write to FILE",
+            "  -b",
+            "  not an option",
+            "",
+        ] {
+            let single = Parser::preprocess_single(line);
+            let multi = Parser::preprocess(line);
+            assert_eq!(single.as_ref(), multi.first(), "line: {line:?}");
+            assert!(multi.len() <= 1);
+        }
+    }
+
     #[test]
     fn test_preprocess_same_and_next_line_descriptions() {
         let input = "  -a, --all  show all\n  -b\n    show b";
@@ -226,11 +846,44 @@ mod tests {
         assert_eq!(pairs[1].1.as_str(), "show b");
     }
 
+    #[test]
+    fn test_preprocess_with_column_splits_argument_from_description() {
+        let input = "  -o, --output FILE      Output BAM file\n  --min-mapq INT         Minimum mapping quality\n  --a-very-long-option-name  Overflowing\n  -q\n    quiet";
+        let pairs = Parser::preprocess_with_column(input, Some(25));
+        assert_eq!(pairs.len(), 4);
+        assert_eq!(pairs[0].0.as_str(), "-o, --output FILE");
+        assert_eq!(pairs[0].1.as_str(), "Output BAM file");
+        assert_eq!(pairs[1].0.as_str(), "--min-mapq INT");
+        assert_eq!(pairs[2].0.as_str(), "--a-very-long-option-name");
+        assert_eq!(pairs[2].1.as_str(), "Overflowing");
+        assert_eq!(pairs[3].0.as_str(), "-q");
+        assert_eq!(pairs[3].1.as_str(), "quiet");
+
+        let wrapped = "  -o, --output FILE      Output BAM file,\n                         written in place\n  -q\n    be\n    quiet\n  -v  Verbose\n";
+        let pairs = Parser::preprocess_with_column(wrapped, Some(25));
+        assert_eq!(pairs.len(), 3);
+        assert_eq!(pairs[0].1.as_str(), "Output BAM file, written in place");
+        assert_eq!(pairs[1].1.as_str(), "be quiet");
+        assert_eq!(pairs[2].0.as_str(), "-v");
+
+        assert_eq!(
+            Parser::preprocess_with_column(input, None),
+            Parser::preprocess(input)
+        );
+    }
+
     #[test]
     fn test_parse_usage_header_matches_keywords() {
         let block = "Usage:\n  cmd [OPTIONS]\n";
         let header = Parser::parse_usage_header(&["usage"], block).unwrap();
         assert!(header.contains("usage"));
+
+        for block in ["  SYNOPSIS", "synopsis :", "Usage"] {
+            assert!(Parser::parse_usage_header(&["usage", "synopsis"], block).is_some());
+        }
+        for block in ["Usage of this tool", "usages:", ":"] {
+            assert!(Parser::parse_usage_header(&["usage", "synopsis"], block).is_none());
+        }
     }
 
     #[test]
@@ -1,20 +1,219 @@
-use crate::types::{Command, Opt, OptName};
+use crate::layout::Layout;
+use crate::types::{Command, Opt, OptName, OptNameType};
+use crate::util::levenshtein;
 use bstr::ByteSlice;
-use ecow::{EcoString, EcoVec};
+use ecow::{EcoString, EcoVec, eco_format};
 use memchr::memchr;
+use regex::Regex;
+use std::borrow::Cow;
 use std::collections::HashSet;
+use std::sync::LazyLock;
+use tracing::{info, instrument, warn};
+
+/// Maximum subcommand nesting processed by [`Postprocessor::fix_command`]
+const MAX_FIX_DEPTH: usize = 64;
+
+/// Name given to commands whose name is empty
+const UNNAMED_COMMAND: &str = "unnamed";
+
+/// Descriptions closer than this edit distance count as the same option text
+/// in [`Postprocessor::merge_aliases`]
+const ALIAS_MAX_DESC_DISTANCE: usize = 5;
+
+/// Longest `-abc` style name [`Postprocessor::split_combined_short_opts`]
+/// splits into short options; longer ones are more likely real old-style names.
+const MAX_COMBINED_SHORT_OPTS: usize = 4;
+
+/// `$NAME` references in option descriptions, as in "Overrides $EDITOR".
+static ENV_VAR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$([A-Z][A-Z0-9_]{2,})").expect("valid env var regex"));
+
+/// Options controlling how [`Postprocessor::fix_command_with_config`] cleans up a command.
+#[derive(Debug, Clone, Copy)]
+pub struct PostprocessorConfig {
+    /// Keep options that have names but no description.
+    pub allow_empty_descriptions: bool,
+    /// Drop options without names or descriptions. When `false`, every parsed
+    /// option is kept.
+    pub filter_invalid: bool,
+    /// Merge options with identical names and argument.
+    pub dedup: bool,
+    /// Rewrite the root command's name with [`Postprocessor::normalize_command_name`].
+    pub normalize_name: bool,
+    /// Split combined short options such as `-abc` with
+    /// [`Postprocessor::split_combined_short_opts`].
+    pub split_combined: bool,
+}
+
+impl Default for PostprocessorConfig {
+    fn default() -> Self {
+        Self {
+            allow_empty_descriptions: false,
+            filter_invalid: true,
+            dedup: true,
+            normalize_name: true,
+            split_combined: false,
+        }
+    }
+}
 
 pub struct Postprocessor;
 
 impl Postprocessor {
-    pub fn fix_command(mut cmd: Command) -> Command {
-        cmd.options = Self::deduplicate_options(cmd.options);
-        cmd.options = Self::filter_invalid_options(cmd.options);
-        cmd.subcommands = cmd.subcommands.into_iter().map(Self::fix_command).collect();
+    pub fn fix_command(cmd: Command) -> Command {
+        Self::fix_command_with_config(cmd, &PostprocessorConfig::default())
+    }
+
+    #[instrument(level = "debug", skip_all, fields(name = %cmd.name))]
+    pub fn fix_command_with_config(cmd: Command, config: &PostprocessorConfig) -> Command {
+        Self::fix_command_inner(cmd, config, 0)
+    }
+
+    fn fix_command_inner(mut cmd: Command, config: &PostprocessorConfig, depth: usize) -> Command {
+        if depth >= MAX_FIX_DEPTH {
+            warn!(
+                "Subcommand nesting exceeds {} levels at '{}', leaving it unprocessed",
+                MAX_FIX_DEPTH, cmd.name
+            );
+            return cmd;
+        }
+
+        if cmd.name.trim().is_empty() {
+            // Generators interpolate the name into function names and `complete -c`
+            warn!("Command has empty name, using '{}'", UNNAMED_COMMAND);
+            cmd.name = EcoString::from(UNNAMED_COMMAND);
+        } else if depth == 0 && config.normalize_name {
+            let normalized = Self::normalize_command_name(&cmd.name);
+            if normalized != cmd.name {
+                info!("Normalized command name '{}' to '{}'", cmd.name, normalized);
+                cmd.name = EcoString::from(normalized);
+            }
+        }
+        if depth == 0 {
+            Self::reconcile_version(&mut cmd);
+        }
+        if config.split_combined {
+            cmd.options = Self::split_combined_short_opts(cmd.options);
+        }
+        if config.dedup {
+            cmd.options = Self::merge_aliases(Self::deduplicate_options(cmd.options));
+        }
+        if !config.filter_invalid {
+            // Keep every parsed option
+        } else if config.allow_empty_descriptions {
+            cmd.options = Self::filter_invalid_options_with_pred(cmd.options, Self::has_name);
+        } else {
+            cmd.options = Self::filter_invalid_options(cmd.options);
+        }
+        cmd.options = Self::detect_env_from_description(cmd.options);
+        cmd.subcommands = cmd
+            .subcommands
+            .into_iter()
+            .map(|sub| Self::fix_command_inner(sub, config, depth + 1))
+            .collect();
 
         cmd
     }
 
+    /// Lowercase `name` and turn underscores and spaces into hyphens, so
+    /// `My_Tool` and `my tool` both become `my-tool`.
+    pub fn normalize_command_name(name: &str) -> String {
+        name.trim()
+            .chars()
+            .map(|c| match c {
+                '_' | ' ' => '-',
+                c => c.to_ascii_lowercase(),
+            })
+            .collect()
+    }
+
+    /// Fill an empty `version` of the top-level command from the version
+    /// mentioned in its description, and warn when an existing version (e.g.
+    /// loaded from JSON or `--version` output) disagrees with it.
+    fn reconcile_version(cmd: &mut Command) {
+        let Some(found) = Layout::parse_version(&cmd.description) else {
+            return;
+        };
+
+        if cmd.version.is_empty() {
+            info!(
+                "Populated version {} for '{}' from its description",
+                found, cmd.name
+            );
+            cmd.version = found;
+        } else if Layout::parse_version(&cmd.version).is_some_and(|version| version != found) {
+            warn!(
+                "Version {} of '{}' differs from version {} in its description",
+                cmd.version, cmd.name, found
+            );
+        }
+    }
+
+    /// Split old-style names made of up to [`MAX_COMBINED_SHORT_OPTS`] letters,
+    /// such as `-abc` in `-abc, -xyz  do multiple things`, into one `-a`, `-b`,
+    /// `-c` option each. Options keep any names that cannot be split.
+    pub fn split_combined_short_opts(options: EcoVec<Opt>) -> EcoVec<Opt> {
+        let is_combined = |name: &OptName| {
+            let letters = &name.raw[1..];
+            name.opt_type == OptNameType::OldType
+                && (2..=MAX_COMBINED_SHORT_OPTS).contains(&letters.len())
+                && letters.bytes().all(|b| b.is_ascii_alphabetic())
+        };
+
+        let mut result = EcoVec::with_capacity(options.len());
+        for opt in options {
+            if !opt.names.iter().any(is_combined) {
+                result.push(opt);
+                continue;
+            }
+
+            let mut rest = opt.clone();
+            rest.names = opt
+                .names
+                .iter()
+                .filter(|n| !is_combined(n))
+                .cloned()
+                .collect();
+            for name in opt.names.iter().filter(|n| is_combined(n)) {
+                for letter in name.raw[1..].chars() {
+                    let mut split = opt.clone();
+                    split.names = EcoVec::from([OptName::new(
+                        eco_format!("-{}", letter),
+                        OptNameType::ShortType,
+                    )]);
+                    result.push(split);
+                }
+            }
+            if !rest.names.is_empty() {
+                result.push(rest);
+            }
+        }
+        result
+    }
+
+    /// Fill `env_var` from `$NAME` references in each description, such as
+    /// "Overrides $EDITOR" or "[$MYAPP_CONFIG]". The last reference wins, as
+    /// that is usually the override. Options with an `env_var` are kept as is.
+    pub fn detect_env_from_description(mut options: EcoVec<Opt>) -> EcoVec<Opt> {
+        // SIMD fast path: most descriptions mention no variables at all
+        let mentions_env = |opt: &Opt| {
+            opt.env_var.is_empty() && memchr(b'$', opt.description.as_bytes()).is_some()
+        };
+        if !options.iter().any(mentions_env) {
+            return options;
+        }
+
+        for opt in options.make_mut() {
+            if !mentions_env(opt) {
+                continue;
+            }
+            if let Some(caps) = ENV_VAR_RE.captures_iter(&opt.description).last() {
+                opt.env_var = EcoString::from(&caps[1]);
+            }
+        }
+        options
+    }
+
     fn deduplicate_options(options: EcoVec<Opt>) -> EcoVec<Opt> {
         // Deduplicate based on (names, argument) - description is not part of the key
         let mut seen: HashSet<(EcoVec<OptName>, EcoString), foldhash::fast::RandomState> =
@@ -34,26 +233,72 @@ impl Postprocessor {
         result
     }
 
+    /// Merge options that share a name and whose descriptions differ by fewer
+    /// than [`ALIAS_MAX_DESC_DISTANCE`] edits, e.g. `--colour, --color` parsed
+    /// once more as `--color` alone. The merged option keeps every name.
+    pub fn merge_aliases(options: EcoVec<Opt>) -> EcoVec<Opt> {
+        let mut result: EcoVec<Opt> = EcoVec::with_capacity(options.len());
+
+        for opt in options.into_iter() {
+            let alias_of = result.iter().position(|existing| {
+                existing
+                    .names
+                    .iter()
+                    .any(|name| opt.names.iter().any(|n| n.raw == name.raw))
+                    && levenshtein(&existing.description, &opt.description)
+                        < ALIAS_MAX_DESC_DISTANCE
+            });
+
+            let Some(idx) = alias_of else {
+                result.push(opt);
+                continue;
+            };
+
+            let merged = &mut result.make_mut()[idx];
+            for name in opt.names.iter() {
+                if !merged.names.iter().any(|n| n.raw == name.raw) {
+                    merged.names.push(name.clone());
+                }
+            }
+            if merged.argument.is_empty() {
+                merged.argument = opt.argument;
+            }
+        }
+
+        result
+    }
+
     fn filter_invalid_options(options: EcoVec<Opt>) -> EcoVec<Opt> {
-        options
-            .into_iter()
-            .filter(|opt| {
-                !opt.names.is_empty() && !opt.names[0].raw.is_empty() && !opt.description.is_empty()
-            })
-            .collect()
+        Self::filter_invalid_options_with_pred(options, |opt| {
+            Self::has_name(opt) && !opt.description.is_empty()
+        })
+    }
+
+    /// Keep only the options for which `pred` returns `true`.
+    pub fn filter_invalid_options_with_pred<F: Fn(&Opt) -> bool>(
+        options: EcoVec<Opt>,
+        pred: F,
+    ) -> EcoVec<Opt> {
+        options.into_iter().filter(|opt| pred(opt)).collect()
+    }
+
+    fn has_name(opt: &Opt) -> bool {
+        !opt.names.is_empty() && !opt.names[0].raw.is_empty()
     }
 
-    pub fn remove_bullets(text: &str) -> EcoString {
+    pub fn remove_bullets(text: &str) -> Cow<'_, str> {
         let bytes = text.as_bytes();
 
         // SIMD fast path: check if any bullet characters exist
-        // Bullets we care about: '*' (0x2A), '-' (0x2D), '•' (0xE2 0x80 0xA2)
+        // Bullets we care about: '*' (0x2A), '-' (0x2D), '•' (0xE2 0x80 0xA2),
+        // and numbered items like "1. " which always contain a '.'
         let has_asterisk = memchr(b'*', bytes).is_some();
         let has_dash = memchr(b'-', bytes).is_some();
         let has_bullet_utf8 = memchr(0xE2, bytes).is_some();
+        let has_period = memchr(b'.', bytes).is_some();
 
-        if !has_asterisk && !has_dash && !has_bullet_utf8 {
-            return EcoString::from(text);
+        if !has_asterisk && !has_dash && !has_bullet_utf8 && !has_period {
+            return Cow::Borrowed(text);
         }
 
         // Pre-allocate with capacity hint
@@ -75,22 +320,24 @@ impl Postprocessor {
 
             // Fast path: check first byte for bullet characters
             if trimmed_bytes.len() >= 2 {
-                let is_bullet = match trimmed_bytes[0] {
-                    b'*' | b'-' => trimmed_bytes[1].is_ascii_whitespace(),
+                // Length of the bullet plus the whitespace byte after it
+                let skip = match trimmed_bytes[0] {
+                    b'*' | b'-' if trimmed_bytes[1].is_ascii_whitespace() => Some(2),
                     // UTF-8 bullet point (•) starts with 0xE2
                     0xE2 if trimmed_bytes.len() >= 4
                         && trimmed_bytes[1] == 0x80
-                        && trimmed_bytes[2] == 0xA2 =>
+                        && trimmed_bytes[2] == 0xA2
+                        && trimmed_bytes[3].is_ascii_whitespace() =>
                     {
-                        trimmed_bytes[3].is_ascii_whitespace()
+                        Some(4)
                     }
-                    _ => false,
+                    b'0'..=b'9' => Self::numbered_prefix_len(trimmed_bytes),
+                    _ => None,
                 };
 
-                if is_bullet {
+                if let Some(skip) = skip {
                     result.push_str(&line_str[..prefix_len]);
                     // Skip bullet and whitespace
-                    let skip = if trimmed_bytes[0] == 0xE2 { 4 } else { 2 };
                     result.push_str(trimmed[skip..].trim_start());
                     continue;
                 }
@@ -98,10 +345,60 @@ impl Postprocessor {
             result.push_str(line_str);
         }
 
-        EcoString::from(result)
+        Cow::Owned(result)
+    }
+
+    /// Length of a `N. ` numbered list prefix (digits, period, one whitespace byte).
+    fn numbered_prefix_len(bytes: &[u8]) -> Option<usize> {
+        let digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+        match bytes.get(digits..digits + 2) {
+            Some([b'.', ws]) if digits > 0 && ws.is_ascii_whitespace() => Some(digits + 2),
+            _ => None,
+        }
+    }
+
+    /// Drop every `\r` from text with Windows (`\r\n`) line endings, so
+    /// option names never end in a stray carriage return.
+    pub fn strip_carriage_returns(text: &str) -> Cow<'_, str> {
+        // SIMD fast path: no carriage return at all
+        if memchr(b'\r', text.as_bytes()).is_none()
+            || memchr::memmem::find(text.as_bytes(), b"\r\n").is_none()
+        {
+            return Cow::Borrowed(text);
+        }
+
+        Cow::Owned(text.replace('\r', ""))
     }
 
-    pub fn unicode_spaces_to_ascii(text: &str) -> EcoString {
+    /// Strip overstrike formatting left in man output: `c\x08c` (bold) and
+    /// `_\x08c` (underline) collapse to `c`, and any other backspace is dropped.
+    pub fn fix_overstrike(text: &str) -> Cow<'_, str> {
+        // SIMD fast path: no backspace, nothing to undo
+        if memchr(0x08, text.as_bytes()).is_none() {
+            return Cow::Borrowed(text);
+        }
+
+        let mut result = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '\x08' {
+                result.push(c);
+                continue;
+            }
+
+            // The overstruck character is pushed again on the next iteration
+            if let (Some(&next), Some(prev)) = (chars.peek(), result.chars().next_back())
+                && (prev == next || prev == '_')
+            {
+                result.pop();
+            }
+        }
+
+        Cow::Owned(result)
+    }
+
+    pub fn unicode_spaces_to_ascii(text: &str) -> Cow<'_, str> {
         let bytes = text.as_bytes();
 
         // SIMD fast path: scan for any high bytes using memchr
@@ -112,7 +409,7 @@ impl Postprocessor {
             && memchr::memchr(0xE2, bytes).is_none()
         {
             // Pure ASCII - no unicode spaces possible
-            return EcoString::from(text);
+            return Cow::Borrowed(text);
         }
 
         // Check if any of our target characters exist using a single pass
@@ -124,7 +421,7 @@ impl Postprocessor {
         });
 
         if !has_targets {
-            return EcoString::from(text);
+            return Cow::Borrowed(text);
         }
 
         // Pre-allocate result
@@ -140,15 +437,15 @@ impl Postprocessor {
             }
         }
 
-        EcoString::from(result)
+        Cow::Owned(result)
     }
 
-    pub fn convert_tabs_to_spaces(text: &str, spaces: usize) -> EcoString {
+    pub fn convert_tabs_to_spaces(text: &str, spaces: usize) -> Cow<'_, str> {
         // SIMD fast path: use memchr to check for tabs
         if memchr(b'\t', text.as_bytes()).is_none() {
-            return EcoString::from(text);
+            return Cow::Borrowed(text);
         }
-        EcoString::from(text.replace('\t', &" ".repeat(spaces)))
+        Cow::Owned(text.replace('\t', &" ".repeat(spaces)))
     }
 }
 
@@ -156,9 +453,193 @@ impl Postprocessor {
 mod tests {
     use super::*;
     use crate::OptName;
+    use crate::parser::Parser;
     use crate::types::OptNameType;
     use ecow::EcoString;
 
+    #[test]
+    fn test_fix_command_populates_version_from_description() {
+        let mut cmd = Command::new(EcoString::from("tool"));
+        cmd.description = EcoString::from("tool 1.4.2 - does things");
+
+        let fixed = Postprocessor::fix_command(cmd);
+        assert_eq!(fixed.version, "1.4.2");
+    }
+
+    #[test]
+    fn test_fix_command_keeps_existing_version() {
+        let mut cmd = Command::new(EcoString::from("tool"));
+        cmd.description = EcoString::from("tool 1.4.2 - does things");
+        cmd.version = EcoString::from("2.0.0");
+
+        let fixed = Postprocessor::fix_command(cmd);
+        assert_eq!(fixed.version, "2.0.0");
+    }
+
+    #[test]
+    fn test_fix_command_reconciles_version_only_at_top_level() {
+        let mut sub = Command::new(EcoString::from("build"));
+        sub.description = EcoString::from("Build with gcc 12.1");
+        let mut cmd = Command::new(EcoString::from("tool"));
+        cmd.description = EcoString::from("tool 1.4.2 - does things");
+        cmd.subcommands.push(sub);
+
+        let fixed = Postprocessor::fix_command(cmd);
+        assert_eq!(fixed.version, "1.4.2");
+        assert!(fixed.subcommands[0].version.is_empty());
+    }
+
+    #[test]
+    fn test_detect_env_from_description() {
+        let opt = |desc: &str| Opt {
+            names: EcoVec::from([OptName::new(
+                EcoString::from("--opt"),
+                OptNameType::LongType,
+            )]),
+            description: EcoString::from(desc),
+            ..Default::default()
+        };
+
+        let cases = [
+            ("Editor to use. Overrides $EDITOR", "EDITOR"),
+            ("Config directory. See also: $HOME", "HOME"),
+            ("Path to the config file [$MYAPP_CONFIG]", "MYAPP_CONFIG"),
+            (
+                "Defaults to $VISUAL, overridden by $GIT_EDITOR",
+                "GIT_EDITOR",
+            ),
+            ("Costs $5 per run", ""),
+            ("Lowercase $home and short $AB are ignored", ""),
+            ("No variables here", ""),
+        ];
+        let options = cases.iter().map(|(desc, _)| opt(desc)).collect();
+        let detected = Postprocessor::detect_env_from_description(options);
+        for (opt, (desc, expected)) in detected.iter().zip(cases) {
+            assert_eq!(opt.env_var, expected, "{}", desc);
+        }
+
+        let mut explicit = opt("Overrides $EDITOR");
+        explicit.env_var = EcoString::from("MY_EDITOR");
+        let kept = Postprocessor::detect_env_from_description(EcoVec::from([explicit]));
+        assert_eq!(kept[0].env_var, "MY_EDITOR");
+    }
+
+    #[test]
+    fn test_split_combined_short_opts() {
+        let opt = |names: &[(&str, OptNameType)]| Opt {
+            names: names
+                .iter()
+                .map(|&(raw, opt_type)| OptName::new(EcoString::from(raw), opt_type))
+                .collect(),
+            description: EcoString::from("do multiple things"),
+            ..Default::default()
+        };
+
+        let split = Postprocessor::split_combined_short_opts(EcoVec::from([opt(&[
+            ("-abc", OptNameType::OldType),
+            ("-xyz", OptNameType::OldType),
+        ])]));
+        let names: Vec<_> = split.iter().map(|o| o.names[0].raw.as_str()).collect();
+        assert_eq!(names, ["-a", "-b", "-c", "-x", "-y", "-z"]);
+        assert!(split.iter().all(|o| o.names.len() == 1
+            && o.names[0].opt_type == OptNameType::ShortType
+            && o.description == "do multiple things"));
+
+        // Long old-style names and other name types are kept as they are
+        let kept = EcoVec::from([
+            opt(&[("-print", OptNameType::OldType)]),
+            opt(&[
+                ("-v", OptNameType::ShortType),
+                ("--verbose", OptNameType::LongType),
+            ]),
+        ]);
+        assert_eq!(Postprocessor::split_combined_short_opts(kept.clone()), kept);
+
+        // Unsplittable names stay on the original option
+        let mixed = Postprocessor::split_combined_short_opts(EcoVec::from([opt(&[
+            ("-ab", OptNameType::OldType),
+            ("--all-both", OptNameType::LongType),
+        ])]));
+        assert_eq!(mixed.len(), 3);
+        assert_eq!(mixed[2].names[0].raw, "--all-both");
+
+        // Off by default
+        let mut cmd = Command::new(EcoString::from("test"));
+        cmd.options.push(opt(&[("-abc", OptNameType::OldType)]));
+        assert_eq!(Postprocessor::fix_command(cmd.clone()).options.len(), 1);
+        let config = PostprocessorConfig {
+            split_combined: true,
+            ..PostprocessorConfig::default()
+        };
+        assert_eq!(
+            Postprocessor::fix_command_with_config(cmd, &config)
+                .options
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_normalize_command_name() {
+        assert_eq!(Postprocessor::normalize_command_name("myTool"), "mytool");
+        assert_eq!(Postprocessor::normalize_command_name("MyTool"), "mytool");
+        assert_eq!(Postprocessor::normalize_command_name("my_tool"), "my-tool");
+        assert_eq!(Postprocessor::normalize_command_name("My Tool"), "my-tool");
+        assert_eq!(Postprocessor::normalize_command_name("my-tool"), "my-tool");
+
+        let mut cmd = Command::new(EcoString::from("My_Tool"));
+        cmd.subcommands
+            .push(Command::new(EcoString::from("Sub_Cmd")));
+        let fixed = Postprocessor::fix_command(cmd.clone());
+        assert_eq!(fixed.name, "my-tool");
+        // Subcommand names are what users type, so they are left alone
+        assert_eq!(fixed.subcommands[0].name, "Sub_Cmd");
+
+        let config = PostprocessorConfig {
+            normalize_name: false,
+            ..PostprocessorConfig::default()
+        };
+        assert_eq!(
+            Postprocessor::fix_command_with_config(cmd, &config).name,
+            "My_Tool"
+        );
+    }
+
+    #[test]
+    fn test_fix_command_names_empty_commands() {
+        let mut cmd = Command::new(EcoString::new());
+        cmd.subcommands.push(Command::new(EcoString::from("  ")));
+
+        let fixed = Postprocessor::fix_command(cmd);
+        assert_eq!(fixed.name, "unnamed");
+        assert_eq!(fixed.subcommands[0].name, "unnamed");
+    }
+
+    #[test]
+    fn test_merge_aliases() {
+        let opt = |names: &[&str], desc: &str| Opt {
+            names: names
+                .iter()
+                .map(|n| OptName::from_text(n).unwrap())
+                .collect(),
+            description: EcoString::from(desc),
+            ..Default::default()
+        };
+
+        let merged = Postprocessor::merge_aliases(ecow::eco_vec![
+            opt(&["--colour"], "set colour output"),
+            opt(&["--colour", "--color"], "set color output"),
+            opt(&["-v", "--verbose"], "be verbose"),
+            opt(&["-v"], "print the version and exit"),
+        ]);
+
+        assert_eq!(merged.len(), 3);
+        let names: Vec<&str> = merged[0].names.iter().map(|n| n.raw.as_str()).collect();
+        assert_eq!(names, ["--colour", "--color"]);
+        assert_eq!(merged[0].description, "set colour output");
+        assert_eq!(merged[2].description, "print the version and exit");
+    }
+
     #[test]
     fn test_deduplicate_options() {
         let mut opts = EcoVec::new();
@@ -168,8 +649,8 @@ mod tests {
                 v.push(OptName::new(EcoString::from("-v"), OptNameType::ShortType));
                 v
             },
-            argument: EcoString::new(),
             description: EcoString::from("verbose"),
+            ..Default::default()
         });
         opts.push(Opt {
             names: {
@@ -177,8 +658,8 @@ mod tests {
                 v.push(OptName::new(EcoString::from("-v"), OptNameType::ShortType));
                 v
             },
-            argument: EcoString::new(),
             description: EcoString::from("verbose"),
+            ..Default::default()
         });
 
         let result = Postprocessor::deduplicate_options(opts);
@@ -192,6 +673,62 @@ mod tests {
         assert!(!result.contains("•"));
     }
 
+    #[test]
+    fn test_remove_bullets_numbered_items() {
+        let text = "1. First item\n  2. Second item\n10.  Tenth item";
+        let result = Postprocessor::remove_bullets(text);
+        assert_eq!(result, "First item\n  Second item\nTenth item");
+
+        // Decimal numbers and bare periods are left alone
+        let text = "1.5 seconds\n3.\nv2. not a list";
+        assert_eq!(Postprocessor::remove_bullets(text), text);
+    }
+
+    #[test]
+    fn test_strip_carriage_returns() {
+        assert_eq!(
+            Postprocessor::strip_carriage_returns("a\r\nb\r\n"),
+            "a\nb\n"
+        );
+        // Lone carriage returns without CRLF endings are left alone
+        assert!(matches!(
+            Postprocessor::strip_carriage_returns("a\rb\n"),
+            Cow::Borrowed("a\rb\n")
+        ));
+        assert!(matches!(
+            Postprocessor::strip_carriage_returns("a\nb"),
+            Cow::Borrowed(_)
+        ));
+
+        let help = "Options:\r\n  --verbose\r\n      Be verbose\r\n";
+        let content = Postprocessor::strip_carriage_returns(help);
+        let opts = Parser::parse_line(&content);
+        assert_eq!(opts.len(), 1);
+        assert_eq!(opts[0].names[0].raw, "--verbose");
+        assert_eq!(opts[0].description, "Be verbose");
+    }
+
+    #[test]
+    fn test_fix_overstrike() {
+        // Bold: each character struck over itself, possibly more than once
+        assert_eq!(Postprocessor::fix_overstrike("b\x08bo\x08ol\x08ld"), "bold");
+        assert_eq!(Postprocessor::fix_overstrike("-\x08-\x08-v"), "-v");
+        // Underline
+        assert_eq!(
+            Postprocessor::fix_overstrike("_\x08f_\x08i_\x08l_\x08e"),
+            "file"
+        );
+        // Multi-byte characters
+        assert_eq!(Postprocessor::fix_overstrike("é\x08é•\x08• ü"), "é• ü");
+        assert_eq!(Postprocessor::fix_overstrike("_\x08ñ"), "ñ");
+        // Bare backspaces are dropped
+        assert_eq!(Postprocessor::fix_overstrike("a\x08b\x08"), "ab");
+        assert!(matches!(
+            Postprocessor::fix_overstrike("plain"),
+            Cow::Borrowed(_)
+        ));
+    }
+
     #[test]
     fn test_unicode_and_tabs_helpers() {
         // Text with various unicode spaces and a tab
@@ -199,11 +736,65 @@ mod tests {
         let ascii = Postprocessor::unicode_spaces_to_ascii(text);
 
         // Non-breaking/en-space/em-space should be replaced with ASCII spaces
-        assert_eq!(ascii.as_str(), " foo  bar   baz\tend");
+        assert_eq!(&*ascii, " foo  bar   baz\tend");
 
         let with_spaces = Postprocessor::convert_tabs_to_spaces(&ascii, 4);
         assert!(!with_spaces.contains('\t'));
         assert!(with_spaces.ends_with("    end"));
+
+        // Clean input is borrowed rather than copied
+        let clean = "plain ascii text";
+        assert!(matches!(
+            Postprocessor::unicode_spaces_to_ascii(clean),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            Postprocessor::convert_tabs_to_spaces(clean, 4),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(
+            Postprocessor::remove_bullets(clean),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_allow_empty_descriptions_keeps_undocumented_options() {
+        let mut cmd = Command::new(EcoString::from("root"));
+        cmd.options.push(Opt {
+            names: {
+                let mut v = EcoVec::new();
+                v.push(OptName::new(EcoString::from("-q"), OptNameType::ShortType));
+                v
+            },
+            ..Default::default()
+        });
+        cmd.options.push(Opt {
+            names: EcoVec::new(),
+            description: EcoString::from("nameless"),
+            ..Default::default()
+        });
+
+        let strict = Postprocessor::fix_command(cmd.clone());
+        assert!(strict.options.is_empty());
+
+        let config = PostprocessorConfig {
+            allow_empty_descriptions: true,
+            ..PostprocessorConfig::default()
+        };
+        let lenient = Postprocessor::fix_command_with_config(cmd.clone(), &config);
+        assert_eq!(lenient.options.len(), 1);
+        assert_eq!(lenient.options[0].names[0].raw, "-q");
+
+        let config = PostprocessorConfig {
+            filter_invalid: false,
+            dedup: false,
+            ..PostprocessorConfig::default()
+        };
+        let mut duplicated = cmd.clone();
+        duplicated.options.push(cmd.options[0].clone());
+        let unfiltered = Postprocessor::fix_command_with_config(duplicated, &config);
+        assert_eq!(unfiltered.options.len(), 3);
     }
 
     #[test]
@@ -214,20 +805,17 @@ mod tests {
                 v.push(OptName::new(EcoString::from("-v"), OptNameType::ShortType));
                 v
             },
-            argument: EcoString::new(),
             description: EcoString::from("verbose"),
+            ..Default::default()
         };
 
         let invalid_opt = Opt {
             names: EcoVec::new(),
-            argument: EcoString::new(),
-            description: EcoString::new(),
+            ..Default::default()
         };
 
         let cmd = Command {
             name: EcoString::from("root"),
-            description: EcoString::new(),
-            usage: EcoString::new(),
             options: {
                 let mut v = EcoVec::new();
                 v.push(valid_opt.clone());
@@ -239,19 +827,16 @@ mod tests {
                 let mut v = EcoVec::new();
                 v.push(Command {
                     name: EcoString::from("child"),
-                    description: EcoString::new(),
-                    usage: EcoString::new(),
                     options: {
                         let mut opts = EcoVec::new();
                         opts.push(valid_opt.clone());
                         opts
                     },
-                    subcommands: EcoVec::new(),
-                    version: EcoString::new(),
+                    ..Default::default()
                 });
                 v
             },
-            version: EcoString::new(),
+            ..Default::default()
         };
 
         let fixed = Postprocessor::fix_command(cmd);
@@ -259,4 +844,24 @@ mod tests {
         assert_eq!(fixed.subcommands.len(), 1);
         assert_eq!(fixed.subcommands[0].options.len(), 1);
     }
+
+    #[test]
+    fn test_fix_command_deeply_nested_does_not_panic() {
+        let mut cmd = Command::new(EcoString::from("leaf"));
+        for i in 0..100 {
+            let mut parent = Command::new(EcoString::from(format!("level{}", i)));
+            parent.subcommands.push(cmd);
+            cmd = parent;
+        }
+
+        let fixed = Postprocessor::fix_command(cmd);
+
+        let mut depth = 0;
+        let mut current = &fixed;
+        while let Some(sub) = current.subcommands.first() {
+            current = sub;
+            depth += 1;
+        }
+        assert_eq!(depth, 100);
+    }
 }
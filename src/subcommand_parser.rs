@@ -1,36 +1,183 @@
 use crate::types::Subcommand;
 use bstr::ByteSlice;
 use ecow::{EcoString, EcoVec};
-use std::collections::BTreeSet;
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+
+/// Rules deciding which words [`SubcommandParser`] accepts as subcommand names.
+#[derive(Debug, Clone, Copy)]
+pub struct SubcommandParserConfig {
+    /// Shortest accepted name, in bytes.
+    pub min_name_len: usize,
+    /// Longest accepted name, in bytes.
+    pub max_name_len: usize,
+    /// Accept `.` inside names, e.g. `sub.command`.
+    pub allow_dots: bool,
+}
+
+impl Default for SubcommandParserConfig {
+    fn default() -> Self {
+        Self {
+            min_name_len: 1,
+            max_name_len: 64,
+            allow_dots: false,
+        }
+    }
+}
 
 pub struct SubcommandParser;
 
 impl SubcommandParser {
     pub fn parse(content: &str) -> EcoVec<Subcommand> {
+        Self::parse_with_config(content, &SubcommandParserConfig::default())
+    }
+
+    /// Parse subcommands, validating names against `config`.
+    pub fn parse_with_config(content: &str, config: &SubcommandParserConfig) -> EcoVec<Subcommand> {
+        Self::parse_impl(content, config, false)
+    }
+
+    /// `in_commands_section` is set when `content` is already limited to
+    /// command sections; otherwise the name/blank/description heuristic only
+    /// applies below a `Commands:` style header.
+    fn parse_impl(
+        content: &str,
+        config: &SubcommandParserConfig,
+        in_commands_section: bool,
+    ) -> EcoVec<Subcommand> {
         // Use bstr for SIMD-accelerated line iteration
         let bytes = content.as_bytes();
         let lines: Vec<&str> = bytes
             .lines()
             .filter_map(|line| std::str::from_utf8(line).ok())
             .collect();
-        let mut subcommands = BTreeSet::new();
+        // Keyed by name so both heuristics firing for the same subcommand
+        // produce a single entry, keeping the longer description
+        let mut subcommands: BTreeMap<EcoString, EcoString> = BTreeMap::new();
 
-        for window in lines.windows(2) {
-            if let Some(subcommand) = Self::parse_line_pair(window[0], window[1]) {
-                subcommands.insert(subcommand);
+        // Pipe-separated rows (`run | Execute the runner`) are complete on
+        // their own and must not feed the whitespace heuristics below
+        let pairs = lines
+            .windows(2)
+            .filter(|window| Self::parse_pipe_line(window[0], config).is_none())
+            .filter_map(|window| Self::parse_line_pair(window[0], window[1], config));
+        // Man pages put every section body after a blank line (`NAME`, blank,
+        // indented text), so triplets are only trusted inside command sections
+        let mut in_section = in_commands_section;
+        let section_flags: Vec<bool> = lines
+            .iter()
+            .map(|line| {
+                if !in_commands_section
+                    && !line.is_empty()
+                    && !line.starts_with(char::is_whitespace)
+                {
+                    in_section = Self::is_subcommand_section_header(line);
+                }
+                in_section
+            })
+            .collect();
+        let triplets = lines
+            .windows(3)
+            .zip(&section_flags)
+            .filter(|&(_, &in_section)| in_section)
+            .filter_map(|(window, _)| {
+                Self::parse_line_triplet(window[0], window[1], window[2], config)
+            });
+        let singles = lines.iter().filter_map(|line| {
+            Self::parse_pipe_line(line, config).or_else(|| Self::parse_single_line(line, config))
+        });
+
+        for Subcommand { cmd, desc } in pairs.chain(triplets).chain(singles) {
+            match subcommands.entry(cmd) {
+                Entry::Vacant(entry) => {
+                    entry.insert(desc);
+                }
+                Entry::Occupied(mut entry) => {
+                    if desc.len() > entry.get().len() {
+                        entry.insert(desc);
+                    }
+                }
             }
         }
 
-        for line in &lines {
-            if let Some(subcommand) = Self::parse_single_line(line) {
-                subcommands.insert(subcommand);
+        subcommands
+            .into_iter()
+            .map(|(cmd, desc)| Subcommand { cmd, desc })
+            .collect()
+    }
+
+    /// Parse subcommands only from `COMMANDS`/`SUBCOMMANDS` style sections.
+    ///
+    /// Falls back to scanning the whole content when no such section exists.
+    pub fn parse_from_sections(content: &str) -> EcoVec<Subcommand> {
+        let mut sections = String::new();
+        let mut in_section = false;
+
+        for line in content.as_bytes().lines() {
+            let Ok(line) = std::str::from_utf8(line) else {
+                continue;
+            };
+
+            // Section headers start at column zero
+            if !line.is_empty() && !line.starts_with(char::is_whitespace) {
+                in_section = Self::is_subcommand_section_header(line);
+                continue;
             }
+
+            if in_section {
+                sections.push_str(line);
+                sections.push('\n');
+            }
+        }
+
+        let config = SubcommandParserConfig::default();
+        if sections.is_empty() {
+            Self::parse_with_config(content, &config)
+        } else {
+            Self::parse_impl(&sections, &config, true)
+        }
+    }
+
+    /// Parse columnar listings that separate name and description with
+    /// ` | `, e.g. `run | Execute the runner`.
+    pub fn parse_pipe_separated(content: &str) -> EcoVec<Subcommand> {
+        let config = SubcommandParserConfig::default();
+        content
+            .as_bytes()
+            .lines()
+            .filter_map(|line| std::str::from_utf8(line).ok())
+            .filter_map(|line| Self::parse_pipe_line(line, &config))
+            .collect()
+    }
+
+    fn parse_pipe_line(line: &str, config: &SubcommandParserConfig) -> Option<Subcommand> {
+        let (name, desc) = line.trim().split_once(" | ")?;
+        let name = name.trim();
+        let desc = desc.trim();
+
+        if desc.is_empty() || !Self::is_valid_subcommand_name_with_config(name, config) {
+            return None;
         }
 
-        subcommands.into_iter().collect()
+        Some(Subcommand {
+            cmd: EcoString::from(name),
+            desc: EcoString::from(desc),
+        })
+    }
+
+    fn is_subcommand_section_header(line: &str) -> bool {
+        let header = line.trim().trim_end_matches(':').to_ascii_lowercase();
+        matches!(
+            header.as_str(),
+            "command" | "commands" | "subcommand" | "subcommands" | "available commands"
+        )
     }
 
-    fn parse_line_pair(first: &str, second: &str) -> Option<Subcommand> {
+    fn parse_line_pair(
+        first: &str,
+        second: &str,
+        config: &SubcommandParserConfig,
+    ) -> Option<Subcommand> {
         let trimmed_first = first.trim();
         let trimmed_bytes = trimmed_first.as_bytes();
 
@@ -41,7 +188,7 @@ impl SubcommandParser {
 
         let first_word = trimmed_first.split_whitespace().next()?;
 
-        if !Self::is_valid_subcommand_name(first_word) {
+        if !Self::is_valid_subcommand_name_with_config(first_word, config) {
             return None;
         }
 
@@ -65,7 +212,44 @@ impl SubcommandParser {
         })
     }
 
-    fn parse_single_line(line: &str) -> Option<Subcommand> {
+    /// A name alone on its line, a blank line, then the description indented
+    /// deeper than the name.
+    fn parse_line_triplet(
+        first: &str,
+        second: &str,
+        third: &str,
+        config: &SubcommandParserConfig,
+    ) -> Option<Subcommand> {
+        if !second.trim().is_empty() {
+            return None;
+        }
+
+        let mut words = first.split_whitespace();
+        let name = words.next()?;
+        if words.next().is_some() || !Self::is_valid_subcommand_name_with_config(name, config) {
+            return None;
+        }
+        // Section headings such as `EXAMPLES` or `Options:`
+        if name.ends_with(':')
+            || (name.bytes().any(|b| b.is_ascii_alphabetic())
+                && !name.bytes().any(|b| b.is_ascii_lowercase()))
+        {
+            return None;
+        }
+
+        let desc = third.trim();
+        let indent = |line: &str| line.len() - line.trim_start().len();
+        if desc.is_empty() || desc.starts_with('-') || indent(third) <= indent(first) {
+            return None;
+        }
+
+        Some(Subcommand {
+            cmd: EcoString::from(name),
+            desc: EcoString::from(desc),
+        })
+    }
+
+    fn parse_single_line(line: &str, config: &SubcommandParserConfig) -> Option<Subcommand> {
         let trimmed = line.trim();
         let trimmed_bytes = trimmed.as_bytes();
 
@@ -84,7 +268,7 @@ impl SubcommandParser {
 
         third?;
 
-        if !Self::is_valid_subcommand_name(name) {
+        if !Self::is_valid_subcommand_name_with_config(name, config) {
             return None;
         }
 
@@ -103,8 +287,16 @@ impl SubcommandParser {
         })
     }
 
+    /// Check `name` against the default [`SubcommandParserConfig`].
     #[inline]
-    fn is_valid_subcommand_name(name: &str) -> bool {
+    pub fn is_valid_subcommand_name(name: &str) -> bool {
+        Self::is_valid_subcommand_name_with_config(name, &SubcommandParserConfig::default())
+    }
+
+    pub fn is_valid_subcommand_name_with_config(
+        name: &str,
+        config: &SubcommandParserConfig,
+    ) -> bool {
         let bytes = name.as_bytes();
 
         // Fast path: check first byte
@@ -112,10 +304,19 @@ impl SubcommandParser {
             return false;
         }
 
+        if bytes.len() < config.min_name_len || bytes.len() > config.max_name_len {
+            return false;
+        }
+
+        // Dots may separate words but cannot start or end a name
+        if config.allow_dots && (bytes[0] == b'.' || bytes[bytes.len() - 1] == b'.') {
+            return false;
+        }
+
         // SIMD-friendly byte iteration
-        bytes
-            .iter()
-            .all(|&b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        bytes.iter().all(|&b| {
+            b.is_ascii_alphanumeric() || b == b'-' || b == b'_' || (config.allow_dots && b == b'.')
+        })
     }
 }
 
@@ -131,6 +332,160 @@ mod tests {
         assert!(subs.iter().any(|s| s.cmd.as_str() == "build"));
     }
 
+    #[test]
+    fn test_parse_merges_duplicate_subcommands_keeping_longer_description() {
+        // "run" is found both on its own line and paired with the wrapped line below
+        let content = "  run   Run it\n        Run the given target and wait for completion";
+        let subs = SubcommandParser::parse(content);
+        let run: Vec<_> = subs.iter().filter(|s| s.cmd.as_str() == "run").collect();
+        assert_eq!(run.len(), 1);
+        assert_eq!(
+            run[0].desc.as_str(),
+            "Run the given target and wait for completion"
+        );
+    }
+
+    #[test]
+    fn test_parse_description_after_blank_line() {
+        let content =
+            "Commands:\n  run\n\n      Run the given target\n  build\n\n      Build the project";
+        let subs = SubcommandParser::parse(content);
+        let run = subs.iter().find(|s| s.cmd == "run").unwrap();
+        assert_eq!(run.desc, "Run the given target");
+        let build = subs.iter().find(|s| s.cmd == "build").unwrap();
+        assert_eq!(build.desc, "Build the project");
+
+        // A following paragraph at the same indentation is not a description
+        let content = "run\n\nsome other paragraph";
+        assert!(
+            !SubcommandParser::parse(content)
+                .iter()
+                .any(|s| s.cmd == "run")
+        );
+    }
+
+    #[test]
+    fn test_parse_man_page_sections_are_not_subcommands() {
+        let content = "\
+NAME
+
+       foo - do things
+
+SYNOPSIS
+
+       foo [OPTION]...
+
+DESCRIPTION
+
+       Does things to files.
+
+EXAMPLES
+
+       foo --all
+";
+        let subs = SubcommandParser::parse(content);
+        for heading in ["NAME", "SYNOPSIS", "DESCRIPTION", "EXAMPLES"] {
+            assert!(!subs.iter().any(|s| s.cmd == heading), "{}", heading);
+        }
+
+        // Outside a command section, name/blank/description is not a subcommand
+        let content = "Notes\n  run\n\n      Run the given target\n";
+        assert!(
+            !SubcommandParser::parse(content)
+                .iter()
+                .any(|s| s.cmd == "run")
+        );
+
+        // Headings are rejected even inside a command section
+        let content = "Commands:\n  BUILD\n\n      Heading text\n  usage:\n\n      more text\n";
+        let subs = SubcommandParser::parse(content);
+        assert!(!subs.iter().any(|s| s.cmd == "BUILD" || s.cmd == "usage:"));
+    }
+
+    #[test]
+    fn test_parse_pipe_separated() {
+        let content = "  run | Execute
+  build | Build the project
+  not a name | Ignored";
+        let subs = SubcommandParser::parse_pipe_separated(content);
+        assert_eq!(subs.len(), 2);
+        assert_eq!(subs[0].cmd, "run");
+        assert_eq!(subs[0].desc, "Execute");
+        assert_eq!(subs[1].cmd, "build");
+        assert_eq!(subs[1].desc, "Build the project");
+
+        // `parse` picks them up without leaking the separator into descriptions
+        let subs = SubcommandParser::parse(content);
+        let run = subs.iter().find(|s| s.cmd == "run").unwrap();
+        assert_eq!(run.desc, "Execute");
+        let build = subs.iter().find(|s| s.cmd == "build").unwrap();
+        assert_eq!(build.desc, "Build the project");
+    }
+
+    #[test]
+    fn test_parse_from_sections_limits_scan_to_command_sections() {
+        let content = "\
+DESCRIPTION
+  mytool does many useful things
+
+SUBCOMMANDS:
+  run       Run a command
+  build     Build a project
+
+OPTIONS:
+  -v, --verbose  Be verbose";
+        let subs = SubcommandParser::parse_from_sections(content);
+        assert!(subs.iter().any(|s| s.cmd.as_str() == "run"));
+        assert!(subs.iter().any(|s| s.cmd.as_str() == "build"));
+        assert!(!subs.iter().any(|s| s.cmd.as_str() == "mytool"));
+    }
+
+    #[test]
+    fn test_parse_from_sections_falls_back_without_sections() {
+        let content = "run       Run a command\nbuild     Build a project";
+        assert_eq!(
+            SubcommandParser::parse_from_sections(content),
+            SubcommandParser::parse(content)
+        );
+    }
+
+    #[test]
+    fn test_parse_with_config_name_rules() {
+        let content = "sub.command   Run the dotted command\nv2            Use the second version";
+
+        let default = SubcommandParser::parse(content);
+        assert!(!default.iter().any(|s| s.cmd == "sub.command"));
+        assert!(default.iter().any(|s| s.cmd == "v2"));
+
+        let dotted = SubcommandParserConfig {
+            allow_dots: true,
+            ..SubcommandParserConfig::default()
+        };
+        let subs = SubcommandParser::parse_with_config(content, &dotted);
+        assert!(subs.iter().any(|s| s.cmd == "sub.command"));
+        assert!(!SubcommandParser::is_valid_subcommand_name_with_config(
+            ".hidden", &dotted
+        ));
+
+        let min_three = SubcommandParserConfig {
+            min_name_len: 3,
+            ..SubcommandParserConfig::default()
+        };
+        let subs = SubcommandParser::parse_with_config(content, &min_three);
+        assert!(!subs.iter().any(|s| s.cmd == "v2"));
+
+        let short_max = SubcommandParserConfig {
+            max_name_len: 4,
+            ..SubcommandParserConfig::default()
+        };
+        assert!(SubcommandParser::is_valid_subcommand_name_with_config(
+            "run", &short_max
+        ));
+        assert!(!SubcommandParser::is_valid_subcommand_name_with_config(
+            "build", &short_max
+        ));
+    }
+
     #[test]
     fn test_is_valid_subcommand_name() {
         assert!(SubcommandParser::is_valid_subcommand_name("run"));
@@ -1,13 +1,33 @@
+use aho_corasick::AhoCorasick;
 use ecow::{EcoString, EcoVec};
 use foldhash::quality::RandomState;
+use regex::Regex;
 use scc::{HashMap as SccHashMap, HashSet as SccHashSet};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::sync::LazyLock;
+
+// Pre-compiled Aho-Corasick automaton for file/dir/path matching (SIMD-accelerated)
+static FILE_PATH_MATCHER: LazyLock<AhoCorasick> = LazyLock::new(|| {
+    AhoCorasick::builder()
+        .ascii_case_insensitive(true)
+        .build(["file", "dir", "path", "archive"])
+        .unwrap()
+});
+
+// `DEPTH=N` annotation in a subcommand description, read by `Command::depth_override`
+static DEPTH_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\bDEPTH=(\d+)\b").unwrap());
 
 pub type HashMap<K, V> = SccHashMap<K, V, RandomState>;
 pub type HashSet<T> = SccHashSet<T, RandomState>;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// Option names seen so far, so repeated names share one allocation.
+#[cfg(feature = "intern")]
+static INTERNED_NAMES: LazyLock<HashMap<EcoString, EcoString>> =
+    LazyLock::new(|| HashMap::with_hasher(RandomState::default()));
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct Command {
     pub name: EcoString,
     pub description: EcoString,
@@ -17,17 +37,59 @@ pub struct Command {
     pub subcommands: EcoVec<Command>,
     #[serde(default)]
     pub version: EcoString,
+    /// Whether the usage line advertises options (`[OPTIONS]`, `[flags]`, ...).
+    #[serde(default = "default_accepts_options")]
+    pub accepts_options: bool,
+    /// Groups of option names of which at most one may be given.
+    #[serde(default)]
+    pub exclusive_groups: EcoVec<EcoVec<EcoString>>,
+    /// Options as grouped under headings in the help text (e.g. "Output options").
+    #[serde(default)]
+    pub option_groups: EcoVec<OptionGroup>,
+    /// Alternative invocations from a usage line such as
+    /// `mycmd -a FILE | mycmd -b DIR`, empty when there is only one form.
+    #[serde(default, skip_serializing_if = "EcoVec::is_empty")]
+    pub usage_alternatives: EcoVec<EcoString>,
+}
+
+fn default_accepts_options() -> bool {
+    true
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct Opt {
     pub names: EcoVec<OptName>,
     pub argument: EcoString,
     pub description: EcoString,
+    /// Values the option's argument accepts, when the help text lists them.
+    #[serde(default)]
+    pub possible_values: EcoVec<EcoString>,
+    /// Whether the argument may be omitted, as in `--count [N]`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub argument_optional: bool,
+    /// Whether the help text wrote the name with a trailing `=` (`--flag=`),
+    /// meaning the argument must be given.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub argument_required: bool,
+    /// Environment variable the description says overrides this option,
+    /// e.g. `EDITOR` for "Overrides $EDITOR".
+    #[serde(default, skip_serializing_if = "EcoString::is_empty")]
+    pub env_var: EcoString,
+}
+
+/// A named group of options, such as the options listed under one help heading.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct OptionGroup {
+    pub name: EcoString,
+    pub options: EcoVec<Opt>,
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct OptName {
+    /// The name as written in the help text. `EcoString` is reference counted
+    /// (short names are stored inline), so cloning across Rayon workers is cheap.
     pub raw: EcoString,
     #[serde(rename = "type")]
     pub opt_type: OptNameType,
@@ -40,6 +102,7 @@ impl<'de> Deserialize<'de> for OptName {
     {
         #[derive(Deserialize)]
         #[serde(untagged)]
+        #[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
         enum OptNameCompat {
             Legacy(String),
             Structured {
@@ -65,6 +128,7 @@ impl<'de> Deserialize<'de> for OptName {
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[serde(rename_all = "UPPERCASE")]
+#[non_exhaustive]
 pub enum OptNameType {
     LongType,
     ShortType,
@@ -96,7 +160,10 @@ impl OptName {
         Self { raw, opt_type }
     }
 
+    /// Build a name from help text. An attached argument (`--flag=`,
+    /// `--flag=VALUE`, `--color[=WHEN]`) is not part of the name and is dropped.
     pub fn from_text(s: &str) -> Option<Self> {
+        let (s, _) = Self::split_attached_argument(s);
         let opt_type = Self::determine_type(s)?;
         Some(Self {
             raw: EcoString::from(s),
@@ -104,6 +171,54 @@ impl OptName {
         })
     }
 
+    /// Like [`OptName::new`], but shares storage with earlier names of the same text.
+    ///
+    /// Names short enough to be stored inline by `EcoString` gain nothing from
+    /// interning; long names repeated across many subcommands do.
+    #[cfg(feature = "intern")]
+    pub fn new_interned(raw: &str, opt_type: OptNameType) -> Self {
+        Self {
+            raw: Self::intern(raw),
+            opt_type,
+        }
+    }
+
+    /// Like [`OptName::from_text`], but interns the name.
+    #[cfg(feature = "intern")]
+    pub fn from_text_interned(s: &str) -> Option<Self> {
+        let (s, _) = Self::split_attached_argument(s);
+        let opt_type = Self::determine_type(s)?;
+        Some(Self::new_interned(s, opt_type))
+    }
+
+    #[cfg(feature = "intern")]
+    fn intern(raw: &str) -> EcoString {
+        if let Some(shared) = INTERNED_NAMES.read_sync(raw, |_, v| v.clone()) {
+            return shared;
+        }
+        let raw = EcoString::from(raw);
+        INTERNED_NAMES
+            .entry_sync(raw.clone())
+            .or_insert(raw)
+            .get()
+            .clone()
+    }
+
+    /// Split a word such as `--output=FILE` into the name and the argument
+    /// written onto it. Only the first `=` splits (`--opt=a=b` gives `a=b`),
+    /// and an optional argument keeps its brackets (`--color[=WHEN]` gives
+    /// `[=WHEN]`). A separator directly after the dashes is part of the name.
+    pub fn split_attached_argument(s: &str) -> (&str, Option<&str>) {
+        let dashes = s.len() - s.trim_start_matches('-').len();
+        match memchr::memchr2(b'=', b'[', s.as_bytes()) {
+            Some(pos) if pos > dashes && s.as_bytes()[pos] == b'=' => {
+                (&s[..pos], Some(&s[pos + 1..]))
+            }
+            Some(pos) if pos > dashes => (&s[..pos], Some(&s[pos..])),
+            _ => (s, None),
+        }
+    }
+
     fn determine_type(s: &str) -> Option<OptNameType> {
         match s {
             "-" => Some(OptNameType::SingleDashAlone),
@@ -116,6 +231,55 @@ impl OptName {
     }
 }
 
+impl Opt {
+    /// First name of [`OptNameType::LongType`], e.g. `--verbose`.
+    pub fn long_name(&self) -> Option<&OptName> {
+        self.names
+            .iter()
+            .find(|n| n.opt_type == OptNameType::LongType)
+    }
+
+    /// First name of [`OptNameType::ShortType`], e.g. `-v`.
+    pub fn short_name(&self) -> Option<&OptName> {
+        self.names
+            .iter()
+            .find(|n| n.opt_type == OptNameType::ShortType)
+    }
+
+    /// The long name if present, otherwise the short name, otherwise the first name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the option has no names.
+    pub fn primary_name(&self) -> &OptName {
+        self.long_name()
+            .or_else(|| self.short_name())
+            .unwrap_or(&self.names[0])
+    }
+
+    /// Whether the option takes a file or directory argument, judged by keywords
+    /// in its argument placeholder or description.
+    #[inline]
+    pub fn is_file_arg(&self) -> bool {
+        !self.argument.is_empty()
+            && (FILE_PATH_MATCHER.is_match(self.argument.as_str())
+                || FILE_PATH_MATCHER.is_match(self.description.as_str()))
+    }
+}
+
+impl std::fmt::Display for OptNameType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            OptNameType::LongType => "long (--)",
+            OptNameType::ShortType => "short (-)",
+            OptNameType::OldType => "old-style (-)",
+            OptNameType::DoubleDashAlone => "-- alone",
+            OptNameType::SingleDashAlone => "- alone",
+        };
+        f.write_str(name)
+    }
+}
+
 impl std::fmt::Display for OptName {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.raw)
@@ -144,6 +308,105 @@ impl std::fmt::Display for Subcommand {
     }
 }
 
+/// Options and subcommands that differ between two versions of a command,
+/// as computed by [`Command::diff`].
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct CommandDiff {
+    pub added_options: EcoVec<Opt>,
+    pub removed_options: EcoVec<Opt>,
+    /// `(old, new)` pairs of options sharing a name whose names, argument or
+    /// description changed.
+    pub changed_options: EcoVec<(Opt, Opt)>,
+    pub added_subcommands: EcoVec<EcoString>,
+    pub removed_subcommands: EcoVec<EcoString>,
+}
+
+impl CommandDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_options.is_empty()
+            && self.removed_options.is_empty()
+            && self.changed_options.is_empty()
+            && self.added_subcommands.is_empty()
+            && self.removed_subcommands.is_empty()
+    }
+}
+
+impl std::fmt::Display for CommandDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn names(opt: &Opt) -> String {
+            opt.names
+                .iter()
+                .map(|n| n.raw.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        if self.is_empty() {
+            return f.write_str("No changes");
+        }
+
+        let mut lines = Vec::new();
+        for opt in self.added_options.iter() {
+            lines.push(format!("+ {}  {}", names(opt), opt.description));
+        }
+        for opt in self.removed_options.iter() {
+            lines.push(format!("- {}  {}", names(opt), opt.description));
+        }
+        for (old, new) in self.changed_options.iter() {
+            lines.push(format!("~ {}", names(new)));
+            if names(old) != names(new) {
+                lines.push(format!("    names: {} -> {}", names(old), names(new)));
+            }
+            if old.argument != new.argument {
+                lines.push(format!(
+                    "    argument: {:?} -> {:?}",
+                    old.argument, new.argument
+                ));
+            }
+            if old.description != new.description {
+                lines.push(format!(
+                    "    description: {:?} -> {:?}",
+                    old.description, new.description
+                ));
+            }
+        }
+        for name in self.added_subcommands.iter() {
+            lines.push(format!("+ subcommand {}", name));
+        }
+        for name in self.removed_subcommands.iter() {
+            lines.push(format!("- subcommand {}", name));
+        }
+
+        f.write_str(&lines.join("\n"))
+    }
+}
+
+/// A [`Command`] compared and hashed by name only, e.g. to deduplicate
+/// subcommands in a `HashSet`.
+#[derive(Debug, Clone)]
+pub struct CommandByName(pub Command);
+
+impl PartialEq for CommandByName {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_by_name(&other.0)
+    }
+}
+
+impl Eq for CommandByName {}
+
+impl std::hash::Hash for CommandByName {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.name.hash(state);
+    }
+}
+
+impl Default for Command {
+    /// An unnamed command that accepts options, like [`Command::new`].
+    fn default() -> Self {
+        Self::new(EcoString::new())
+    }
+}
+
 impl Command {
     pub fn new(name: EcoString) -> Self {
         Self {
@@ -153,9 +416,160 @@ impl Command {
             options: EcoVec::new(),
             subcommands: EcoVec::new(),
             version: EcoString::new(),
+            accepts_options: true,
+            exclusive_groups: EcoVec::new(),
+            option_groups: EcoVec::new(),
+            usage_alternatives: EcoVec::new(),
+        }
+    }
+
+    /// Detect an options placeholder such as `[OPTIONS]`, `[flags]` or `[ARGS]` in a
+    /// usage string. An empty usage tells us nothing, so it counts as accepting options.
+    pub fn usage_accepts_options(usage: &str) -> bool {
+        if usage.trim().is_empty() {
+            return true;
+        }
+        let lower = usage.to_ascii_lowercase();
+        ["[option", "[flag", "[args", "[-"]
+            .iter()
+            .any(|placeholder| lower.contains(placeholder))
+    }
+
+    /// Options to offer as completions: none when the command takes no options.
+    pub fn completion_options(&self) -> &[Opt] {
+        if self.accepts_options {
+            &self.options
+        } else {
+            &[]
         }
     }
 
+    /// Whether `other` has the same name, ignoring options, subcommands and the
+    /// rest of the fields compared by `PartialEq`.
+    pub fn eq_by_name(&self, other: &Command) -> bool {
+        self.name == other.name
+    }
+
+    /// Compare two versions of a command. Options are matched by any shared name,
+    /// subcommands by name. The order in which an option lists its names is ignored.
+    pub fn diff(old: &Command, new: &Command) -> CommandDiff {
+        fn shares_name(a: &Opt, b: &Opt) -> bool {
+            a.names
+                .iter()
+                .any(|name| b.names.iter().any(|n| n.raw == name.raw))
+        }
+
+        fn same_names(a: &Opt, b: &Opt) -> bool {
+            let mut a_names: Vec<&str> = a.names.iter().map(|n| n.raw.as_str()).collect();
+            let mut b_names: Vec<&str> = b.names.iter().map(|n| n.raw.as_str()).collect();
+            a_names.sort_unstable();
+            b_names.sort_unstable();
+            a_names == b_names
+        }
+
+        let mut diff = CommandDiff::default();
+
+        for new_opt in new.options.iter() {
+            match old
+                .options
+                .iter()
+                .find(|old_opt| shares_name(old_opt, new_opt))
+            {
+                None => diff.added_options.push(new_opt.clone()),
+                Some(old_opt)
+                    if !same_names(old_opt, new_opt)
+                        || old_opt.argument != new_opt.argument
+                        || old_opt.description != new_opt.description =>
+                {
+                    diff.changed_options
+                        .push((old_opt.clone(), new_opt.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+        diff.removed_options = old
+            .options
+            .iter()
+            .filter(|old_opt| !new.options.iter().any(|n| shares_name(old_opt, n)))
+            .cloned()
+            .collect();
+
+        diff.added_subcommands = new
+            .subcommands
+            .iter()
+            .filter(|sub| !old.subcommands.iter().any(|o| o.eq_by_name(sub)))
+            .map(|sub| sub.name.clone())
+            .collect();
+        diff.removed_subcommands = old
+            .subcommands
+            .iter()
+            .filter(|sub| !new.subcommands.iter().any(|n| n.eq_by_name(sub)))
+            .map(|sub| sub.name.clone())
+            .collect();
+
+        diff
+    }
+
+    /// A command is empty when it has neither options nor subcommands.
+    pub fn is_empty(&self) -> bool {
+        self.options.is_empty() && self.subcommands.is_empty()
+    }
+
+    /// Per-branch subcommand depth from a `DEPTH=N` annotation in the description.
+    pub fn depth_override(&self) -> Option<usize> {
+        DEPTH_RE
+            .captures(&self.description)
+            .and_then(|caps| caps[1].parse().ok())
+    }
+
+    /// Like [`Command::depth_override`], but also removes the annotation from
+    /// the description so it does not end up in generated output.
+    pub fn take_depth_override(&mut self) -> Option<usize> {
+        let caps = DEPTH_RE.captures(&self.description)?;
+        let depth = caps[1].parse().ok();
+        let range = caps.get(0)?.range();
+
+        let before = self.description[..range.start].trim_end();
+        let after = self.description[range.end..].trim_start();
+        let description = if before.is_empty() || after.is_empty() {
+            format!("{}{}", before, after)
+        } else {
+            format!("{} {}", before, after)
+        };
+        self.description = EcoString::from(description);
+        depth
+    }
+
+    /// Options of `root` (typically global options listed only at the top level)
+    /// whose names do not already appear in this command's options.
+    pub fn inherited_options<'a>(
+        &'a self,
+        root: &'a Command,
+    ) -> impl Iterator<Item = &'a Opt> + 'a {
+        root.options.iter().filter(move |root_opt| {
+            !root_opt.names.iter().any(|name| {
+                self.options
+                    .iter()
+                    .any(|opt| opt.names.iter().any(|n| n.raw == name.raw))
+            })
+        })
+    }
+
+    /// A copy of this command with [`Command::inherited_options`] from `root`
+    /// appended, here and in every nested subcommand.
+    pub fn with_inherited_options(&self, root: Option<&Command>) -> Command {
+        let mut cmd = self.clone();
+        if let Some(root) = root {
+            cmd.options.extend(self.inherited_options(root).cloned());
+            cmd.subcommands = self
+                .subcommands
+                .iter()
+                .map(|sub| sub.with_inherited_options(Some(root)))
+                .collect();
+        }
+        cmd
+    }
+
     pub fn as_subcommand(&self) -> Subcommand {
         Subcommand {
             cmd: self.name.clone(),
@@ -168,6 +582,202 @@ impl Command {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_command_diff() {
+        let opt = |names: &[&str], arg: &str, desc: &str| Opt {
+            names: names
+                .iter()
+                .map(|n| OptName::from_text(n).unwrap())
+                .collect(),
+            argument: EcoString::from(arg),
+            description: EcoString::from(desc),
+            ..Default::default()
+        };
+
+        let mut old = Command::new(EcoString::from("tool"));
+        old.options
+            .push(opt(&["-v", "--verbose"], "", "Be verbose"));
+        old.options.push(opt(&["--jobs"], "N", "Parallel jobs"));
+        old.options.push(opt(&["--legacy"], "", "Old behaviour"));
+        old.subcommands.push(Command::new(EcoString::from("build")));
+        old.subcommands.push(Command::new(EcoString::from("clean")));
+
+        let mut new = Command::new(EcoString::from("tool"));
+        new.options
+            .push(opt(&["-v", "--verbose"], "", "Be verbose"));
+        new.options
+            .push(opt(&["-j", "--jobs"], "N", "Number of parallel jobs"));
+        new.options
+            .push(opt(&["--color"], "WHEN", "Colorize output"));
+        new.subcommands.push(Command::new(EcoString::from("build")));
+        new.subcommands.push(Command::new(EcoString::from("test")));
+
+        let diff = Command::diff(&old, &new);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.added_options.len(), 1);
+        assert_eq!(diff.added_options[0].names[0].raw, "--color");
+        assert_eq!(diff.removed_options.len(), 1);
+        assert_eq!(diff.removed_options[0].names[0].raw, "--legacy");
+        assert_eq!(diff.changed_options.len(), 1);
+        assert_eq!(diff.changed_options[0].1.names[0].raw, "-j");
+        assert_eq!(diff.added_subcommands.as_slice(), ["test"]);
+        assert_eq!(diff.removed_subcommands.as_slice(), ["clean"]);
+
+        let text = diff.to_string();
+        assert!(text.contains("+ --color  Colorize output"));
+        assert!(text.contains("- --legacy  Old behaviour"));
+        assert!(text.contains("~ -j, --jobs\n    names: --jobs -> -j, --jobs"));
+        assert!(text.contains("+ subcommand test"));
+        assert!(text.contains("- subcommand clean"));
+
+        assert!(Command::diff(&new, &new).is_empty());
+        assert_eq!(Command::diff(&new, &new).to_string(), "No changes");
+    }
+
+    #[test]
+    fn test_eq_by_name_ignores_options() {
+        let plain = Command::new(EcoString::from("build"));
+        let mut with_opts = Command::new(EcoString::from("build"));
+        with_opts.options.push(Opt {
+            names: ecow::eco_vec![OptName::new(
+                EcoString::from("--release"),
+                OptNameType::LongType
+            )],
+            description: EcoString::from("Build in release mode"),
+            ..Default::default()
+        });
+
+        assert_ne!(plain, with_opts);
+        assert!(plain.eq_by_name(&with_opts));
+        assert!(!plain.eq_by_name(&Command::new(EcoString::from("test"))));
+
+        let unique: std::collections::HashSet<CommandByName> = [
+            CommandByName(plain),
+            CommandByName(with_opts),
+            CommandByName(Command::new(EcoString::from("test"))),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(unique.len(), 2);
+    }
+
+    #[test]
+    fn test_command_hash_matches_eq() {
+        use std::collections::HashSet;
+
+        let mut cmd = Command::new(EcoString::from("test"));
+        cmd.options.push(Opt {
+            names: EcoVec::from([OptName::new(
+                EcoString::from("--verbose"),
+                OptNameType::LongType,
+            )]),
+            description: EcoString::from("Be verbose"),
+            ..Default::default()
+        });
+        cmd.subcommands.push(Command::new(EcoString::from("run")));
+
+        let set: HashSet<Command> = [cmd.clone(), cmd.clone()].into_iter().collect();
+        assert_eq!(set.len(), 1);
+
+        let mut other = cmd.clone();
+        other.subcommands.make_mut()[0].description = EcoString::from("Run it");
+        let set: HashSet<Command> = [cmd, other].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_from_text_strips_equals() {
+        for text in ["--flag=", "--flag=VALUE", "--flag"] {
+            let name = OptName::from_text(text).unwrap();
+            assert_eq!(name.raw, "--flag", "{}", text);
+            assert_eq!(name.opt_type, OptNameType::LongType);
+        }
+        assert_eq!(OptName::from_text("-o=FILE").unwrap().raw, "-o");
+        assert_eq!(OptName::from_text("--color[=WHEN]").unwrap().raw, "--color");
+        assert_eq!(
+            OptName::split_attached_argument("--opt=a=b"),
+            ("--opt", Some("a=b"))
+        );
+        assert_eq!(OptName::split_attached_argument("--opt"), ("--opt", None));
+    }
+
+    #[cfg(feature = "intern")]
+    #[test]
+    fn test_interned_names_share_storage() {
+        let a = OptName::from_text_interned("--ignore-whitespace-changes").unwrap();
+        let b = OptName::new_interned("--ignore-whitespace-changes", OptNameType::LongType);
+        assert_eq!(a, b);
+        assert_eq!(a.raw.as_ptr(), b.raw.as_ptr());
+        assert!(OptName::from_text_interned("plain").is_none());
+    }
+
+    #[test]
+    fn test_opt_name_type_display() {
+        assert_eq!(OptNameType::LongType.to_string(), "long (--)");
+        assert_eq!(OptNameType::ShortType.to_string(), "short (-)");
+        assert_eq!(OptNameType::OldType.to_string(), "old-style (-)");
+        assert_eq!(OptNameType::DoubleDashAlone.to_string(), "-- alone");
+        assert_eq!(OptNameType::SingleDashAlone.to_string(), "- alone");
+    }
+
+    #[test]
+    fn test_usage_accepts_options() {
+        assert!(Command::usage_accepts_options(
+            "Usage: foo [OPTIONS] <FILE>"
+        ));
+        assert!(Command::usage_accepts_options("usage: foo [flags] args"));
+        assert!(Command::usage_accepts_options(
+            "Usage: ls [OPTION]... [FILE]..."
+        ));
+        assert!(Command::usage_accepts_options(""));
+        assert!(!Command::usage_accepts_options("Usage: true"));
+        assert!(!Command::usage_accepts_options(
+            "Usage: basename NAME SUFFIX"
+        ));
+    }
+
+    #[test]
+    fn test_inherited_options_skips_shadowed_names() {
+        let opt = |raw: &str| Opt {
+            names: ecow::eco_vec![OptName::from_text(raw).unwrap()],
+            description: EcoString::from("desc"),
+            ..Default::default()
+        };
+        let mut root = Command::new(EcoString::from("git"));
+        root.options.push(opt("--no-pager"));
+        root.options.push(opt("--verbose"));
+        let mut sub = Command::new(EcoString::from("log"));
+        sub.options.push(opt("--verbose"));
+
+        let inherited: Vec<_> = sub.inherited_options(&root).collect();
+        assert_eq!(inherited.len(), 1);
+        assert_eq!(inherited[0].names[0].raw, "--no-pager");
+
+        assert_eq!(sub.with_inherited_options(Some(&root)).options.len(), 2);
+        assert_eq!(sub.with_inherited_options(None), sub);
+
+        // Nested subcommands inherit from the same root
+        let mut nested = Command::new(EcoString::from("show"));
+        nested.options.push(opt("--stat"));
+        let mut sub = sub.clone();
+        sub.subcommands.push(nested);
+        root.subcommands.push(sub);
+        let tree = root.with_inherited_options(Some(&root));
+        assert_eq!(tree.options.len(), 2);
+        assert_eq!(tree.subcommands[0].options.len(), 2);
+        assert_eq!(tree.subcommands[0].subcommands[0].options.len(), 3);
+    }
+
+    #[test]
+    fn test_opt_name_serializes_raw_as_plain_string() {
+        let name = OptName::from_text("--verbose").unwrap();
+        let json = serde_json::to_string(&name).unwrap();
+        assert_eq!(json, r#"{"raw":"--verbose","type":"LONGTYPE"}"#);
+
+        let back: OptName = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, name);
+    }
+
     #[test]
     fn test_command_new_and_as_subcommand() {
         let mut cmd = Command::new(EcoString::from("test"));
@@ -179,4 +789,78 @@ mod tests {
         assert_eq!(sub.cmd.as_str(), "test");
         assert_eq!(sub.desc.as_str(), "Test command");
     }
+
+    #[test]
+    fn test_opt_name_accessors() {
+        let mut opt = Opt {
+            names: EcoVec::new(),
+            ..Default::default()
+        };
+        opt.names.push(OptName::from_text("-old").unwrap());
+        assert!(opt.long_name().is_none());
+        assert!(opt.short_name().is_none());
+        assert_eq!(opt.primary_name().raw, "-old");
+
+        opt.names.push(OptName::from_text("-v").unwrap());
+        assert_eq!(opt.short_name().unwrap().raw, "-v");
+        assert_eq!(opt.primary_name().raw, "-v");
+
+        opt.names.push(OptName::from_text("--verbose").unwrap());
+        assert_eq!(opt.long_name().unwrap().raw, "--verbose");
+        assert_eq!(opt.primary_name().raw, "--verbose");
+    }
+
+    #[test]
+    fn test_opt_is_file_arg() {
+        let opt = |argument: &str, description: &str| Opt {
+            names: EcoVec::new(),
+            argument: EcoString::from(argument),
+            description: EcoString::from(description),
+            ..Default::default()
+        };
+
+        assert!(opt("FILE", "Input").is_file_arg());
+        assert!(opt("<dir>", "").is_file_arg());
+        assert!(opt("NAME", "Write to the given path").is_file_arg());
+        assert!(opt("TARBALL", "Archive to extract").is_file_arg());
+
+        assert!(!opt("NUM", "Number of jobs").is_file_arg());
+        assert!(!opt("", "Read from a file").is_file_arg());
+    }
+
+    #[test]
+    fn test_command_depth_override() {
+        let mut cmd = Command::new(EcoString::from("run"));
+        assert_eq!(cmd.depth_override(), None);
+
+        cmd.description = EcoString::from("Run a container DEPTH=1");
+        assert_eq!(cmd.depth_override(), Some(1));
+
+        cmd.description = EcoString::from("NODEPTH=3");
+        assert_eq!(cmd.depth_override(), None);
+    }
+
+    #[test]
+    fn test_command_take_depth_override_strips_annotation() {
+        let mut cmd = Command::new(EcoString::from("run"));
+        cmd.description = EcoString::from("Run a DEPTH=1 container");
+        assert_eq!(cmd.take_depth_override(), Some(1));
+        assert_eq!(cmd.description.as_str(), "Run a container");
+
+        cmd.description = EcoString::from("Run a container DEPTH=2");
+        assert_eq!(cmd.take_depth_override(), Some(2));
+        assert_eq!(cmd.description.as_str(), "Run a container");
+
+        assert_eq!(cmd.take_depth_override(), None);
+        assert_eq!(cmd.description.as_str(), "Run a container");
+    }
+
+    #[test]
+    fn test_command_is_empty() {
+        let mut cmd = Command::new(EcoString::from("test"));
+        assert!(cmd.is_empty());
+
+        cmd.subcommands.push(Command::new(EcoString::from("sub")));
+        assert!(!cmd.is_empty());
+    }
 }
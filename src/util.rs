@@ -0,0 +1,34 @@
+//! Small std-only helpers shared by the library and `build.rs`, which
+//! includes `cli.rs` and therefore everything it imports.
+
+/// Edit distance between `a` and `b`, counted in chars.
+pub(crate) fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("zsh", "zsh"), 0);
+        assert_eq!(levenshtein("jsno", "json"), 2);
+        assert_eq!(levenshtein("fsh", "fish"), 1);
+        assert_eq!(levenshtein("", "bash"), 4);
+    }
+}
@@ -0,0 +1,56 @@
+use crate::types::Command;
+use anyhow::{Context, Result};
+use ecow::EcoString;
+
+pub struct YamlGenerator;
+
+impl YamlGenerator {
+    pub fn generate(cmd: &Command) -> EcoString {
+        EcoString::from(serde_norway::to_string(cmd).unwrap_or_default())
+    }
+
+    /// Parse a `Command` from YAML produced by [`YamlGenerator::generate`].
+    pub fn parse(s: &str) -> Result<Command> {
+        serde_norway::from_str(s).context("Failed to parse Command YAML")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Opt, OptName, OptNameType};
+    use ecow::EcoVec;
+
+    #[test]
+    fn test_yaml_roundtrip_preserves_command() {
+        let mut cmd = Command::new(EcoString::from("test"));
+        cmd.description = EcoString::from("Test command");
+        cmd.usage = EcoString::from("test [OPTIONS]");
+        cmd.version = EcoString::from("1.0.0");
+        for (raw, opt_type) in [
+            ("--zeta", OptNameType::LongType),
+            ("-a", OptNameType::ShortType),
+            ("-old", OptNameType::OldType),
+        ] {
+            let mut names = EcoVec::new();
+            names.push(OptName::new(EcoString::from(raw), opt_type));
+            cmd.options.push(Opt {
+                names,
+                argument: EcoString::from("VALUE"),
+                description: EcoString::from("An option: with a colon"),
+                ..Default::default()
+            });
+        }
+        cmd.subcommands.push(Command::new(EcoString::from("sub")));
+
+        let yaml = YamlGenerator::generate(&cmd);
+        let parsed = YamlGenerator::parse(&yaml).expect("parse generated yaml");
+        assert_eq!(parsed, cmd);
+        assert_eq!(YamlGenerator::generate(&parsed), yaml);
+    }
+
+    #[test]
+    fn test_yaml_parse_rejects_invalid_input() {
+        assert!(YamlGenerator::parse("name: [unterminated").is_err());
+    }
+}
@@ -7,7 +7,7 @@ use predicates::prelude::*;
 fn cli_errors_without_input_source() {
     let mut cmd = cargo_bin_cmd!("d2o");
     cmd.assert().failure().stderr(predicate::str::contains(
-        "No input source specified. Use --command, --file, --subcommand, or --loadjson",
+        "No input source specified. Use --command, --file, --subcommand, --loadjson, or --loadyaml",
     ));
 }
 
@@ -43,6 +43,44 @@ fn cli_file_native_output() {
         .stdout(predicate::str::contains("USAGE: mycmd [OPTIONS]"));
 }
 
+/// Verify --grouped lists zsh options under their help section
+#[test]
+fn cli_file_zsh_grouped_output() {
+    use std::io::Write;
+
+    let mut tmp = tempfile::NamedTempFile::new().expect("create temp help");
+    writeln!(
+        tmp,
+        "USAGE: mycmd [OPTIONS]\n\nOptions:\n  -v, --verbose  be verbose\n\nOutput options:\n  -o, --output <FILE>  write to FILE"
+    )
+    .unwrap();
+    let path = tmp.path().to_str().unwrap().to_string();
+
+    let mut cmd = cargo_bin_cmd!("d2o");
+    let assert = cmd
+        .args([
+            "--file",
+            &path,
+            "--format",
+            "zsh",
+            "--grouped",
+            "--cache",
+            "false",
+        ])
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let options = stdout
+        .find("  # Section: Options\n")
+        .expect("Options section");
+    let output = stdout
+        .find("  # Section: Output options\n")
+        .expect("Output options section");
+    assert!(options < stdout.find("--verbose").unwrap());
+    assert!(output < stdout.find("--output").unwrap());
+}
+
 /// Verify --write caches output under ~/.d2o
 #[test]
 fn cli_write_caches_to_home_d2o() {
@@ -79,6 +117,30 @@ fn cli_write_caches_to_home_d2o() {
     );
 }
 
+/// Warn when no options or subcommands could be extracted
+#[test]
+fn cli_warns_on_empty_command() {
+    use std::io::Write;
+
+    let mut tmp = tempfile::NamedTempFile::new().expect("create temp help");
+    writeln!(tmp, "(no help available)").unwrap();
+    let path = tmp.path().to_str().unwrap().to_string();
+
+    let mut cmd = cargo_bin_cmd!("d2o");
+    cmd.args(["--file", &path, "--format", "bash"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("no options or subcommands found"));
+
+    // -q skips generation silently
+    let mut cmd = cargo_bin_cmd!("d2o");
+    cmd.args(["--file", &path, "--format", "bash", "-q"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::is_empty());
+}
+
 /// Use the same help text but output JSON and ensure basic fields exist
 #[test]
 fn cli_file_json_output() {
@@ -105,10 +167,51 @@ fn cli_file_json_output() {
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap();
-    assert_eq!(value["name"], file_name);
+    // Temp file names are mixed case; the command name is normalized
+    assert_eq!(
+        value["name"],
+        d2o::Postprocessor::normalize_command_name(file_name)
+    );
     assert!(value["options"].is_array());
 }
 
+/// Descriptions on the option line, separated by a two-space gap
+#[test]
+fn cli_file_same_line_descriptions() {
+    use std::io::Write;
+
+    let mut tmp = tempfile::NamedTempFile::new().expect("create temp help");
+    writeln!(
+        tmp,
+        "USAGE: mycmd [OPTIONS]\n\nOPTIONS:\n  -v, --verbose  be verbose\n  -o, --output   write output\n  -h, --help     show help"
+    )
+    .unwrap();
+    let path = tmp.path().to_str().unwrap().to_string();
+
+    let mut cmd = cargo_bin_cmd!("d2o");
+    let assert = cmd
+        .args(["--file", &path, "--format", "json", "--cache", "false"])
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&stdout).expect("valid json");
+    let options = value["options"].as_array().expect("options array");
+    assert_eq!(options.len(), 3);
+
+    let verbose = options
+        .iter()
+        .find(|opt| {
+            opt["names"]
+                .as_array()
+                .unwrap()
+                .contains(&"--verbose".into())
+        })
+        .expect("--verbose parsed");
+    assert_eq!(verbose["argument"], "");
+    assert_eq!(verbose["description"], "be verbose");
+}
+
 /// Ensure completions flag at least runs for bash
 #[test]
 fn cli_completions_bash() {
@@ -155,6 +258,95 @@ fn cli_debug_preprocess_only() {
         .stdout(predicate::str::contains("-v, --verbose"));
 }
 
+/// Each `HCL_*` environment variable behaves like the flag it stands in for
+#[test]
+fn cli_env_vars_match_flags() {
+    use std::io::Write;
+
+    let mut tmp = tempfile::NamedTempFile::new().expect("create temp help");
+    writeln!(
+        tmp,
+        "USAGE: mytool [OPTIONS] [COMMAND]\n\nOPTIONS:\n  -v, --verbose  be verbose\n\nSUBCOMMANDS:\n  run   Run things\n  build Build things",
+    )
+    .unwrap();
+    let path = tmp.path().to_str().unwrap().to_string();
+
+    let run = |args: &[&str], envs: &[(&str, &str)]| -> String {
+        let mut cmd = cargo_bin_cmd!("d2o");
+        for var in [
+            "HCL_FORMAT",
+            "HCL_DEPTH",
+            "HCL_SKIP_MAN",
+            "HCL_BASH_COMPAT",
+            "HCL_TAB_WIDTH",
+        ] {
+            cmd.env_remove(var);
+        }
+        let assert = cmd
+            .args(["--cache", "false"])
+            .args(args)
+            .envs(envs.iter().copied())
+            .assert()
+            .success();
+        String::from_utf8(assert.get_output().stdout.clone()).unwrap()
+    };
+
+    let json = run(&["--file", &path, "--format", "json"], &[]);
+    assert_eq!(run(&["--file", &path], &[("HCL_FORMAT", "json")]), json);
+    assert_eq!(
+        run(
+            &["--file", &path, "--format", "json"],
+            &[("HCL_FORMAT", "yaml")]
+        ),
+        json
+    );
+
+    let shallow = run(&["--file", &path, "--format", "json", "--depth", "0"], &[]);
+    assert_ne!(shallow, json);
+    assert_eq!(
+        run(
+            &["--file", &path],
+            &[("HCL_FORMAT", "json"), ("HCL_DEPTH", "0")]
+        ),
+        shallow
+    );
+
+    let compat = run(
+        &[
+            "--file",
+            &path,
+            "--format",
+            "bash",
+            "--bash-completion-compat",
+        ],
+        &[],
+    );
+    assert!(compat.contains("__ltrim_colon_completions"));
+    assert_eq!(
+        run(
+            &["--file", &path],
+            &[("HCL_FORMAT", "bash"), ("HCL_BASH_COMPAT", "1")]
+        ),
+        compat
+    );
+
+    assert_eq!(
+        run(&["--command", "echo"], &[("HCL_SKIP_MAN", "true")]),
+        run(&["--command", "echo", "--skip-man"], &[])
+    );
+
+    let mut tabbed = tempfile::NamedTempFile::new().expect("create temp help");
+    writeln!(tabbed, "OPTIONS:\n  -v\tbe verbose").unwrap();
+    let tabbed = tabbed.path().to_str().unwrap().to_string();
+    let narrow = run(&["--file", &tabbed, "--debug", "--tab-width", "1"], &[]);
+    assert_eq!(narrow.trim(), "-v be verbose");
+    assert_ne!(run(&["--file", &tabbed, "--debug"], &[]), narrow);
+    assert_eq!(
+        run(&["--file", &tabbed, "--debug"], &[("HCL_TAB_WIDTH", "1")]),
+        narrow
+    );
+}
+
 /// Smoke-test --command echo with skip_man so it uses --help
 #[test]
 fn cli_command_echo_native() {
@@ -164,6 +356,140 @@ fn cli_command_echo_native() {
         .success();
 }
 
+/// --version-from-command stores the first line of `<cmd> --version`
+#[cfg(unix)]
+#[test]
+fn cli_version_from_command() {
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::TempDir::new().expect("create temp dir");
+    let script = dir.path().join("vercmd");
+    let mut file = std::fs::File::create(&script).expect("create script");
+    writeln!(
+        file,
+        "#!/bin/sh\nif [ \"$1\" = --version ]; then echo 'vercmd 2.4.1'; exit 0; fi\necho 'Usage: vercmd [OPTIONS]'\necho\necho '  -v, --verbose  be verbose'"
+    )
+    .unwrap();
+    drop(file);
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let subcommand = format!("{} run", script.to_str().unwrap());
+
+    // For --subcommand input the version is only fetched with the flag
+    let mut cmd = cargo_bin_cmd!("d2o");
+    cmd.args([
+        "--subcommand",
+        &subcommand,
+        "--skip-man",
+        "--cache",
+        "false",
+        "--json",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("\"version\"").not());
+
+    let mut cmd = cargo_bin_cmd!("d2o");
+    cmd.args([
+        "--subcommand",
+        &subcommand,
+        "--skip-man",
+        "--version-from-command",
+        "--cache",
+        "false",
+        "--json",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("\"version\": \"vercmd 2.4.1\""));
+
+    let mut cmd = cargo_bin_cmd!("d2o");
+    cmd.args([
+        "--subcommand",
+        &subcommand,
+        "--skip-man",
+        "--version-from-command",
+        "--cache",
+        "false",
+        "--format",
+        "native",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("Version:  vercmd 2.4.1"));
+}
+
+/// A cached run without --version-from-command does not hide the version
+/// from a later run with it
+#[cfg(unix)]
+#[test]
+fn cli_version_from_command_bypasses_cached_entry() {
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+
+    let home = tempfile::TempDir::new().expect("create temp home");
+    let dir = tempfile::TempDir::new().expect("create temp dir");
+    let script = dir.path().join("vercmd");
+    let mut file = std::fs::File::create(&script).expect("create script");
+    writeln!(
+        file,
+        "#!/bin/sh\nif [ \"$1\" = --version ]; then echo 'vercmd 2.4.1'; exit 0; fi\necho 'Usage: vercmd [OPTIONS]'\necho\necho '  -v, --verbose  be verbose'"
+    )
+    .unwrap();
+    drop(file);
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let subcommand = format!("{} run", script.to_str().unwrap());
+
+    let run = |extra: &[&str]| {
+        let mut cmd = cargo_bin_cmd!("d2o");
+        cmd.env("HOME", home.path())
+            .env("USERPROFILE", home.path())
+            .env("XDG_CACHE_HOME", home.path().join("cache"))
+            .args(["--subcommand", &subcommand, "--skip-man", "--json"])
+            .args(extra)
+            .assert()
+            .success()
+    };
+
+    run(&[]).stdout(predicate::str::contains("\"version\"").not());
+    run(&["--version-from-command"])
+        .stdout(predicate::str::contains("\"version\": \"vercmd 2.4.1\""));
+}
+
+/// --no-filter keeps options that have no description
+#[test]
+fn cli_no_filter_keeps_undocumented_options() {
+    use std::io::Write;
+
+    let mut tmp = tempfile::NamedTempFile::new().expect("create temp help");
+    writeln!(
+        tmp,
+        "Usage: mycmd [OPTIONS]\n\nOptions:\n  -v, --verbose  be verbose\n\n  -q"
+    )
+    .unwrap();
+    let path = tmp.path().to_str().unwrap().to_string();
+
+    let mut cmd = cargo_bin_cmd!("d2o");
+    cmd.args(["--file", &path, "--format", "fish", "--cache", "false"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("-s 'q'").not());
+
+    let mut cmd = cargo_bin_cmd!("d2o");
+    cmd.args([
+        "--file",
+        &path,
+        "--format",
+        "fish",
+        "--cache",
+        "false",
+        "--no-filter",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("-s 'q'"));
+}
+
 /// Test --loadjson path end-to-end
 #[test]
 fn cli_loadjson_native_output() {
@@ -178,11 +504,10 @@ fn cli_loadjson_native_output() {
                 EcoString::from("-v"),
                 d2o::types::OptNameType::ShortType,
             )],
-            argument: EcoString::new(),
             description: EcoString::from("Verbose"),
+            ..Default::default()
         }],
-        subcommands: eco_vec![],
-        version: EcoString::new(),
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&cmd_struct).unwrap();
@@ -196,3 +521,286 @@ fn cli_loadjson_native_output() {
         .success()
         .stdout(predicate::str::contains("Name:  jsoncmd").and(predicate::str::contains("-v (")));
 }
+
+/// --inherit-options offers top-level options in zsh subcommand functions
+#[test]
+fn cli_zsh_inherit_options() {
+    use std::io::Write;
+
+    let mut tmp = tempfile::NamedTempFile::new().expect("create json temp");
+    write!(
+        tmp,
+        r#"{{"name":"git","description":"","usage":"","options":[{{"names":["--no-pager"],"argument":"","description":"Do not page"}}],"subcommands":[{{"name":"log","description":"","usage":"","options":[{{"names":["--oneline"],"argument":"","description":"One line"}}],"subcommands":[]}}]}}"#
+    )
+    .unwrap();
+    let path = tmp.path().to_str().unwrap().to_string();
+
+    let sub_fn = |stdout: &[u8]| {
+        let stdout = String::from_utf8(stdout.to_vec()).unwrap();
+        let start = stdout.find("_git_log() {").expect("subcommand function");
+        let end = stdout[start..].find("\n}\n").unwrap();
+        stdout[start..start + end].to_string()
+    };
+
+    let mut cmd = cargo_bin_cmd!("d2o");
+    let plain = cmd
+        .args(["--loadjson", &path, "--format", "zsh"])
+        .assert()
+        .success();
+    assert!(!sub_fn(&plain.get_output().stdout).contains("--no-pager"));
+
+    let mut cmd = cargo_bin_cmd!("d2o");
+    let inherited = cmd
+        .args(["--loadjson", &path, "--format", "zsh", "--inherit-options"])
+        .assert()
+        .success();
+    assert!(sub_fn(&inherited.get_output().stdout).contains("'--no-pager[Do not page]'"));
+}
+
+/// Ensure --ndjson emits one JSON object per line
+#[test]
+fn cli_file_ndjson_output() {
+    use std::io::Write;
+
+    let mut tmp = tempfile::NamedTempFile::new().expect("create temp help");
+    writeln!(
+        tmp,
+        "USAGE: mycmd [OPTIONS]\n\nOPTIONS:\n  -v, --verbose\n      Be verbose\n  -q, --quiet\n      Be quiet"
+    )
+    .unwrap();
+    let path = tmp.path().to_str().unwrap().to_string();
+
+    let mut cmd = cargo_bin_cmd!("d2o");
+    let assert = cmd
+        .args(["--file", &path, "--ndjson", "--cache", "false"])
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let lines: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("valid json line"))
+        .collect();
+    assert_eq!(lines.len(), 3, "one header line and one line per option");
+    assert!(lines[0]["usage"].as_str().unwrap().contains("mycmd"));
+    assert_eq!(lines[1]["names"], serde_json::json!(["--verbose", "-v"]));
+    assert_eq!(lines[1]["description"], "Be verbose");
+    assert_eq!(lines[2]["names"], serde_json::json!(["--quiet", "-q"]));
+}
+
+/// Generate YAML from a help file, load it back with --loadyaml and compare
+#[test]
+fn cli_yaml_roundtrip_via_loadyaml() {
+    use std::io::Write;
+
+    let mut tmp = tempfile::NamedTempFile::new().expect("create temp help");
+    writeln!(
+        tmp,
+        "USAGE: mycmd [OPTIONS]\n\nOPTIONS:\n  -v, --verbose\n      be verbose\n  -o, --output FILE\n      write to FILE"
+    )
+    .unwrap();
+    let path = tmp.path().to_str().unwrap().to_string();
+
+    let mut cmd = cargo_bin_cmd!("d2o");
+    let assert = cmd
+        .args(["--file", &path, "--format", "yaml", "--cache", "false"])
+        .assert()
+        .success();
+    let yaml = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(yaml.contains("--verbose"));
+
+    let mut yaml_tmp = tempfile::NamedTempFile::new().expect("create yaml temp");
+    write!(yaml_tmp, "{}", yaml).unwrap();
+    let yaml_path = yaml_tmp.path().to_str().unwrap().to_string();
+
+    let mut cmd = cargo_bin_cmd!("d2o");
+    let assert = cmd
+        .args(["--loadyaml", &yaml_path, "--format", "yaml"])
+        .assert()
+        .success();
+    let reloaded = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert_eq!(yaml, reloaded);
+}
+
+/// --diff compares the parsed command against an older Command JSON
+#[test]
+fn cli_diff_against_old_json() {
+    use std::io::Write;
+
+    let mut old_json = tempfile::NamedTempFile::new().expect("create temp json");
+    write!(
+        old_json,
+        r#"{{"name":"mytool","description":"","usage":"","options":[
+            {{"names":["-v","--verbose"],"argument":"","description":"be verbose"}},
+            {{"names":["--legacy"],"argument":"","description":"old behaviour"}}
+        ]}}"#
+    )
+    .unwrap();
+
+    let mut help = tempfile::NamedTempFile::new().expect("create temp help");
+    writeln!(
+        help,
+        "USAGE: mytool [OPTIONS]\n\nOPTIONS:\n  -v, --verbose\n      be verbose\n  -q, --quiet\n      be quiet"
+    )
+    .unwrap();
+
+    let mut cmd = cargo_bin_cmd!("d2o");
+    cmd.args([
+        "--cache",
+        "false",
+        "--file",
+        help.path().to_str().unwrap(),
+        "--diff",
+        old_json.path().to_str().unwrap(),
+    ])
+    .assert()
+    .success()
+    .stdout(
+        predicate::str::contains("+ --quiet, -q  be quiet")
+            .and(predicate::str::contains("- --legacy  old behaviour"))
+            .and(predicate::str::contains("--verbose").not()),
+    );
+}
+
+/// --multi-format writes one completion script per shell into the directory
+#[test]
+fn cli_multi_format_writes_all_shells() {
+    use std::io::Write;
+
+    let mut help = tempfile::NamedTempFile::new().expect("create temp help");
+    writeln!(
+        help,
+        "USAGE: mytool [OPTIONS]\n\nOPTIONS:\n  -v, --verbose\n      be verbose"
+    )
+    .unwrap();
+    let out_dir = tempfile::tempdir().expect("create output dir");
+    let out_path = out_dir.path().join("completions");
+
+    let mut cmd = cargo_bin_cmd!("d2o");
+    cmd.args([
+        "--cache",
+        "false",
+        "--file",
+        help.path().to_str().unwrap(),
+        "--multi-format",
+        out_path.to_str().unwrap(),
+    ])
+    .assert()
+    .success();
+
+    let name = d2o::Postprocessor::normalize_command_name(
+        help.path().file_name().unwrap().to_str().unwrap(),
+    );
+    for ext in ["bash", "zsh", "fish", "elv", "nu", "ps1"] {
+        let path = out_path.join(format!("{}.{}", name, ext));
+        let content = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("missing {}: {}", path.display(), e));
+        assert!(content.contains("verbose"), "{} has no options", ext);
+    }
+    assert_eq!(std::fs::read_dir(&out_path).unwrap().count(), 6);
+}
+
+/// --fish-completion-dir and --zsh-completion-dir install the script instead of printing it
+#[test]
+fn cli_completion_dirs_write_scripts() {
+    use std::io::Write;
+
+    let mut help = tempfile::NamedTempFile::new().expect("create temp help");
+    writeln!(
+        help,
+        "USAGE: mytool [OPTIONS]\n\nOPTIONS:\n  -v, --verbose\n      be verbose"
+    )
+    .unwrap();
+    let help_path = help.path().to_str().unwrap();
+    let name = d2o::Postprocessor::normalize_command_name(
+        help.path().file_name().unwrap().to_str().unwrap(),
+    );
+    let out_dir = tempfile::tempdir().expect("create output dir");
+
+    for (format, flag, file_name) in [
+        ("fish", "--fish-completion-dir", format!("{}.fish", name)),
+        ("zsh", "--zsh-completion-dir", format!("_{}", name)),
+    ] {
+        let dir = out_dir.path().join(format);
+        let assert = cargo_bin_cmd!("d2o")
+            .args(["--cache", "false", "--file", help_path, "--format", format])
+            .args([flag, dir.to_str().unwrap()])
+            .assert()
+            .success();
+
+        let path = dir.join(&file_name);
+        let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+        assert_eq!(stdout.trim(), path.to_str().unwrap());
+        let content = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("missing {}: {}", path.display(), e));
+        assert!(content.contains("verbose"), "{} has no options", format);
+    }
+
+    // The directory flags only apply to their own shell
+    cargo_bin_cmd!("d2o")
+        .args(["--file", help_path, "--format", "bash"])
+        .args(["--fish-completion-dir", out_dir.path().to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("require --format fish"));
+}
+
+/// A path given as --command installs under its file name, inside the directory
+#[cfg(unix)]
+#[test]
+fn cli_completion_dir_uses_command_file_name() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let root = tempfile::tempdir().expect("create temp dir");
+    let bin = root.path().join("bin");
+    std::fs::create_dir(&bin).unwrap();
+    let tool = bin.join("tool");
+    std::fs::write(
+        &tool,
+        "#!/bin/sh\nprintf 'Usage: tool [OPTIONS]\\n\\nOptions:\\n  -v, --verbose\\n      be verbose\\n'\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let out = root.path().join("out");
+
+    cargo_bin_cmd!("d2o")
+        .args(["--cache", "false", "--skip-man", "--format", "fish"])
+        .args(["--command", tool.to_str().unwrap()])
+        .args(["--fish-completion-dir", out.to_str().unwrap()])
+        .assert()
+        .success();
+
+    assert!(out.join("tool.fish").exists());
+    assert!(!bin.join("tool.fish").exists());
+}
+
+/// --user-fish installs into ~/.config/fish/completions
+#[cfg(unix)]
+#[test]
+fn cli_user_fish_writes_to_home_config() {
+    use std::io::Write;
+
+    let mut help = tempfile::NamedTempFile::new().expect("create temp help");
+    writeln!(
+        help,
+        "USAGE: mytool [OPTIONS]\n\nOPTIONS:\n  -v, --verbose\n      be verbose"
+    )
+    .unwrap();
+    let name = d2o::Postprocessor::normalize_command_name(
+        help.path().file_name().unwrap().to_str().unwrap(),
+    );
+    let home = tempfile::tempdir().expect("create home dir");
+
+    cargo_bin_cmd!("d2o")
+        .env("HOME", home.path())
+        .args(["--cache", "false", "--file", help.path().to_str().unwrap()])
+        .args(["--format", "fish", "--user-fish"])
+        .assert()
+        .success();
+
+    let path = home
+        .path()
+        .join(".config/fish/completions")
+        .join(format!("{}.fish", name));
+    assert!(path.exists(), "missing {}", path.display());
+}
@@ -71,6 +71,7 @@ fn opt_strategy() -> impl Strategy<Value = Opt> {
             names: names.into_iter().collect::<EcoVec<_>>(),
             argument,
             description,
+            ..Default::default()
         })
 }
 
@@ -84,10 +85,8 @@ fn command_strategy() -> impl Strategy<Value = Command> {
         .prop_map(|(name, description, options)| Command {
             name: EcoString::from(name),
             description: EcoString::from(description),
-            usage: EcoString::new(),
             options: options.into_iter().collect::<EcoVec<_>>(),
-            subcommands: eco_vec![],
-            version: EcoString::new(),
+            ..Default::default()
         })
 }
 
@@ -291,14 +290,14 @@ proptest! {
     fn unicode_spaces_to_ascii_is_idempotent(s in ".*") {
         let once = Postprocessor::unicode_spaces_to_ascii(&s);
         let twice = Postprocessor::unicode_spaces_to_ascii(&once);
-        prop_assert_eq!(once, twice);
+        prop_assert_eq!(&once, &twice);
     }
 
     #[test]
     fn remove_bullets_is_idempotent(s in ".*") {
         let once = Postprocessor::remove_bullets(&s);
         let twice = Postprocessor::remove_bullets(&once);
-        prop_assert_eq!(once, twice);
+        prop_assert_eq!(&once, &twice);
     }
 
     #[test]
@@ -332,19 +331,8 @@ proptest! {
 
     #[test]
     fn handles_unicode_in_descriptions(desc in "[\\p{L}\\p{N}\\s]{0,50}") {
-        let opt = Opt {
-            names: eco_vec![OptName::new(EcoString::from("-u"), OptNameType::ShortType)],
-            argument: EcoString::new(),
-            description: EcoString::from(desc.clone()),
-        };
-        let cmd = Command {
-            name: EcoString::from("unicode-test"),
-            description: EcoString::new(),
-            usage: EcoString::new(),
-            options: eco_vec![opt],
-            subcommands: eco_vec![],
-            version: EcoString::new(),
-        };
+        let opt = Opt { names: eco_vec![OptName::new(EcoString::from("-u"), OptNameType::ShortType)], description: EcoString::from(desc.clone()), ..Default::default() };
+        let cmd = Command { name: EcoString::from("unicode-test"), options: eco_vec![opt], ..Default::default() };
 
         // All generators should handle unicode without panicking
         let _ = BashGenerator::generate(&cmd);
@@ -363,19 +351,8 @@ proptest! {
     #[test]
     fn handles_very_long_descriptions(len in 100usize..1000) {
         let desc = "a".repeat(len);
-        let opt = Opt {
-            names: eco_vec![OptName::new(EcoString::from("--long-desc"), OptNameType::LongType)],
-            argument: EcoString::new(),
-            description: EcoString::from(desc),
-        };
-        let cmd = Command {
-            name: EcoString::from("long-test"),
-            description: EcoString::new(),
-            usage: EcoString::new(),
-            options: eco_vec![opt],
-            subcommands: eco_vec![],
-            version: EcoString::new(),
-        };
+        let opt = Opt { names: eco_vec![OptName::new(EcoString::from("--long-desc"), OptNameType::LongType)], description: EcoString::from(desc), ..Default::default() };
+        let cmd = Command { name: EcoString::from("long-test"), options: eco_vec![opt], ..Default::default() };
 
         // Should handle long descriptions without issues
         let json = JsonGenerator::generate(&cmd);
@@ -385,21 +362,10 @@ proptest! {
     #[test]
     fn handles_many_options(count in 50usize..200) {
         let options: EcoVec<Opt> = (0..count)
-            .map(|i| Opt {
-                names: eco_vec![OptName::new(EcoString::from(format!("--opt-{}", i)), OptNameType::LongType)],
-                argument: EcoString::new(),
-                description: EcoString::from(format!("Option {}", i)),
-            })
+            .map(|i| Opt { names: eco_vec![OptName::new(EcoString::from(format!("--opt-{}", i)), OptNameType::LongType)], description: EcoString::from(format!("Option {}", i)), ..Default::default() })
             .collect();
 
-        let cmd = Command {
-            name: EcoString::from("many-opts"),
-            description: EcoString::new(),
-            usage: EcoString::new(),
-            options,
-            subcommands: eco_vec![],
-            version: EcoString::new(),
-        };
+        let cmd = Command { name: EcoString::from("many-opts"), options, ..Default::default() };
 
         // Should handle many options
         let json = JsonGenerator::generate(&cmd);
@@ -1,10 +1,10 @@
 use clap::Parser as ClapParser;
 use d2o::types::OptNameType;
 use d2o::{
-    BashGenerator, Cli, Command, ElvishGenerator, FishGenerator, NushellGenerator, Opt, OptName,
-    Parser as D2oParser, ZshGenerator,
+    BashGenerator, Cli, Command, ElvishGenerator, FishGenerator, MarkdownGenerator,
+    NushellGenerator, Opt, OptName, Parser as D2oParser, TcshGenerator, ZshGenerator,
 };
-use ecow::{EcoString, eco_vec};
+use ecow::{EcoString, EcoVec, eco_vec};
 
 #[test]
 fn test_parse_ls_help_snapshot() {
@@ -30,17 +30,50 @@ fn test_zsh_generator_with_descriptions_snapshot() {
                 OptName::new(EcoString::from("-v"), OptNameType::ShortType),
                 OptName::new(EcoString::from("--verbose"), OptNameType::LongType),
             ],
-            argument: EcoString::new(),
             description: EcoString::from("Enable verbose mode"),
+            ..Default::default()
         }],
-        subcommands: eco_vec![],
-        version: EcoString::new(),
+        ..Default::default()
     };
 
     let output = ZshGenerator::generate(&cmd);
     insta::assert_snapshot!(output);
 }
 
+#[test]
+fn test_zsh_generator_with_comments_snapshot() {
+    let cmd = Command {
+        name: EcoString::from("test"),
+        description: EcoString::from("Test command"),
+        usage: EcoString::from("test [OPTIONS]"),
+        options: eco_vec![
+            Opt {
+                names: eco_vec![
+                    OptName::new(EcoString::from("-v"), OptNameType::ShortType),
+                    OptName::new(EcoString::from("--verbose"), OptNameType::LongType),
+                ],
+                description: EcoString::from("Enable verbose mode. Repeat for more output"),
+                ..Default::default()
+            },
+            Opt {
+                names: eco_vec![OptName::new(
+                    EcoString::from("--config"),
+                    OptNameType::LongType
+                )],
+                argument: EcoString::from("FILE"),
+                description: EcoString::from(
+                    "Read configuration from the given file instead of searching the default locations"
+                ),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    let output = ZshGenerator::generate_with_comments(&cmd, true);
+    insta::assert_snapshot!(output);
+}
+
 #[test]
 fn test_parse_docker_help_snapshot() {
     let docker_help = r#"
@@ -65,11 +98,44 @@ fn test_elvish_generator_snapshot() {
                 OptName::new(EcoString::from("-v"), OptNameType::ShortType),
                 OptName::new(EcoString::from("--verbose"), OptNameType::LongType),
             ],
-            argument: EcoString::new(),
             description: EcoString::from("Enable verbose mode"),
+            ..Default::default()
         }],
-        subcommands: eco_vec![],
-        version: EcoString::new(),
+        ..Default::default()
+    };
+
+    let output = ElvishGenerator::generate(&cmd);
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn test_elvish_generator_with_subcommands_snapshot() {
+    let cmd = Command {
+        name: EcoString::from("test"),
+        description: EcoString::from("Test command"),
+        usage: EcoString::from("test [OPTIONS] <COMMAND>"),
+        options: eco_vec![Opt {
+            names: eco_vec![
+                OptName::new(EcoString::from("-v"), OptNameType::ShortType),
+                OptName::new(EcoString::from("--verbose"), OptNameType::LongType),
+            ],
+            description: EcoString::from("Enable verbose mode"),
+            ..Default::default()
+        }],
+        subcommands: eco_vec![Command {
+            name: EcoString::from("run"),
+            description: EcoString::from("Run the project. Builds first"),
+            options: eco_vec![Opt {
+                names: eco_vec![OptName::new(
+                    EcoString::from("--release"),
+                    OptNameType::LongType
+                )],
+                description: EcoString::from("Build in release mode"),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+        ..Default::default()
     };
 
     let output = ElvishGenerator::generate(&cmd);
@@ -87,17 +153,130 @@ fn test_nushell_generator_snapshot() {
                 OptName::new(EcoString::from("-v"), OptNameType::ShortType),
                 OptName::new(EcoString::from("--verbose"), OptNameType::LongType),
             ],
-            argument: EcoString::new(),
             description: EcoString::from("Enable verbose mode"),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let output = NushellGenerator::generate(&cmd);
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn test_nushell_generator_possible_values_snapshot() {
+    let cmd = Command {
+        name: EcoString::from("test"),
+        description: EcoString::from("Test command"),
+        usage: EcoString::from("test [OPTIONS]"),
+        options: eco_vec![Opt {
+            names: eco_vec![OptName::new(
+                EcoString::from("--color"),
+                OptNameType::LongType
+            )],
+            argument: EcoString::from("WHEN"),
+            description: EcoString::from("When to use colors"),
+            possible_values: eco_vec![
+                EcoString::from("always"),
+                EcoString::from("auto"),
+                EcoString::from("never"),
+            ],
+            ..Default::default()
         }],
-        subcommands: eco_vec![],
-        version: EcoString::new(),
+        ..Default::default()
     };
 
     let output = NushellGenerator::generate(&cmd);
     insta::assert_snapshot!(output);
 }
 
+#[test]
+fn test_nushell_generator_with_subcommands_snapshot() {
+    let verbose = Opt {
+        names: eco_vec![
+            OptName::new(EcoString::from("-v"), OptNameType::ShortType),
+            OptName::new(EcoString::from("--verbose"), OptNameType::LongType),
+        ],
+        description: EcoString::from("Enable verbose mode"),
+        ..Default::default()
+    };
+    let cmd = Command {
+        name: EcoString::from("test"),
+        description: EcoString::from("Test command"),
+        usage: EcoString::from("test [OPTIONS] <COMMAND>"),
+        options: eco_vec![verbose.clone()],
+        subcommands: eco_vec![Command {
+            name: EcoString::from("run"),
+            description: EcoString::from("Run things"),
+            options: eco_vec![Opt {
+                names: eco_vec![OptName::new(
+                    EcoString::from("--jobs"),
+                    OptNameType::LongType
+                )],
+                argument: EcoString::from("N"),
+                description: EcoString::from("Number of jobs"),
+                ..Default::default()
+            }],
+            subcommands: eco_vec![Command {
+                name: EcoString::from("all"),
+                description: EcoString::from("Run everything"),
+                options: eco_vec![verbose],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let output = NushellGenerator::generate_with_subcommands(&cmd);
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn test_zsh_generator_with_subcommands_snapshot() {
+    let cmd = Command {
+        name: EcoString::from("test"),
+        description: EcoString::from("Test command"),
+        usage: EcoString::from("test [OPTIONS] <COMMAND>"),
+        options: eco_vec![Opt {
+            names: eco_vec![
+                OptName::new(EcoString::from("-v"), OptNameType::ShortType),
+                OptName::new(EcoString::from("--verbose"), OptNameType::LongType),
+            ],
+            description: EcoString::from("Enable verbose mode"),
+            ..Default::default()
+        }],
+        subcommands: eco_vec![
+            Command {
+                name: EcoString::from("run"),
+                description: EcoString::from("Run things"),
+                options: eco_vec![Opt {
+                    names: eco_vec![OptName::new(
+                        EcoString::from("--jobs"),
+                        OptNameType::LongType
+                    )],
+                    argument: EcoString::from("N"),
+                    description: EcoString::from("Number of jobs"),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            Command {
+                name: EcoString::from("clean"),
+                description: EcoString::from("Remove build artifacts"),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    let output = ZshGenerator::generate(&cmd);
+    assert!(output.contains("_arguments -C"));
+    assert!(output.contains("case $state in"));
+    assert!(output.contains("_test_run()"));
+    insta::assert_snapshot!(output);
+}
+
 #[test]
 fn test_cli_short_f_and_conflicts() {
     // -f should work as shorthand for --file
@@ -109,6 +288,45 @@ fn test_cli_short_f_and_conflicts() {
     assert!(res.is_err());
 }
 
+#[test]
+fn test_cli_format_typo_suggests_closest_match() {
+    let err = Cli::try_parse_from(["d2o", "--command", "ls", "--format", "jsn"]).unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains("similar value exists: 'json'"), "{msg}");
+
+    let err = Cli::try_parse_from(["d2o", "--command", "ls", "--format", "basj"]).unwrap_err();
+    assert!(err.to_string().contains("'bash'"));
+
+    assert_eq!(d2o::cli::closest_format("nushel"), Some("nushell"));
+    assert_eq!(d2o::cli::closest_format("plaintext"), None);
+}
+
+#[test]
+fn test_cli_subcommand_parts() {
+    let hyphen = Cli::try_parse_from(["d2o", "--subcommand", "git-log"]).unwrap();
+    assert_eq!(hyphen.subcommand_parts(), Some(("git", "log")));
+
+    let space = Cli::try_parse_from(["d2o", "--subcommand", "cargo build-script"]).unwrap();
+    assert_eq!(space.subcommand_parts(), Some(("cargo", "build-script")));
+
+    let explicit = Cli::try_parse_from([
+        "d2o",
+        "--subcommand",
+        "my-tool-run",
+        "--subcommand-sep",
+        "-",
+    ])
+    .unwrap();
+    assert_eq!(explicit.subcommand_parts(), Some(("my", "tool-run")));
+
+    let missing = Cli::try_parse_from(["d2o", "--subcommand", "git"]).unwrap();
+    assert_eq!(missing.subcommand_parts(), None);
+
+    assert!(
+        Cli::try_parse_from(["d2o", "--subcommand", "git log", "--subcommand-sep", "/"]).is_err()
+    );
+}
+
 #[test]
 fn test_cli_effective_format_and_helpers() {
     let cli = Cli::try_parse_from(["d2o", "--command", "ls", "--format", "bash"]).unwrap();
@@ -125,6 +343,74 @@ fn test_cli_effective_format_and_helpers() {
     assert_eq!(cli_json.effective_format(), "json");
 }
 
+#[test]
+fn test_cli_validate_semantic_conflicts() {
+    let ok = Cli::try_parse_from(["d2o", "-c", "ls", "-o", "bash", "-b", "-w"]).unwrap();
+    assert!(ok.validate().is_ok());
+
+    let compat = Cli::try_parse_from(["d2o", "-c", "ls", "-o", "zsh", "-b"]).unwrap();
+    let err = compat.validate().unwrap_err().to_string();
+    assert!(err.contains("--bash-completion-compat"));
+
+    let compat_json = Cli::try_parse_from(["d2o", "-c", "ls", "-o", "bash", "-b", "-j"]).unwrap();
+    assert!(compat_json.validate().is_err());
+
+    let write_native = Cli::try_parse_from(["d2o", "-c", "ls", "-w"]).unwrap();
+    let err = write_native.validate().unwrap_err().to_string();
+    assert!(err.contains("--write"));
+
+    let write_json = Cli::try_parse_from(["d2o", "-c", "ls", "-o", "json", "-w"]).unwrap();
+    assert!(write_json.validate().is_err());
+
+    let debug_list = Cli::try_parse_from(["d2o", "-c", "ls", "-d", "-L"]).unwrap();
+    assert!(debug_list.validate().is_err());
+
+    let comments_fish =
+        Cli::try_parse_from(["d2o", "-c", "ls", "-o", "fish", "--comments"]).unwrap();
+    assert!(comments_fish.validate().is_err());
+
+    let comments_zsh = Cli::try_parse_from(["d2o", "-c", "ls", "-o", "zsh", "--comments"]).unwrap();
+    assert!(comments_zsh.validate().is_ok());
+
+    let grouped_bash = Cli::try_parse_from(["d2o", "-c", "ls", "-o", "bash", "--grouped"]).unwrap();
+    assert!(grouped_bash.validate().is_err());
+
+    let grouped_zsh = Cli::try_parse_from(["d2o", "-c", "ls", "-o", "zsh", "--grouped"]).unwrap();
+    assert!(grouped_zsh.validate().is_ok());
+}
+
+#[test]
+fn test_markdown_table_snapshot() {
+    let cmd = Command {
+        name: EcoString::from("test"),
+        description: EcoString::from("Test command"),
+        usage: EcoString::from("test [OPTIONS]"),
+        options: eco_vec![
+            Opt {
+                names: eco_vec![
+                    OptName::new(EcoString::from("-v"), OptNameType::ShortType),
+                    OptName::new(EcoString::from("--verbose"), OptNameType::LongType),
+                ],
+                description: EcoString::from("Enable verbose mode"),
+                ..Default::default()
+            },
+            Opt {
+                names: eco_vec![OptName::new(
+                    EcoString::from("--color"),
+                    OptNameType::LongType
+                )],
+                argument: EcoString::from("WHEN"),
+                description: EcoString::from("One of always|auto|never,\n  defaults to auto"),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    let output = MarkdownGenerator::generate_table(&cmd);
+    insta::assert_snapshot!(output);
+}
+
 #[test]
 fn test_bash_generator_snapshot() {
     let cmd = Command {
@@ -136,17 +422,61 @@ fn test_bash_generator_snapshot() {
                 OptName::new(EcoString::from("-v"), OptNameType::ShortType),
                 OptName::new(EcoString::from("--verbose"), OptNameType::LongType),
             ],
-            argument: EcoString::new(),
             description: EcoString::from("Enable verbose mode"),
+            ..Default::default()
         }],
-        subcommands: eco_vec![],
-        version: EcoString::new(),
+        ..Default::default()
     };
 
     let output = BashGenerator::generate(&cmd);
     insta::assert_snapshot!(output);
 }
 
+#[test]
+fn test_bash_generator_path_arguments_snapshot() {
+    let cmd = Command {
+        name: EcoString::from("test"),
+        description: EcoString::from("Test command"),
+        usage: EcoString::from("test [OPTIONS]"),
+        options: eco_vec![
+            Opt {
+                names: eco_vec![
+                    OptName::new(EcoString::from("-c"), OptNameType::ShortType),
+                    OptName::new(EcoString::from("--config"), OptNameType::LongType),
+                ],
+                argument: EcoString::from("FILE"),
+                description: EcoString::from("Read configuration from FILE"),
+                ..Default::default()
+            },
+            Opt {
+                names: eco_vec![OptName::new(
+                    EcoString::from("--output"),
+                    OptNameType::LongType
+                )],
+                argument: EcoString::from("DIR"),
+                description: EcoString::from("Write results into DIR"),
+                ..Default::default()
+            },
+            Opt {
+                names: eco_vec![OptName::new(
+                    EcoString::from("--jobs"),
+                    OptNameType::LongType
+                )],
+                argument: EcoString::from("N"),
+                description: EcoString::from("Number of jobs"),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    let output = BashGenerator::generate(&cmd);
+    assert!(output.contains("-c|--config)"));
+    assert!(output.contains("compgen -d"));
+    assert!(!output.contains("--jobs)"));
+    insta::assert_snapshot!(output);
+}
+
 #[test]
 fn test_bash_generator_compat_snapshot() {
     let cmd = Command {
@@ -158,17 +488,50 @@ fn test_bash_generator_compat_snapshot() {
                 OptName::new(EcoString::from("-v"), OptNameType::ShortType),
                 OptName::new(EcoString::from("--verbose"), OptNameType::LongType),
             ],
-            argument: EcoString::new(),
             description: EcoString::from("Enable verbose mode"),
+            ..Default::default()
         }],
-        subcommands: eco_vec![],
-        version: EcoString::new(),
+        ..Default::default()
     };
 
     let output = BashGenerator::generate_with_compat(&cmd, true);
     insta::assert_snapshot!(output);
 }
 
+#[test]
+fn test_bash_generator_with_comments_snapshot() {
+    let cmd = Command {
+        name: EcoString::from("test"),
+        description: EcoString::from("Test command"),
+        usage: EcoString::from("test [OPTIONS]"),
+        options: eco_vec![
+            Opt {
+                names: eco_vec![
+                    OptName::new(EcoString::from("-v"), OptNameType::ShortType),
+                    OptName::new(EcoString::from("--verbose"), OptNameType::LongType),
+                ],
+                description: EcoString::from("Enable verbose mode"),
+                ..Default::default()
+            },
+            Opt {
+                names: eco_vec![OptName::new(
+                    EcoString::from("--color"),
+                    OptNameType::LongType
+                )],
+                argument: EcoString::from("WHEN"),
+                description: EcoString::from(
+                    "Colorize the output. WHEN can be 'always', 'auto' or 'never'; defaults to 'auto' when unset"
+                ),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    let output = BashGenerator::generate_with_comments(&cmd);
+    insta::assert_snapshot!(output);
+}
+
 #[test]
 fn test_fish_generator_snapshot() {
     let cmd = Command {
@@ -182,11 +545,136 @@ fn test_fish_generator_snapshot() {
             ],
             argument: EcoString::from("FILE"),
             description: EcoString::from("Enable verbose mode using a file"),
+            ..Default::default()
         }],
-        subcommands: eco_vec![],
-        version: EcoString::new(),
+        ..Default::default()
     };
 
     let output = FishGenerator::generate(&cmd);
     insta::assert_snapshot!(output);
 }
+
+#[test]
+fn test_fish_generator_exclusions_snapshot() {
+    let opt = |short: &str, long: &str, desc: &str| Opt {
+        names: eco_vec![
+            OptName::new(EcoString::from(short), OptNameType::ShortType),
+            OptName::new(EcoString::from(long), OptNameType::LongType),
+        ],
+        description: EcoString::from(desc),
+        ..Default::default()
+    };
+    let cmd = Command {
+        name: EcoString::from("fmt"),
+        description: EcoString::from("Format data"),
+        usage: EcoString::from("fmt [OPTIONS]"),
+        options: eco_vec![
+            opt("-j", "--json", "Write JSON"),
+            opt("-y", "--yaml", "Write YAML"),
+            opt("-v", "--verbose", "Be verbose"),
+        ],
+        ..Default::default()
+    };
+
+    let output = FishGenerator::generate_with_exclusions(&cmd, &[("--json", "--yaml")]);
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn test_zsh_generator_possible_values_snapshot() {
+    let cmd = Command {
+        name: EcoString::from("log"),
+        description: EcoString::from("Write logs"),
+        usage: EcoString::from("log [OPTIONS]"),
+        options: eco_vec![
+            Opt {
+                names: eco_vec![
+                    OptName::new(EcoString::from("-l"), OptNameType::ShortType),
+                    OptName::new(EcoString::from("--level"), OptNameType::LongType),
+                ],
+                argument: EcoString::from("LEVEL"),
+                description: EcoString::from("Minimum level to log"),
+                possible_values: eco_vec![
+                    EcoString::from("debug"),
+                    EcoString::from("info"),
+                    EcoString::from("warn"),
+                    EcoString::from("error"),
+                ],
+                ..Default::default()
+            },
+            Opt {
+                names: eco_vec![OptName::new(
+                    EcoString::from("--quiet"),
+                    OptNameType::LongType
+                )],
+                description: EcoString::from("Log nothing"),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    let output = ZshGenerator::generate(&cmd);
+    insta::assert_snapshot!(output);
+}
+
+/// Same shape as `sample_command_medium` in `benches/parsing.rs`
+fn sample_command_medium() -> Command {
+    let options: EcoVec<Opt> = (0..50)
+        .map(|i| Opt {
+            names: eco_vec![OptName::new(
+                EcoString::from(format!("--opt-{}", i)),
+                OptNameType::LongType
+            )],
+            argument: if i % 3 == 0 {
+                EcoString::from("VALUE")
+            } else {
+                EcoString::new()
+            },
+            description: EcoString::from(format!("Option number {}", i)),
+            ..Default::default()
+        })
+        .collect();
+
+    let subcommands: EcoVec<Command> = (0..10)
+        .map(|i| Command {
+            name: EcoString::from(format!("sub{}", i)),
+            description: EcoString::from(format!("Subcommand {}", i)),
+            ..Default::default()
+        })
+        .collect();
+
+    Command {
+        name: EcoString::from("mediumcmd"),
+        description: EcoString::from("A medium-sized command"),
+        usage: EcoString::from("mediumcmd [OPTIONS] [COMMAND]"),
+        options,
+        subcommands,
+        version: EcoString::from("2.0.0"),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_tcsh_generator_snapshot() {
+    let output = TcshGenerator::generate(&sample_command_medium());
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn test_zsh_generator_subcommand_dispatch() {
+    let cmd = sample_command_medium();
+    let output = ZshGenerator::generate(&cmd);
+
+    assert!(output.contains("  _arguments -C -s -S $options \\\n    '1:command:->cmds' \\\n"));
+
+    let (_, args) = output
+        .split_once("    args)\n      case $line[1] in\n")
+        .expect("args state dispatching on the subcommand");
+    let (arms, _) = args.split_once("      esac").unwrap();
+    assert_eq!(arms.matches(";;").count(), cmd.subcommands.len());
+    for i in 0..10 {
+        assert!(arms.contains(&format!("        sub{i})\n          _mediumcmd_sub{i}\n")));
+        assert!(output.contains(&format!("_mediumcmd_sub{i}() {{\n")));
+    }
+}